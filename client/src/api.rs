@@ -0,0 +1,236 @@
+use anyhow::{anyhow, Result};
+use chess::ChessMove;
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use shared::protocol::{
+    AuthResponse, ErrorResponse, GameListResponse, GameResponse, LoginRequest, MoveResponse,
+    SubmitMoveRequest,
+};
+use shared::types::{Color, GameInfo};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Client for the server's REST API
+///
+/// The access token from `login` is kept in memory for the session only.
+pub struct ApiClient {
+    http: Client,
+    server: String,
+    token: Option<String>,
+}
+
+impl ApiClient {
+    pub fn new(server: &str) -> Self {
+        Self {
+            http: Client::new(),
+            server: server.trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    /// Log in, keeping the token for later requests
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<AuthResponse> {
+        let request = LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        let auth: AuthResponse = self
+            .send(self.http.post(self.url("/api/auth/login")).json(&request))
+            .await?;
+        self.token = Some(auth.token.clone());
+        Ok(auth)
+    }
+
+    /// The user's active games
+    pub async fn active_games(&self) -> Result<GameListResponse> {
+        self.send(self.http.get(self.url("/api/games?status=active")))
+            .await
+    }
+
+    pub async fn game(&self, game_id: Uuid) -> Result<GameResponse> {
+        self.send(self.http.get(self.url(&format!("/api/games/{}", game_id))))
+            .await
+    }
+
+    /// Submit a move typed as UCI ("g1f3") or SAN ("Nf3")
+    pub async fn submit_move(&self, game_id: Uuid, input: &str) -> Result<MoveResponse> {
+        let request = move_request(input);
+        let url = self.url(&format!("/api/games/{}/moves", game_id));
+        self.send(self.http.post(url).json(&request)).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.server, path)
+    }
+
+    /// Send a request with the session's token, decoding the reply or the
+    /// server's error message
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let request = match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+
+        if status.is_success() {
+            Ok(serde_json::from_slice(&body)?)
+        } else {
+            Err(anyhow!(error_message(status.as_u16(), &body)))
+        }
+    }
+}
+
+/// Body for submitting `input`, sent as UCI if it parses as UCI, else as SAN
+pub fn move_request(input: &str) -> SubmitMoveRequest {
+    let input = input.trim();
+    let is_uci = ChessMove::from_str(input).is_ok();
+
+    SubmitMoveRequest {
+        move_uci: if is_uci {
+            input.to_string()
+        } else {
+            String::new()
+        },
+        move_san: (!is_uci).then(|| input.to_string()),
+        dry_run: false,
+        comment: None,
+    }
+}
+
+/// The message of an error response, or the status if the body isn't one
+pub fn error_message(status: u16, body: &[u8]) -> String {
+    match serde_json::from_slice::<ErrorResponse>(body) {
+        Ok(error) => error.error,
+        Err(_) => format!("Server returned status {}", status),
+    }
+}
+
+/// Whether it's `username`'s move in `game`
+pub fn is_your_turn(game: &GameInfo, username: &str) -> bool {
+    let on_move = match game.current_turn {
+        Color::White => &game.white_player_username,
+        Color::Black => &game.black_player_username,
+    };
+    on_move.eq_ignore_ascii_case(username)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn game_info(white: &str, black: &str, current_turn: &str) -> GameInfo {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "white_player_username": white,
+            "black_player_username": black,
+            "status": "active",
+            "current_turn": current_turn,
+            "move_deadline": "2026-10-19T12:00:00Z",
+            "created_at": "2026-10-16T12:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_move_request_uci_or_san() {
+        let uci = serde_json::to_value(move_request("g1f3")).unwrap();
+        assert_eq!(uci["move_uci"], "g1f3");
+        assert!(uci["move_san"].is_null());
+
+        let san = serde_json::to_value(move_request(" Nf3 ")).unwrap();
+        assert_eq!(san["move_uci"], "");
+        assert_eq!(san["move_san"], "Nf3");
+        assert_eq!(san["dry_run"], false);
+    }
+
+    #[test]
+    fn test_login_request_body() {
+        let body = serde_json::to_value(LoginRequest {
+            username: "alice".to_string(),
+            password: "secret".to_string(),
+        })
+        .unwrap();
+        assert_eq!(body, json!({ "username": "alice", "password": "secret" }));
+    }
+
+    #[test]
+    fn test_auth_and_game_list_responses_decode() {
+        let auth: AuthResponse = serde_json::from_value(json!({
+            "token": "jwt",
+            "refresh_token": "refresh",
+            "user": {
+                "id": Uuid::new_v4(),
+                "username": "alice",
+                "email": "alice@example.com",
+                "created_at": "2026-10-01T09:00:00Z",
+            },
+        }))
+        .unwrap();
+        assert_eq!(auth.token, "jwt");
+        assert!(!auth.user.verified);
+
+        let list: GameListResponse = serde_json::from_value(json!({
+            "games": [serde_json::to_value(game_info("alice", "bob", "white")).unwrap()],
+            "total_count": 1,
+        }))
+        .unwrap();
+        assert_eq!(list.games[0].current_turn, Color::White);
+    }
+
+    #[test]
+    fn test_game_response_decodes_position() {
+        let game_id = Uuid::new_v4();
+        let player = |name: &str| json!({ "id": Uuid::new_v4(), "username": name });
+        let response: GameResponse = serde_json::from_value(json!({
+            "game": {
+                "id": game_id,
+                "white_player_id": Uuid::new_v4(),
+                "black_player_id": Uuid::new_v4(),
+                "current_position": "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+                "status": "active",
+                "current_turn": "black",
+                "move_deadline": null,
+                "created_at": "2026-10-16T12:00:00Z",
+                "completed_at": null,
+                "takebacks_allowed": false,
+                "move_deadline_hours": 72,
+            },
+            "white_player": player("alice"),
+            "black_player": player("bob"),
+            "moves": [],
+            "your_color": "black",
+        }))
+        .unwrap();
+
+        assert_eq!(response.game.id, game_id);
+        assert_eq!(response.your_color, Some(Color::Black));
+        let board = chess::Board::from_str(&response.game.current_position).unwrap();
+        assert_eq!(board.side_to_move(), chess::Color::Black);
+    }
+
+    #[test]
+    fn test_error_message() {
+        let body = serde_json::to_vec(&ErrorResponse {
+            error: "Illegal move".to_string(),
+            details: None,
+            code: "illegal_move".to_string(),
+        })
+        .unwrap();
+        assert_eq!(error_message(400, &body), "Illegal move");
+        assert_eq!(
+            error_message(502, b"Bad Gateway"),
+            "Server returned status 502"
+        );
+    }
+
+    #[test]
+    fn test_is_your_turn() {
+        assert!(is_your_turn(&game_info("alice", "bob", "white"), "alice"));
+        assert!(is_your_turn(&game_info("alice", "bob", "black"), "Bob"));
+        assert!(!is_your_turn(&game_info("alice", "bob", "black"), "alice"));
+    }
+}
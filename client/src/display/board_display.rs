@@ -1,55 +1,12 @@
-use chess::{Board, Color, Piece, Square, File, Rank};
-
-/// Display the chess board with Unicode pieces
-pub fn display_board(board: &Board) {
-    println!("\n  a b c d e f g h");
-
-    for rank in (0..8).rev() {
-        print!("{} ", rank + 1);
-
-        for file in 0..8 {
-            let square = Square::make_square(
-                Rank::from_index(rank),
-                File::from_index(file),
-            );
-
-            let piece_str = if let Some(piece) = board.piece_on(square) {
-                let color = board.color_on(square).unwrap();
-                piece_to_unicode(piece, color)
-            } else {
-                // Checkered pattern
-                if (rank + file) % 2 == 0 {
-                    "·"
-                } else {
-                    " "
-                }
-            };
-
-            print!("{} ", piece_str);
-        }
-
-        println!("{}", rank + 1);
-    }
-
-    println!("  a b c d e f g h\n");
-}
+use chess::{Board, ChessMove, Color, Piece};
+use shared::board::render_board_highlighted;
 
-/// Convert a piece to Unicode character
-fn piece_to_unicode(piece: Piece, color: Color) -> &'static str {
-    match (piece, color) {
-        (Piece::King, Color::White) => "♔",
-        (Piece::Queen, Color::White) => "♕",
-        (Piece::Rook, Color::White) => "♖",
-        (Piece::Bishop, Color::White) => "♗",
-        (Piece::Knight, Color::White) => "♘",
-        (Piece::Pawn, Color::White) => "♙",
-        (Piece::King, Color::Black) => "♚",
-        (Piece::Queen, Color::Black) => "♛",
-        (Piece::Rook, Color::Black) => "♜",
-        (Piece::Bishop, Color::Black) => "♝",
-        (Piece::Knight, Color::Black) => "♞",
-        (Piece::Pawn, Color::Black) => "♟",
-    }
+/// Display the chess board with Unicode pieces, from Black's side when
+/// `flip` is set
+///
+/// The squares of `last_move` are marked, and so is a king in check.
+pub fn display_board_oriented(board: &Board, flip: bool, last_move: Option<ChessMove>) {
+    println!("\n{}", render_board_highlighted(board, flip, last_move));
 }
 
 /// Display the current game status
@@ -75,3 +32,127 @@ pub fn display_status(board: &Board) {
         BoardStatus::Ongoing => {}
     }
 }
+
+/// Pieces each side starts with, in the order captures are listed
+const STARTING_PIECES: [(Piece, u32); 5] = [
+    (Piece::Queen, 1),
+    (Piece::Rook, 2),
+    (Piece::Bishop, 2),
+    (Piece::Knight, 2),
+    (Piece::Pawn, 8),
+];
+
+/// Standard piece value in pawns
+fn piece_value(piece: Piece) -> u32 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight | Piece::Bishop => 3,
+        Piece::Rook => 5,
+        Piece::Queen => 9,
+        Piece::King => 0,
+    }
+}
+
+fn count(board: &Board, piece: Piece, color: Color) -> u32 {
+    (board.pieces(piece) & board.color_combined(color)).popcnt()
+}
+
+/// Total value of `color`'s pieces on the board
+pub fn material(board: &Board, color: Color) -> u32 {
+    STARTING_PIECES
+        .iter()
+        .map(|&(piece, _)| piece_value(piece) * count(board, piece, color))
+        .sum()
+}
+
+/// One line with the pieces each side has captured and who is ahead,
+/// e.g. "White took ♟♟♞, Black took ♙ (White +3)"
+///
+/// Captures are counted against the starting set, so a promoted piece can
+/// hide a captured one; the advantage is always exact.
+pub fn material_summary(board: &Board) -> String {
+    let taken_from = |color: Color| -> String {
+        STARTING_PIECES
+            .iter()
+            .flat_map(|&(piece, start)| {
+                let missing = start.saturating_sub(count(board, piece, color));
+                std::iter::repeat_n(piece_symbol(piece, color), missing as usize)
+            })
+            .collect()
+    };
+    let or_none = |taken: String| {
+        if taken.is_empty() {
+            "-".to_string()
+        } else {
+            taken
+        }
+    };
+
+    let white = material(board, Color::White) as i64;
+    let black = material(board, Color::Black) as i64;
+    let advantage = match white - black {
+        0 => "even".to_string(),
+        diff if diff > 0 => format!("White +{}", diff),
+        diff => format!("Black +{}", -diff),
+    };
+
+    format!(
+        "White took {}, Black took {} ({})",
+        or_none(taken_from(Color::Black)),
+        or_none(taken_from(Color::White)),
+        advantage
+    )
+}
+
+/// Display the captured pieces and material balance
+pub fn display_material(board: &Board) {
+    println!("Material: {}", material_summary(board));
+}
+
+fn piece_symbol(piece: Piece, color: Color) -> char {
+    let symbols = match color {
+        Color::White => ['♕', '♖', '♗', '♘', '♙'],
+        Color::Black => ['♛', '♜', '♝', '♞', '♟'],
+    };
+    match piece {
+        Piece::Queen => symbols[0],
+        Piece::Rook => symbols[1],
+        Piece::Bishop => symbols[2],
+        Piece::Knight => symbols[3],
+        _ => symbols[4],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_material_start_position() {
+        let board = Board::default();
+        assert_eq!(material(&board, Color::White), 39);
+        assert_eq!(material(&board, Color::Black), 39);
+        assert_eq!(
+            material_summary(&board),
+            "White took -, Black took - (even)"
+        );
+    }
+
+    #[test]
+    fn test_material_with_imbalance() {
+        // White is missing a pawn, Black a knight and two pawns
+        let board =
+            Board::from_str("r1bqkbnr/ppp2ppp/8/8/8/8/PPP1PPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert_eq!(material(&board, Color::White), 38);
+        assert_eq!(material(&board, Color::Black), 34);
+        assert_eq!(
+            material_summary(&board),
+            "White took ♞♟♟, Black took ♙ (White +4)"
+        );
+
+        // Rook against queen
+        let board = Board::from_str("3qk3/8/8/8/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert!(material_summary(&board).ends_with("(Black +4)"));
+    }
+}
@@ -1,46 +1,61 @@
 mod display;
 
-use chess::{Board, ChessMove, BoardStatus, MoveGen, Square, Piece, File};
+use chess::{Board, ChessMove, BoardStatus, Color, MoveGen};
+use shared::notation::{move_to_san, san_to_move, SanError};
 use std::io::{self, Write};
 use std::str::FromStr;
 
-use display::{display_board, display_status};
+use display::{display_board_oriented, display_material, display_status};
 
 fn main() {
     println!("=== Rusty Chess - Local Two Player Mode ===\n");
-    println!("Enter moves: e2e4 (UCI), e4 (pawn move), or exd5 (pawn capture)");
+    println!("Enter moves: Nf3, exd5, O-O (SAN) or g1f3 (UCI)");
     println!("Type 'quit' to exit, 'moves' to see legal moves\n");
 
-    let mut board = Board::default();
+    // Boards are immutable, so undo replays the history from `start`
+    let mut start = Board::default();
+    // Boards don't keep the FEN's move counters, so remember where `start` was
+    let mut first_move = 1;
+    let mut board = start;
     let mut move_history: Vec<String> = Vec::new();
+    // Marked on the board until the next move
+    let mut last_move: Option<ChessMove> = None;
+    // Board orientation: `flip` turns the board over, `rotate` keeps the
+    // side to move at the bottom
+    let mut flipped = false;
+    let mut rotate = false;
+
+    // Optionally start from a position given with --fen "<FEN>"
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--fen") {
+        match args.get(i + 1).map(|fen| load_fen(fen)) {
+            Some(Ok(loaded)) => {
+                start = loaded;
+                first_move = fullmove_number(&args[i + 1]);
+                board = loaded;
+            }
+            Some(Err(e)) => println!("❌ {}", e),
+            None => println!("❌ --fen requires a FEN string"),
+        }
+    }
 
     loop {
         // Display the board
-        display_board(&board);
+        display_board_oriented(&board, view_from_black(&board, flipped, rotate), last_move);
         display_status(&board);
+        display_material(&board);
 
         // Check if game is over
-        match board.status() {
-            BoardStatus::Checkmate | BoardStatus::Stalemate => {
-                println!("\nGame Over!");
-                println!("\nMove history:");
-                for (i, mv) in move_history.iter().enumerate() {
-                    if i % 2 == 0 {
-                        print!("{}. {} ", (i / 2) + 1, mv);
-                    } else {
-                        println!("{}", mv);
-                    }
-                }
-                if move_history.len() % 2 == 1 {
-                    println!();
-                }
-                break;
-            }
-            BoardStatus::Ongoing => {}
+        let game_over = board.status() != BoardStatus::Ongoing;
+        if game_over {
+            println!("\nGame Over!");
+            println!("\nMove history:");
+            print!("{}", format_history(&start, first_move, &move_history));
+            println!("\nType 'undo' to take back the last move, or press Enter to exit.");
         }
 
         // Get move input
-        print!("Enter move: ");
+        print!("{}", if game_over { "> " } else { "Enter move: " });
         io::stdout().flush().unwrap();
 
         let mut input = String::new();
@@ -48,7 +63,23 @@ fn main() {
         let input = input.trim();
 
         // Handle special commands
-        if input.eq_ignore_ascii_case("quit") || input.eq_ignore_ascii_case("exit") {
+        if input.eq_ignore_ascii_case("undo") {
+            match move_history.pop() {
+                Some(san) => {
+                    board = replay(&start, &move_history)
+                        .expect("history only holds moves played from the start position");
+                    last_move = last_played(&start, &move_history);
+                    println!("Took back {}.", san);
+                }
+                None => println!("Nothing to undo."),
+            }
+            continue;
+        }
+
+        if game_over
+            || input.eq_ignore_ascii_case("quit")
+            || input.eq_ignore_ascii_case("exit")
+        {
             println!("Thanks for playing!");
             break;
         }
@@ -58,92 +89,138 @@ fn main() {
             continue;
         }
 
+        if input.eq_ignore_ascii_case("flip") {
+            flipped = !flipped;
+            continue;
+        }
+
+        if input.eq_ignore_ascii_case("rotate") {
+            rotate = !rotate;
+            println!("Auto-rotate {}.", if rotate { "on" } else { "off" });
+            continue;
+        }
+
         if input.eq_ignore_ascii_case("help") {
             show_help();
             continue;
         }
 
-        // Try to parse and make the move
-        let chess_move = match ChessMove::from_str(input) {
-            Ok(mv) => Some(mv),
-            Err(_) => parse_short_notation(input, &board),
-        };
-
-        match chess_move {
-            Some(chess_move) => {
-                if board.legal(chess_move) {
-                    move_history.push(input.to_string());
-                    board = board.make_move_new(chess_move);
-                } else {
-                    println!("❌ Illegal move! Try again.");
+        if let Some(fen) = strip_command(input, "setfen") {
+            match load_fen(fen) {
+                Ok(loaded) => {
+                    start = loaded;
+                    first_move = fullmove_number(fen);
+                    board = loaded;
+                    move_history.clear();
+                    last_move = None;
+                    println!("Position loaded.");
                 }
+                Err(e) => println!("❌ {}", e),
             }
-            None => {
-                println!("❌ Invalid move format! Use UCI notation (e.g., e2e4) or short notation (e.g., e4)");
+            continue;
+        }
+
+        // Try to parse and make the move
+        match parse_move(input, &board) {
+            Ok(chess_move) => {
+                // Record SAN whatever the input was, so the log reads naturally
+                let san = move_to_san(&board, chess_move)
+                    .unwrap_or_else(|_| chess_move.to_string());
+                move_history.push(san);
+                board = board.make_move_new(chess_move);
+                last_move = Some(chess_move);
             }
+            Err(e) => println!("❌ {}", e),
         }
     }
 }
 
-fn parse_short_notation(input: &str, board: &Board) -> Option<ChessMove> {
-    let (source_file, dest_square) = match input.len() {
-        // Regular pawn move: "e4"
-        2 => {
-            let dest = Square::from_str(input).ok()?;
-            (None, dest)
+/// Move list for the SAN `moves` played from `start`, one numbered line per
+/// move pair counting on from `first_move`; starts "N..." if Black moved first
+fn format_history(start: &Board, first_move: u32, moves: &[String]) -> String {
+    let mut history = String::new();
+    let mut number = first_move;
+    let mut moves = moves.iter().map(String::as_str);
+    if start.side_to_move() == Color::Black {
+        if let Some(san) = moves.next() {
+            history.push_str(&format!("{}... {}\n", number, san));
+            number += 1;
         }
-        // Pawn capture: "exd5" (4 characters: e + x + d + 5)
-        4 => {
-            if input.chars().nth(1)? != 'x' {
-                return None;
-            }
-            let file_char = input.chars().next()?;
-            let file = match file_char {
-                'a' => File::A,
-                'b' => File::B,
-                'c' => File::C,
-                'd' => File::D,
-                'e' => File::E,
-                'f' => File::F,
-                'g' => File::G,
-                'h' => File::H,
-                _ => return None,
-            };
-            let dest = Square::from_str(&input[2..]).ok()?;
-            (Some(file), dest)
-        }
-        _ => return None,
-    };
-
-    // Find all legal pawn moves to this square
-    let legal_moves: Vec<ChessMove> = MoveGen::new_legal(board)
-        .filter(|mv| {
-            // Check if this move ends at the destination square
-            if mv.get_dest() != dest_square {
-                return false;
-            }
-            // Check if it's a pawn move
-            if board.piece_on(mv.get_source()) != Some(Piece::Pawn) {
-                return false;
-            }
-            // If source file specified, check it matches
-            if let Some(file) = source_file {
-                if mv.get_source().get_file() != file {
-                    return false;
-                }
-            }
-            true
-        })
-        .collect();
+    }
+    let rest: Vec<&str> = moves.collect();
+    for pair in rest.chunks(2) {
+        history.push_str(&format!("{}. {}\n", number, pair.join(" ")));
+        number += 1;
+    }
+    history
+}
+
+/// Board reached by playing the SAN `moves` from `start`
+fn replay(start: &Board, moves: &[String]) -> Result<Board, SanError> {
+    moves.iter().try_fold(*start, |board, san| {
+        Ok(board.make_move_new(san_to_move(&board, san)?))
+    })
+}
+
+/// The last of the SAN `moves` played from `start`, if any
+fn last_played(start: &Board, moves: &[String]) -> Option<ChessMove> {
+    let (last, earlier) = moves.split_last()?;
+    let board = replay(start, earlier).ok()?;
+    san_to_move(&board, last).ok()
+}
+
+/// Whether to draw the board from Black's side
+///
+/// With `rotate` on the side to move is at the bottom; `flip` turns
+/// whatever that gives upside down.
+fn view_from_black(board: &Board, flipped: bool, rotate: bool) -> bool {
+    let black_to_move = rotate && board.side_to_move() == Color::Black;
+    black_to_move != flipped
+}
+
+/// The fullmove number from a FEN, 1 if it has none
+fn fullmove_number(fen: &str) -> u32 {
+    fen.split_whitespace()
+        .nth(5)
+        .and_then(|f| f.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(1)
+}
+
+/// Parse a FEN into a board, for starting from an arbitrary position
+fn load_fen(fen: &str) -> Result<Board, String> {
+    let fen = fen.trim();
+    Board::from_str(fen).map_err(|_| format!("Invalid FEN: {}", fen))
+}
 
-    // If exactly one pawn can move there, use it
-    if legal_moves.len() == 1 {
-        Some(legal_moves[0])
+/// Return the argument of `command` if the input starts with it (case-insensitive)
+fn strip_command<'a>(input: &'a str, command: &str) -> Option<&'a str> {
+    let (name, rest) = input.split_once(char::is_whitespace)?;
+    if name.eq_ignore_ascii_case(command) {
+        Some(rest)
     } else {
         None
     }
 }
 
+/// Parse a move typed as UCI ("g1f3") or SAN ("Nf3", "exd5", "O-O")
+fn parse_move(input: &str, board: &Board) -> Result<ChessMove, String> {
+    if let Ok(chess_move) = ChessMove::from_str(input) {
+        return if board.legal(chess_move) {
+            Ok(chess_move)
+        } else {
+            Err("Illegal move! Try again.".to_string())
+        };
+    }
+
+    san_to_move(board, input).map_err(|e| match e {
+        SanError::Ambiguous { candidates, .. } => {
+            format!("Ambiguous move! Did you mean: {}?", candidates.join(", "))
+        }
+        _ => "Invalid or illegal move! Use SAN (e.g., Nf3, O-O) or UCI (e.g., g1f3)".to_string(),
+    })
+}
+
 fn show_legal_moves(board: &Board) {
     println!("\nLegal moves:");
     let mut moves: Vec<String> = MoveGen::new_legal(board)
@@ -162,16 +239,169 @@ fn show_legal_moves(board: &Board) {
 
 fn show_help() {
     println!("\n=== Help ===");
-    println!("Enter moves in UCI format:");
+    println!("Enter moves in SAN:");
+    println!("  - Piece move: Nf3, Bxe5 (add a file or rank if needed: Nbd2, R1e1)");
+    println!("  - Pawn move: e4, capture: exd5");
+    println!("  - Castling: O-O (kingside), O-O-O (queenside)");
+    println!("  - Promotion: e8=Q");
+    println!("Or in UCI format:");
     println!("  - Normal move: e2e4 (from e2 to e4)");
-    println!("  - Short notation: e4 (pawn moves only)");
-    println!("  - Pawn capture: exd5 (pawn on e-file captures on d5)");
     println!("  - Castling: e1g1 (kingside), e1c1 (queenside)");
     println!("  - Promotion: e7e8q (promote to queen)");
     println!("    Promotion pieces: q=queen, r=rook, b=bishop, n=knight");
     println!("\nCommands:");
     println!("  moves - Show all legal moves");
+    println!("  undo  - Take back the last move (repeat to go further back)");
+    println!("  setfen <FEN> - Load a position (clears move history)");
+    println!("  flip  - Turn the board over");
+    println!("  rotate - Toggle keeping the side to move at the bottom");
     println!("  help  - Show this help");
     println!("  quit  - Exit the game");
     println!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Piece, Square};
+
+    #[test]
+    fn test_load_fen_accepts_valid_position() {
+        let fen = "8/8/8/4k3/8/8/4P3/4K3 w - - 0 1";
+        let board = load_fen(fen).unwrap();
+        assert_eq!(board.piece_on(Square::from_str("e2").unwrap()), Some(Piece::Pawn));
+    }
+
+    #[test]
+    fn test_load_fen_rejects_malformed_position() {
+        assert!(load_fen("not a fen").is_err());
+        assert!(load_fen("rnbqkbnr/pppppppp/8/8 w KQkq - 0 1").is_err());
+    }
+
+    #[test]
+    fn test_view_from_black() {
+        let white_to_move = Board::default();
+        let black_to_move = load_fen("8/8/8/4k3/8/8/4P3/4K3 b - - 0 1").unwrap();
+
+        assert!(!view_from_black(&black_to_move, false, false));
+        assert!(view_from_black(&white_to_move, true, false));
+
+        assert!(!view_from_black(&white_to_move, false, true));
+        assert!(view_from_black(&black_to_move, false, true));
+        assert!(!view_from_black(&black_to_move, true, true));
+    }
+
+    #[test]
+    fn test_parse_move_uci_and_san() {
+        let board = Board::default();
+        let nf3 = ChessMove::from_str("g1f3").unwrap();
+
+        assert_eq!(parse_move("g1f3", &board), Ok(nf3));
+        assert_eq!(parse_move("Nf3", &board), Ok(nf3));
+        assert_eq!(parse_move("e4", &board), Ok(ChessMove::from_str("e2e4").unwrap()));
+        assert!(parse_move("e2e5", &board).is_err());
+        assert!(parse_move("Nf6", &board).is_err());
+    }
+
+    #[test]
+    fn test_parse_move_castling_and_suffixes() {
+        let board = load_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(parse_move("O-O", &board), Ok(ChessMove::from_str("e1g1").unwrap()));
+        assert_eq!(parse_move("0-0-0", &board), Ok(ChessMove::from_str("e1c1").unwrap()));
+
+        let board = load_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(parse_move("Ra8#", &board), Ok(ChessMove::from_str("a1a8").unwrap()));
+        assert_eq!(parse_move("Ra8+", &board), Ok(ChessMove::from_str("a1a8").unwrap()));
+    }
+
+    #[test]
+    fn test_parse_move_lists_ambiguous_candidates() {
+        let board = load_fen("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1").unwrap();
+        let err = parse_move("Nd2", &board).unwrap_err();
+        assert!(err.contains("Nbd2, Nfd2"), "{}", err);
+    }
+
+    #[test]
+    fn test_replay_after_undo() {
+        let start = Board::default();
+        let mut history: Vec<String> = ["f3", "e5", "g4", "Qh4#"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let mated = replay(&start, &history).unwrap();
+        assert_eq!(mated.status(), BoardStatus::Checkmate);
+
+        // Taking back the mate resumes the game with Black to move
+        history.pop();
+        let resumed = replay(&start, &history).unwrap();
+        assert_eq!(resumed.status(), BoardStatus::Ongoing);
+        assert_eq!(resumed.side_to_move(), Color::Black);
+
+        history.clear();
+        assert_eq!(replay(&start, &history).unwrap(), start);
+    }
+
+    #[test]
+    fn test_last_played_after_undo() {
+        let start = Board::default();
+        let mut history: Vec<String> = ["e4", "e5", "Nf3"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(last_played(&start, &history), Some(ChessMove::from_str("g1f3").unwrap()));
+
+        history.pop();
+        assert_eq!(last_played(&start, &history), Some(ChessMove::from_str("e7e5").unwrap()));
+
+        history.clear();
+        assert_eq!(last_played(&start, &history), None);
+    }
+
+    #[test]
+    fn test_replay_from_loaded_position() {
+        let start = load_fen("8/8/8/4k3/8/8/4P3/4K3 w - - 0 1").unwrap();
+        let board = replay(&start, &["e4".to_string(), "Kd6".to_string()]).unwrap();
+        assert_eq!(board.piece_on(Square::from_str("e4").unwrap()), Some(Piece::Pawn));
+    }
+
+    #[test]
+    fn test_format_history_from_the_start() {
+        let moves: Vec<String> = ["f3", "e5", "g4", "Qh4#"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            format_history(&Board::default(), 1, &moves),
+            "1. f3 e5\n2. g4 Qh4#\n"
+        );
+        assert_eq!(
+            format_history(&Board::default(), 1, &moves[..3]),
+            "1. f3 e5\n2. g4\n"
+        );
+    }
+
+    #[test]
+    fn test_format_history_numbers_on_from_the_fen() {
+        // Black to move after 1. e4 e5 ... 10. Bc4
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 10";
+        let start = load_fen(fen).unwrap();
+        let moves: Vec<String> = ["Nf6", "Ng5", "d5"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            format_history(&start, fullmove_number(fen), &moves),
+            "10... Nf6\n11. Ng5 d5\n"
+        );
+    }
+
+    #[test]
+    fn test_fullmove_number() {
+        assert_eq!(fullmove_number("8/8/8/4k3/8/8/4P3/4K3 b - - 0 42"), 42);
+        assert_eq!(fullmove_number("8/8/8/4k3/8/8/4P3/4K3 w - -"), 1);
+        assert_eq!(fullmove_number("8/8/8/4k3/8/8/4P3/4K3 w - - 0 0"), 1);
+    }
+
+    #[test]
+    fn test_strip_command() {
+        assert_eq!(strip_command("setfen 8/8 w - - 0 1", "setfen"), Some("8/8 w - - 0 1"));
+        assert_eq!(strip_command("SETFEN x", "setfen"), Some("x"));
+        assert_eq!(strip_command("setfen", "setfen"), None);
+        assert_eq!(strip_command("e2e4", "setfen"), None);
+    }
+}
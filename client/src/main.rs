@@ -1,3 +1,202 @@
-fn main() {
-    println!("Rusty Chess Client - Starting soon!");
+mod api;
+mod display;
+
+use anyhow::Result;
+use chess::{Board, ChessMove};
+use shared::protocol::GameResponse;
+use shared::types::{Color, GameInfo};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use api::{is_your_turn, ApiClient};
+use display::{display_board_oriented, display_material, display_status};
+
+/// Where the server listens unless `--server` says otherwise
+const DEFAULT_SERVER: &str = "http://127.0.0.1:8080";
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("online") => {
+            let server = server_url(&args).unwrap_or_else(|| DEFAULT_SERVER.to_string());
+            if let Err(e) = play_online(&server).await {
+                println!("❌ {}", e);
+            }
+        }
+        _ => {
+            println!("Usage: rusty-chess-client online [--server URL]");
+            println!("For two players on one machine, run rusty-chess-local");
+        }
+    }
+}
+
+/// The URL given with `--server URL`, if any
+fn server_url(args: &[String]) -> Option<String> {
+    let i = args.iter().position(|arg| arg == "--server")?;
+    args.get(i + 1).cloned()
+}
+
+/// Log in, then pick games and move in them until the user quits
+async fn play_online(server: &str) -> Result<()> {
+    println!("=== Rusty Chess - Online ({}) ===\n", server);
+
+    let mut client = ApiClient::new(server);
+    let username = prompt("Username: ");
+    let password = prompt("Password: ");
+    let auth = client.login(&username, &password).await?;
+    let username = auth.user.username;
+    println!("Logged in as {}.", username);
+
+    loop {
+        let games = client.active_games().await?.games;
+        list_games(&games, &username);
+
+        let input = prompt("\nGame number (Enter to refresh, 'quit' to exit): ");
+        if input.eq_ignore_ascii_case("quit") || input.eq_ignore_ascii_case("exit") {
+            println!("Thanks for playing!");
+            return Ok(());
+        }
+        if input.is_empty() {
+            continue;
+        }
+
+        let Some(game) = pick_game(&games, &input) else {
+            println!("❌ No game {}.", input);
+            continue;
+        };
+        if !is_your_turn(game, &username) {
+            println!("Waiting for your opponent to move.");
+            continue;
+        }
+
+        if let Err(e) = play_turn(&client, game).await {
+            println!("❌ {}", e);
+        }
+    }
+}
+
+/// Show a game's board and submit the user's move in it
+async fn play_turn(client: &ApiClient, game: &GameInfo) -> Result<()> {
+    let response = client.game(game.id).await?;
+    let board = Board::from_str(&response.game.current_position)
+        .map_err(|_| anyhow::anyhow!("Server sent an invalid position"))?;
+
+    let flip = response.your_color == Some(Color::Black);
+    display_board_oriented(&board, flip, last_move(&response));
+    display_status(&board);
+    display_material(&board);
+
+    loop {
+        let input = prompt("Your move (SAN or UCI, Enter to go back): ");
+        if input.is_empty() {
+            return Ok(());
+        }
+
+        match client.submit_move(game.id, &input).await {
+            Ok(played) => {
+                println!("Played {}.", played.r#move.move_san);
+                return Ok(());
+            }
+            Err(e) => println!("❌ {}", e),
+        }
+    }
+}
+
+fn list_games(games: &[GameInfo], username: &str) {
+    if games.is_empty() {
+        println!("\nNo active games.");
+        return;
+    }
+
+    println!("\nActive games:");
+    for (i, game) in games.iter().enumerate() {
+        let marker = if is_your_turn(game, username) {
+            "your move"
+        } else {
+            "waiting"
+        };
+        println!(
+            "  {}. {} vs {} ({})",
+            i + 1,
+            game.white_player_username,
+            game.black_player_username,
+            marker
+        );
+    }
+}
+
+/// The game numbered `input` in the list, counting from 1
+fn pick_game<'a>(games: &'a [GameInfo], input: &str) -> Option<&'a GameInfo> {
+    let number: usize = input.trim().parse().ok()?;
+    games.get(number.checked_sub(1)?)
+}
+
+/// The game's last move, to mark on the board
+fn last_move(response: &GameResponse) -> Option<ChessMove> {
+    let last = response.moves.last()?;
+    ChessMove::from_str(&last.move_uci).ok()
+}
+
+fn prompt(message: &str) -> String {
+    print!("{}", message);
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn games() -> Vec<GameInfo> {
+        ["alice", "carol"]
+            .iter()
+            .map(|white| {
+                serde_json::from_value(json!({
+                    "id": Uuid::new_v4(),
+                    "white_player_username": white,
+                    "black_player_username": "bob",
+                    "status": "active",
+                    "current_turn": "white",
+                    "move_deadline": null,
+                    "created_at": "2026-10-16T12:00:00Z",
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_server_url() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            server_url(&args(&["online", "--server", "http://chess.example:9000"])),
+            Some("http://chess.example:9000".to_string())
+        );
+        assert_eq!(server_url(&args(&["online"])), None);
+        assert_eq!(server_url(&args(&["online", "--server"])), None);
+    }
+
+    #[test]
+    fn test_pick_game_counts_from_one() {
+        let games = games();
+        assert_eq!(
+            pick_game(&games, "1").unwrap().white_player_username,
+            "alice"
+        );
+        assert_eq!(
+            pick_game(&games, " 2 ").unwrap().white_player_username,
+            "carol"
+        );
+        assert!(pick_game(&games, "0").is_none());
+        assert!(pick_game(&games, "3").is_none());
+        assert!(pick_game(&games, "e4").is_none());
+    }
 }
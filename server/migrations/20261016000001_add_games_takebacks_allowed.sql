@@ -0,0 +1,2 @@
+-- Allow takebacks to be enabled per game
+ALTER TABLE games ADD COLUMN takebacks_allowed BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,7 @@
+-- Email changes awaiting confirmation (one outstanding request per user)
+CREATE TABLE IF NOT EXISTS pending_email_changes (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    new_email VARCHAR(255) NOT NULL,
+    token VARCHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
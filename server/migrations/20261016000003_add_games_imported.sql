@@ -0,0 +1,7 @@
+-- Games imported from PGN for analysis. The importer owns both sides, so
+-- these are exempt from the different-players rule.
+ALTER TABLE games ADD COLUMN imported BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE games DROP CONSTRAINT different_players;
+ALTER TABLE games ADD CONSTRAINT different_players
+    CHECK (white_player_id != black_player_id OR imported);
@@ -0,0 +1,2 @@
+-- Seconds the player spent on each move (NULL for imported moves)
+ALTER TABLE moves ADD COLUMN time_taken_seconds BIGINT;
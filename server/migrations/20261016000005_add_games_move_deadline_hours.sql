@@ -0,0 +1,2 @@
+-- Time control chosen per game; existing games keep the default pace
+ALTER TABLE games ADD COLUMN move_deadline_hours BIGINT NOT NULL DEFAULT 72;
@@ -0,0 +1,7 @@
+-- Usernames keep the case they were registered with, but are unique and
+-- looked up case-insensitively through this column
+ALTER TABLE users ADD COLUMN username_lower VARCHAR(50);
+UPDATE users SET username_lower = LOWER(username);
+ALTER TABLE users ALTER COLUMN username_lower SET NOT NULL;
+
+CREATE UNIQUE INDEX idx_users_username_lower ON users(username_lower);
@@ -0,0 +1,2 @@
+-- Public games can be watched by anyone through /api/games/{id}/public
+ALTER TABLE games ADD COLUMN is_public BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,17 @@
+-- Game invitations; the game is only created once the challenged player accepts
+CREATE TABLE IF NOT EXISTS challenges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    challenger_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    challenged_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    color_preference VARCHAR(10), -- challenger's color, NULL for random
+    takebacks_allowed BOOLEAN,
+    time_control VARCHAR(20),
+    is_public BOOLEAN NOT NULL DEFAULT FALSE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, accepted, declined
+    game_id UUID REFERENCES games(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    responded_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_challenges_challenger_id ON challenges(challenger_id);
+CREATE INDEX idx_challenges_challenged_id ON challenges(challenged_id);
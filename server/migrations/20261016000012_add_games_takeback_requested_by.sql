@@ -0,0 +1,2 @@
+-- The player asking to take back their last move, until the opponent answers
+ALTER TABLE games ADD COLUMN takeback_requested_by UUID REFERENCES users(id);
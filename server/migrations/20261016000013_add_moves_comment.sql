@@ -0,0 +1,2 @@
+-- Optional comment a player attaches to their move, e.g. in teaching games
+ALTER TABLE moves ADD COLUMN comment TEXT;
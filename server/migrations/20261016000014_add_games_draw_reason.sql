@@ -0,0 +1,3 @@
+-- Why a drawn game was drawn: stalemate, threefold, fifty_move,
+-- insufficient_material or agreement (NULL when unknown, e.g. imported games)
+ALTER TABLE games ADD COLUMN draw_reason VARCHAR(30);
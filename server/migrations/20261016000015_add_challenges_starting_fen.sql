@@ -0,0 +1,2 @@
+-- Position the challenged game starts from, NULL for the standard start
+ALTER TABLE challenges ADD COLUMN starting_fen TEXT;
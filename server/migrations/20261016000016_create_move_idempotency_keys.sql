@@ -0,0 +1,14 @@
+-- Responses to moves submitted with an Idempotency-Key header, so a retried
+-- request gets the original answer instead of playing the move again.
+-- Keys are only honoured for a day.
+CREATE TABLE IF NOT EXISTS move_idempotency_keys (
+    idempotency_key VARCHAR(255) NOT NULL,
+    game_id UUID NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    move_id UUID NOT NULL REFERENCES moves(id) ON DELETE CASCADE,
+    response JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (idempotency_key, game_id, user_id)
+);
+
+CREATE INDEX idx_move_idempotency_keys_created_at ON move_idempotency_keys(created_at);
@@ -0,0 +1,2 @@
+-- Bumped whenever the position changes, so concurrent moves can't both apply
+ALTER TABLE games ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Marks the move that ended its game (mate or a draw), so clients can badge
+-- it. Games lost on time or by resignation have no such move, and moves
+-- played before this column existed are left unmarked.
+ALTER TABLE moves ADD COLUMN is_terminal BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,12 @@
+-- Replies a player has entered in advance: when the game reaches
+-- position_fen (normalized, without move counters) with them to move,
+-- move_uci is played for them.
+CREATE TABLE IF NOT EXISTS conditional_moves (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    game_id UUID NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    position_fen VARCHAR(100) NOT NULL,
+    move_uci VARCHAR(10) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (game_id, user_id, position_fen)
+);
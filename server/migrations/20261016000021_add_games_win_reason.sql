@@ -0,0 +1,3 @@
+-- How a decisive game was won: checkmate, resignation or timeout (NULL when
+-- unknown, e.g. imported games or games finished before this column existed)
+ALTER TABLE games ADD COLUMN win_reason VARCHAR(30);
@@ -0,0 +1,4 @@
+-- Finds an active game between two players whichever of them is White
+CREATE INDEX idx_games_active_pair
+    ON games (LEAST(white_player_id, black_player_id), GREATEST(white_player_id, black_player_id))
+    WHERE status = 'active';
@@ -0,0 +1,121 @@
+use anyhow::{anyhow, Result};
+use chess::{Board, Color, Piece, ALL_PIECES};
+use std::str::FromStr;
+
+/// Pieces a pawn can promote to, in the order captures are listed
+const PROMOTION_PIECES: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+
+/// Standard piece value in centipawns (kings aren't counted)
+pub fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 100,
+        Piece::Knight => 300,
+        Piece::Bishop => 300,
+        Piece::Rook => 500,
+        Piece::Queen => 900,
+        Piece::King => 0,
+    }
+}
+
+/// Material balance in centipawns, positive when White is ahead
+pub fn material_eval(board: &Board) -> i32 {
+    ALL_PIECES
+        .iter()
+        .map(|&piece| {
+            let white = (board.pieces(piece) & board.color_combined(Color::White)).popcnt() as i32;
+            let black = (board.pieces(piece) & board.color_combined(Color::Black)).popcnt() as i32;
+            piece_value(piece) * (white - black)
+        })
+        .sum()
+}
+
+/// The pieces each side has captured, as (taken by White, taken by Black)
+///
+/// Worked out from material alone by comparing `current_fen` with
+/// `start_fen`. A side with more of a piece than it started with promoted a
+/// pawn to it, so that pawn isn't counted as captured. Each list runs from
+/// queens down to pawns.
+pub fn captured_pieces(start_fen: &str, current_fen: &str) -> Result<(Vec<Piece>, Vec<Piece>)> {
+    let start = Board::from_str(start_fen).map_err(|e| anyhow!("Invalid FEN: {}", e))?;
+    let current = Board::from_str(current_fen).map_err(|e| anyhow!("Invalid FEN: {}", e))?;
+
+    Ok((
+        lost_pieces(&start, &current, Color::Black),
+        lost_pieces(&start, &current, Color::White),
+    ))
+}
+
+/// The pieces `color` had in `start` that are gone in `current`
+fn lost_pieces(start: &Board, current: &Board, color: Color) -> Vec<Piece> {
+    let count = |board: &Board, piece: Piece| {
+        (board.pieces(piece) & board.color_combined(color)).popcnt() as usize
+    };
+
+    let mut lost = Vec::new();
+    let mut promoted = 0;
+    for piece in PROMOTION_PIECES {
+        let (before, now) = (count(start, piece), count(current, piece));
+        lost.extend(std::iter::repeat_n(piece, before.saturating_sub(now)));
+        promoted += now.saturating_sub(before);
+    }
+
+    let pawns_gone = count(start, Piece::Pawn).saturating_sub(count(current, Piece::Pawn));
+    let pawns_captured = pawns_gone.saturating_sub(promoted);
+    lost.extend(std::iter::repeat_n(Piece::Pawn, pawns_captured));
+    lost
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_start_position_is_level() {
+        assert_eq!(material_eval(&Board::default()), 0);
+    }
+
+    #[test]
+    fn test_white_up_a_queen() {
+        let board =
+            Board::from_str("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert_eq!(material_eval(&board), 900);
+    }
+
+    #[test]
+    fn test_black_up_a_rook_and_pawn() {
+        let board = Board::from_str("4k3/pp6/8/8/8/8/P7/4K2r w - - 0 1").unwrap();
+        assert_eq!(material_eval(&board), -600);
+    }
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn test_nothing_captured_at_the_start() {
+        assert_eq!(captured_pieces(START, START).unwrap(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_captures_listed_for_the_capturing_side() {
+        // White has lost a knight and a pawn, Black a queen
+        let current = "rnb1kbnr/pppppppp/8/8/8/8/1PPPPPPP/R1BQKBNR w KQkq - 0 6";
+        let (by_white, by_black) = captured_pieces(START, current).unwrap();
+        assert_eq!(by_white, vec![Piece::Queen]);
+        assert_eq!(by_black, vec![Piece::Knight, Piece::Pawn]);
+    }
+
+    #[test]
+    fn test_promoted_pawn_is_not_a_capture() {
+        // White's a-pawn took the a8 rook and became a second queen; Black's
+        // h-pawn took the h1 rook and became a third knight
+        let current = "Qnbqkbnr/p1ppppp1/8/8/8/8/1PPPPPPP/RNBQKBNn w Qk - 0 20";
+        let (by_white, by_black) = captured_pieces(START, current).unwrap();
+        assert_eq!(by_white, vec![Piece::Rook, Piece::Pawn]);
+        assert_eq!(by_black, vec![Piece::Rook]);
+    }
+
+    #[test]
+    fn test_invalid_fen_is_an_error() {
+        assert!(captured_pieces(START, "not a fen").is_err());
+    }
+}
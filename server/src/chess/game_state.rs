@@ -1,4 +1,4 @@
-use chess::{Board, BoardStatus, ChessMove, Color as ChessColor, Square, Piece, MoveGen};
+use chess::{Board, BoardStatus, ChessMove, Color as ChessColor, Square, Piece, MoveGen, Rank};
 use serde::{Deserialize, Serialize};
 use shared::types::Color;
 use std::str::FromStr;
@@ -19,10 +19,15 @@ impl GameState {
     }
 
     /// Create a GameState from a FEN string
+    ///
+    /// The FEN must parse and describe a position that could come up in a
+    /// game (see `validate_position`).
     pub fn from_fen(fen: &str) -> Result<Self> {
-        // Validate FEN by parsing it
-        Board::from_str(fen)
-            .map_err(|_| anyhow!("Invalid FEN string"))?;
+        let board = Board::from_str(fen).map_err(|e| match e {
+            chess::Error::InvalidBoard => anyhow!("Illegal position in FEN"),
+            _ => anyhow!("Invalid FEN string"),
+        })?;
+        validate_position(&board)?;
 
         Ok(Self {
             fen: fen.to_string(),
@@ -76,18 +81,75 @@ impl GameState {
 
         Ok((
             GameState {
-                fen: new_board.to_string(),
+                fen: self.next_fen(&board, chess_move, &new_board),
             },
             san,
         ))
     }
 
+    /// FEN of `new_board`, reached by playing `chess_move` on `board`
+    ///
+    /// The chess crate only writes an en passant square when a capture is
+    /// possible and always writes the clocks as "0 1", so both are filled in
+    /// here: the square behind any double pawn push, the halfmove clock reset
+    /// by pawn moves and captures, and the fullmove number carried on from
+    /// this position.
+    fn next_fen(&self, board: &Board, chess_move: ChessMove, new_board: &Board) -> String {
+        let source = chess_move.get_source();
+        let dest = chess_move.get_dest();
+        let pawn_move = board.piece_on(source) == Some(Piece::Pawn);
+        // En passant lands on an empty square, but is still a pawn move
+        let capture = board.piece_on(dest).is_some();
+
+        let (from_rank, to_rank) = (source.get_rank().to_index(), dest.get_rank().to_index());
+        let en_passant = if pawn_move && from_rank.abs_diff(to_rank) == 2 {
+            let behind = Rank::from_index((from_rank + to_rank) / 2);
+            Square::make_square(behind, dest.get_file()).to_string()
+        } else {
+            "-".to_string()
+        };
+
+        let (halfmove, fullmove) = clocks(&self.fen);
+        let halfmove = if pawn_move || capture {
+            0
+        } else {
+            halfmove + 1
+        };
+        let fullmove = if board.side_to_move() == ChessColor::Black {
+            fullmove + 1
+        } else {
+            fullmove
+        };
+
+        // Placement, side to move and castling rights come from the crate
+        let crate_fen = new_board.to_string();
+        let position: Vec<&str> = crate_fen.split_whitespace().take(3).collect();
+        format!("{} {en_passant} {halfmove} {fullmove}", position.join(" "))
+    }
+
+    /// The fullmove number from the FEN, 1 if it has none
+    pub fn fullmove_number(&self) -> u32 {
+        clocks(&self.fen).1
+    }
+
+    /// Resolve a SAN move in this position to UCI
+    pub fn san_to_uci(&self, san: &str) -> Result<String> {
+        let board = self.board()?;
+        let chess_move = super::notation::san_to_move(&board, san)?;
+        Ok(chess_move.to_string())
+    }
+
     /// Get the game status
     pub fn status(&self) -> Result<BoardStatus> {
         let board = self.board()?;
         Ok(board.status())
     }
 
+    /// Whether the side to move is in check
+    pub fn in_check(&self) -> Result<bool> {
+        Ok(self.board()?.checkers().popcnt() > 0)
+    }
+
     /// Check if the game is over
     pub fn is_game_over(&self) -> Result<bool> {
         Ok(self.status()? != BoardStatus::Ongoing)
@@ -102,6 +164,30 @@ impl GameState {
         Ok(moves)
     }
 
+    /// Get the legal moves (UCI) of the piece on `square`, e.g. "e2"
+    pub fn legal_moves_from(&self, square: &str) -> Result<Vec<String>> {
+        let source = Square::from_str(square).map_err(|_| anyhow!("Invalid square"))?;
+        let board = self.board()?;
+        let moves: Vec<String> = MoveGen::new_legal(&board)
+            .filter(|m| m.get_source() == source)
+            .map(|m| m.to_string())
+            .collect();
+        Ok(moves)
+    }
+
+    /// Get all legal moves as (UCI, SAN) pairs
+    pub fn legal_moves_san(&self) -> Result<Vec<(String, String)>> {
+        let board = self.board()?;
+        MoveGen::new_legal(&board)
+            .map(|m| Ok((m.to_string(), super::notation::move_to_san(&board, m)?)))
+            .collect()
+    }
+
+    /// The position without its move counters, for comparing positions
+    pub fn position_key(&self) -> String {
+        normalize_fen(&self.fen)
+    }
+
     /// Get piece at a square
     pub fn piece_at(&self, square: Square) -> Result<Option<(Piece, ChessColor)>> {
         let board = self.board()?;
@@ -117,10 +203,142 @@ impl Default for GameState {
     }
 }
 
+/// Check that a position could come up in a game: one king per side, no
+/// pawns on the first or last rank, and the side that just moved not left
+/// in check
+///
+/// The chess crate refuses most such boards when parsing, but without
+/// saying why; this spells the rules out.
+pub fn validate_position(board: &Board) -> Result<()> {
+    for color in [ChessColor::White, ChessColor::Black] {
+        let kings = (board.pieces(Piece::King) & board.color_combined(color)).popcnt();
+        if kings != 1 {
+            return Err(anyhow!(
+                "{:?} must have exactly one king, found {}",
+                color,
+                kings
+            ));
+        }
+    }
+
+    let back_ranks = chess::get_rank(Rank::First) | chess::get_rank(Rank::Eighth);
+    if (board.pieces(Piece::Pawn) & back_ranks).popcnt() > 0 {
+        return Err(anyhow!("Pawns can't stand on the first or last rank"));
+    }
+
+    // Passing the move shows whether the side not to move is in check
+    let not_to_move_in_check = board
+        .null_move()
+        .is_some_and(|passed| passed.checkers().popcnt() > 0);
+    if not_to_move_in_check {
+        return Err(anyhow!("The side not to move is in check"));
+    }
+
+    Ok(())
+}
+
+/// Strip the halfmove and fullmove counters from a FEN, leaving the parts
+/// that identify a position (placement, side to move, castling, en passant)
+///
+/// The en passant square only counts when a capture there is possible, so a
+/// FEN that records it after every double push matches one that doesn't.
+pub fn normalize_fen(fen: &str) -> String {
+    let mut fields: Vec<&str> = fen.split_whitespace().take(4).collect();
+    if let (Some(field), Ok(board)) = (fields.get_mut(3), Board::from_str(fen)) {
+        if board.en_passant().is_none() {
+            *field = "-";
+        }
+    }
+    fields.join(" ")
+}
+
+/// Read the halfmove clock and fullmove number from a FEN, defaulting to
+/// "0 1" when they are missing or malformed
+fn clocks(fen: &str) -> (u32, u32) {
+    let mut fields = fen.split_whitespace().skip(4);
+    let halfmove = fields.next().and_then(|f| f.parse().ok()).unwrap_or(0);
+    let fullmove = fields
+        .next()
+        .and_then(|f| f.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(1);
+    (halfmove, fullmove)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_legal_moves_from_square() {
+        let game = GameState::new();
+        let mut moves = game.legal_moves_from("g1").unwrap();
+        moves.sort();
+        assert_eq!(moves, vec!["g1f3", "g1h3"]);
+
+        assert!(game.legal_moves_from("e4").unwrap().is_empty());
+        assert!(game.legal_moves_from("z9").is_err());
+    }
+
+    #[test]
+    fn test_legal_moves_san() {
+        let moves = GameState::new().legal_moves_san().unwrap();
+        assert_eq!(moves.len(), 20);
+        assert!(moves.contains(&("g1f3".to_string(), "Nf3".to_string())));
+
+        // Both knights reach d2, so each needs its file
+        let game = GameState::from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPP2PPP/RNBQKB1R w KQkq - 0 1",
+        )
+        .unwrap();
+        let moves = game.legal_moves_san().unwrap();
+        assert!(moves.contains(&("b1d2".to_string(), "Nbd2".to_string())));
+        assert!(moves.contains(&("f3d2".to_string(), "Nfd2".to_string())));
+
+        let mut sans: Vec<&str> = moves.iter().map(|(_, san)| san.as_str()).collect();
+        sans.sort();
+        sans.dedup();
+        assert_eq!(sans.len(), moves.len());
+    }
+
+    #[test]
+    fn test_normalize_fen_ignores_clocks() {
+        assert_eq!(
+            normalize_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+            normalize_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 12")
+        );
+        assert_eq!(
+            normalize_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -"
+        );
+    }
+
+    #[test]
+    fn test_normalize_fen_edge_cases() {
+        // No clocks at all, and extra whitespace
+        assert_eq!(
+            normalize_fen("  4k3/8/8/8/8/8/8/4K3   b  -  - "),
+            "4k3/8/8/8/8/8/8/4K3 b - -"
+        );
+        // Side to move and castling rights still tell positions apart
+        assert_ne!(
+            normalize_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+            normalize_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1")
+        );
+        assert_ne!(
+            normalize_fen("r3k3/8/8/8/8/8/8/4K3 b q - 0 1"),
+            normalize_fen("r3k3/8/8/8/8/8/8/4K3 b - - 0 1")
+        );
+    }
+
+    #[test]
+    fn test_game_state_position_key() {
+        let (after_e4, _) = GameState::new().make_move("e2e4").unwrap();
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 7 30";
+        let same = GameState::from_fen(fen).unwrap();
+        assert_eq!(after_e4.position_key(), same.position_key());
+    }
+
     #[test]
     fn test_new_game() {
         let game = GameState::new();
@@ -136,12 +354,121 @@ mod tests {
         assert_ne!(game.fen(), new_game.fen());
     }
 
+    #[test]
+    fn test_make_move_records_en_passant_square() {
+        let (after_e4, _) = GameState::new().make_move("e2e4").unwrap();
+        assert_eq!(
+            after_e4.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+
+        // Cleared again by the next move
+        let (after_nf6, _) = after_e4.make_move("g8f6").unwrap();
+        assert_eq!(after_nf6.fen().split_whitespace().nth(3), Some("-"));
+
+        // Reloading keeps it, and the capture is still playable
+        let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        let game = GameState::from_fen(fen).unwrap();
+        let (after_exf6, san) = game.make_move("e5f6").unwrap();
+        assert_eq!(san, "exf6");
+        assert_eq!(after_exf6.fen().split_whitespace().nth(4), Some("0"));
+    }
+
+    #[test]
+    fn test_make_move_keeps_clocks() {
+        let mut game = GameState::new();
+        for uci in ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"] {
+            game = game.make_move(uci).unwrap().0;
+        }
+        // Two quiet piece moves since the last pawn move, Black to play move 3
+        assert!(game.fen().ends_with(" b KQkq - 3 3"), "{}", game.fen());
+
+        // A quiet move increments the clock, a capture resets it
+        let (quiet, _) = game.make_move("g8f6").unwrap();
+        assert!(quiet.fen().ends_with(" 4 4"), "{}", quiet.fen());
+        let (capture, san) = quiet.make_move("b5c6").unwrap();
+        assert_eq!(san, "Bxc6");
+        assert!(capture.fen().ends_with(" 0 4"), "{}", capture.fen());
+
+        // Clocks survive a round trip through from_fen
+        let reloaded = GameState::from_fen(capture.fen()).unwrap();
+        let (next, _) = reloaded.make_move("d7c6").unwrap();
+        assert!(next.fen().ends_with(" 0 5"), "{}", next.fen());
+    }
+
+    #[test]
+    fn test_normalize_fen_ignores_uncapturable_en_passant() {
+        let (after_e4, _) = GameState::new().make_move("e2e4").unwrap();
+        assert_eq!(
+            after_e4.position_key(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq -"
+        );
+        assert_eq!(
+            normalize_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3"),
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6"
+        );
+    }
+
     #[test]
     fn test_illegal_move() {
         let game = GameState::new();
         assert!(game.make_move("e2e5").is_err());
     }
 
+    #[test]
+    fn test_validate_position_rules() {
+        let illegal = [
+            // Two white kings
+            "4k3/8/8/8/8/8/8/3KK3 w - - 0 1",
+            // No black king
+            "8/8/8/8/8/8/8/4K3 w - - 0 1",
+            // Pawn on the back rank
+            "P3k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/p3K3 b - - 0 1",
+            // White to move, but the black king is already attacked
+            "4k3/8/3N4/8/8/8/8/4K3 w - - 0 1",
+            "4k3/4R3/8/8/8/8/8/4K3 w - - 0 1",
+        ];
+        for fen in illegal {
+            assert!(GameState::from_fen(fen).is_err(), "{}", fen);
+        }
+
+        for fen in [
+            "4k3/4R3/8/8/8/8/8/4K3 b - - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        ] {
+            let board = Board::from_str(fen).unwrap();
+            assert!(validate_position(&board).is_ok(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_from_fen_tells_bad_syntax_from_illegal_position() {
+        let err = GameState::from_fen("not a fen").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid FEN string");
+
+        let err = GameState::from_fen("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1").unwrap_err();
+        assert!(err.to_string().contains("position") || err.to_string().contains("check"));
+    }
+
+    #[test]
+    fn test_check_and_reply_count_after_move() {
+        // Qh5xf7 is mate; Bc4xf7+ only checks, leaving Ke7 (the queen guards f7)
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+        let game = GameState::from_fen(fen).unwrap();
+
+        let (mate, _) = game.make_move("h5f7").unwrap();
+        assert!(mate.in_check().unwrap());
+        assert_eq!(mate.legal_moves().unwrap().len(), 0);
+
+        let (check, _) = game.make_move("c4f7").unwrap();
+        assert!(check.in_check().unwrap());
+        assert_eq!(check.legal_moves().unwrap().len(), 1);
+
+        let (quiet, _) = game.make_move("b1c3").unwrap();
+        assert!(!quiet.in_check().unwrap());
+    }
+
     #[test]
     fn test_from_fen() {
         let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
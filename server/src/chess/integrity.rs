@@ -0,0 +1,181 @@
+use anyhow::Result;
+use shared::protocol::PositionMismatch;
+
+use super::GameState;
+
+/// Replay stored moves from `start` and report every ply whose stored
+/// position doesn't match the recomputed one.
+///
+/// `moves` is the stored history as `(move_uci, position_after)` pairs in
+/// play order, and `current_position` is the game's stored position, which
+/// must match the end of the replay. Replay stops at the first move that is
+/// illegal in the recomputed position, since nothing after it can be checked.
+pub fn find_position_mismatches(
+    start: &GameState,
+    moves: &[(&str, &str)],
+    current_position: &str,
+) -> Result<Vec<PositionMismatch>> {
+    let mut mismatches = Vec::new();
+    let mut state = start.clone();
+
+    for (i, (move_uci, stored_fen)) in moves.iter().enumerate() {
+        let ply = i + 1;
+
+        let (next_state, _) = match state.make_move(move_uci) {
+            Ok(result) => result,
+            Err(_) => {
+                mismatches.push(PositionMismatch {
+                    ply,
+                    move_uci: move_uci.to_string(),
+                    stored_fen: stored_fen.to_string(),
+                    computed_fen: None,
+                    reason: "Stored move is illegal in the replayed position".to_string(),
+                });
+                return Ok(mismatches);
+            }
+        };
+
+        if next_state.fen() != *stored_fen {
+            mismatches.push(PositionMismatch {
+                ply,
+                move_uci: move_uci.to_string(),
+                stored_fen: stored_fen.to_string(),
+                computed_fen: Some(next_state.fen().to_string()),
+                reason: "Stored position does not match replay".to_string(),
+            });
+        }
+
+        state = next_state;
+    }
+
+    if state.fen() != current_position {
+        mismatches.push(PositionMismatch {
+            ply: moves.len(),
+            move_uci: moves.last().map_or("", |(uci, _)| uci).to_string(),
+            stored_fen: current_position.to_string(),
+            computed_fen: Some(state.fen().to_string()),
+            reason: "Game's current position does not match replay".to_string(),
+        });
+    }
+
+    Ok(mismatches)
+}
+
+/// The mismatch reported when a game's starting position won't parse, so
+/// none of its moves can be replayed
+pub fn unreadable_start(start_fen: &str) -> PositionMismatch {
+    PositionMismatch {
+        ply: 0,
+        move_uci: String::new(),
+        stored_fen: start_fen.to_string(),
+        computed_fen: None,
+        reason: "Stored starting position is not a valid FEN".to_string(),
+    }
+}
+
+/// One-line account of a mismatch, for the logs
+pub fn describe_mismatch(mismatch: &PositionMismatch) -> String {
+    format!(
+        "ply {} ({}): {}; stored {:?}, replayed {}",
+        mismatch.ply,
+        mismatch.move_uci,
+        mismatch.reason,
+        mismatch.stored_fen,
+        mismatch
+            .computed_fen
+            .as_deref()
+            .map_or("nothing".to_string(), |fen| format!("{:?}", fen))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(ucis: &[&str]) -> Vec<(String, String)> {
+        let mut state = GameState::new();
+        let mut history = Vec::new();
+        for uci in ucis {
+            let (next, _) = state.make_move(uci).unwrap();
+            history.push((uci.to_string(), next.fen().to_string()));
+            state = next;
+        }
+        history
+    }
+
+    fn as_refs(history: &[(String, String)]) -> Vec<(&str, &str)> {
+        history
+            .iter()
+            .map(|(uci, fen)| (uci.as_str(), fen.as_str()))
+            .collect()
+    }
+
+    /// Check a history whose game ended where its last stored move left it
+    fn check(history: &[(String, String)]) -> Vec<PositionMismatch> {
+        let current = history
+            .last()
+            .map_or(GameState::new().fen().to_string(), |(_, fen)| fen.clone());
+        find_position_mismatches(&GameState::new(), &as_refs(history), &current).unwrap()
+    }
+
+    #[test]
+    fn test_clean_history_has_no_mismatches() {
+        let history = play(&["e2e4", "e7e5", "g1f3", "b8c6"]);
+        assert!(check(&history).is_empty());
+    }
+
+    #[test]
+    fn test_corrupted_fen_flags_the_right_ply() {
+        let mut history = play(&["e2e4", "e7e5", "g1f3", "b8c6"]);
+        history[2].1 = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2".to_string();
+
+        let mismatches = check(&history);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].ply, 3);
+        assert_eq!(mismatches[0].move_uci, "g1f3");
+
+        let described = describe_mismatch(&mismatches[0]);
+        assert!(described.starts_with("ply 3 (g1f3): Stored position does not match replay"));
+        assert!(described.contains(&history[2].1));
+    }
+
+    #[test]
+    fn test_illegal_stored_move_stops_replay() {
+        let mut history = play(&["e2e4", "e7e5", "g1f3"]);
+        history[1].0 = "e7e4".to_string();
+
+        let mismatches = check(&history);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].ply, 2);
+        assert!(mismatches[0].computed_fen.is_none());
+        assert!(describe_mismatch(&mismatches[0]).ends_with("replayed nothing"));
+    }
+
+    #[test]
+    fn test_stale_current_position_is_flagged() {
+        let history = play(&["e2e4", "e7e5"]);
+        let mismatches =
+            find_position_mismatches(&GameState::new(), &as_refs(&history), &history[0].1).unwrap();
+
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].ply, 2);
+        assert_eq!(mismatches[0].move_uci, "e7e5");
+        assert_eq!(
+            mismatches[0].computed_fen.as_deref(),
+            Some(history[1].1.as_str())
+        );
+    }
+
+    #[test]
+    fn test_game_without_moves_is_checked_against_its_start() {
+        let start = GameState::new();
+        assert!(find_position_mismatches(&start, &[], start.fen())
+            .unwrap()
+            .is_empty());
+
+        let mismatches =
+            find_position_mismatches(&start, &[], "8/8/8/8/8/8/8/K6k w - - 0 1").unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].ply, 0);
+    }
+}
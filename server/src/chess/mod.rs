@@ -1,7 +1,17 @@
 pub mod game_state;
 pub mod validator;
 pub mod notation;
+pub mod integrity;
+pub mod eval;
+pub mod pgn;
+pub mod openings;
+pub mod perft;
 
 pub use game_state::*;
 pub use validator::*;
 pub use notation::*;
+pub use integrity::*;
+pub use eval::*;
+pub use pgn::*;
+pub use openings::*;
+pub use perft::*;
@@ -0,0 +1,174 @@
+/// Known openings as (ECO code, name, SAN moves from the start position)
+///
+/// Matched by move order, so transpositions into a line aren't recognised.
+const OPENINGS: &[(&str, &str, &[&str])] = &[
+    ("B00", "King's Pawn Opening", &["e4"]),
+    ("D00", "Queen's Pawn Opening", &["d4"]),
+    ("A04", "Zukertort Opening", &["Nf3"]),
+    ("A10", "English Opening", &["c4"]),
+    // 1. e4 e5
+    ("C20", "King's Pawn Game", &["e4", "e5"]),
+    ("C40", "King's Knight Opening", &["e4", "e5", "Nf3"]),
+    ("C41", "Philidor Defense", &["e4", "e5", "Nf3", "d6"]),
+    ("C42", "Petrov's Defense", &["e4", "e5", "Nf3", "Nf6"]),
+    ("C44", "Scotch Game", &["e4", "e5", "Nf3", "Nc6", "d4"]),
+    ("C50", "Italian Game", &["e4", "e5", "Nf3", "Nc6", "Bc4"]),
+    (
+        "C50",
+        "Italian Game: Giuoco Piano",
+        &["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5"],
+    ),
+    (
+        "C51",
+        "Italian Game: Evans Gambit",
+        &["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "b4"],
+    ),
+    (
+        "C55",
+        "Italian Game: Two Knights Defense",
+        &["e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6"],
+    ),
+    ("C60", "Ruy Lopez", &["e4", "e5", "Nf3", "Nc6", "Bb5"]),
+    (
+        "C65",
+        "Ruy Lopez: Berlin Defense",
+        &["e4", "e5", "Nf3", "Nc6", "Bb5", "Nf6"],
+    ),
+    (
+        "C68",
+        "Ruy Lopez: Exchange Variation",
+        &["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6"],
+    ),
+    ("C23", "Bishop's Opening", &["e4", "e5", "Bc4"]),
+    ("C25", "Vienna Game", &["e4", "e5", "Nc3"]),
+    ("C30", "King's Gambit", &["e4", "e5", "f4"]),
+    // 1. e4, other replies
+    ("B20", "Sicilian Defense", &["e4", "c5"]),
+    (
+        "B22",
+        "Sicilian Defense: Alapin Variation",
+        &["e4", "c5", "c3"],
+    ),
+    (
+        "B90",
+        "Sicilian Defense: Najdorf Variation",
+        &[
+            "e4", "c5", "Nf3", "d6", "d4", "cxd4", "Nxd4", "Nf6", "Nc3", "a6",
+        ],
+    ),
+    ("C00", "French Defense", &["e4", "e6"]),
+    ("B10", "Caro-Kann Defense", &["e4", "c6"]),
+    ("B01", "Scandinavian Defense", &["e4", "d5"]),
+    ("B02", "Alekhine's Defense", &["e4", "Nf6"]),
+    ("B06", "Modern Defense", &["e4", "g6"]),
+    ("B07", "Pirc Defense", &["e4", "d6", "d4", "Nf6"]),
+    // 1. d4
+    (
+        "D00",
+        "Queen's Pawn Game: London System",
+        &["d4", "d5", "Bf4"],
+    ),
+    ("D06", "Queen's Gambit", &["d4", "d5", "c4"]),
+    ("D10", "Slav Defense", &["d4", "d5", "c4", "c6"]),
+    (
+        "D20",
+        "Queen's Gambit Accepted",
+        &["d4", "d5", "c4", "dxc4"],
+    ),
+    ("D30", "Queen's Gambit Declined", &["d4", "d5", "c4", "e6"]),
+    ("A45", "Indian Defense", &["d4", "Nf6"]),
+    ("E60", "King's Indian Defense", &["d4", "Nf6", "c4", "g6"]),
+    (
+        "D80",
+        "Grünfeld Defense",
+        &["d4", "Nf6", "c4", "g6", "Nc3", "d5"],
+    ),
+    (
+        "E20",
+        "Nimzo-Indian Defense",
+        &["d4", "Nf6", "c4", "e6", "Nc3", "Bb4"],
+    ),
+    (
+        "E12",
+        "Queen's Indian Defense",
+        &["d4", "Nf6", "c4", "e6", "Nf3", "b6"],
+    ),
+    ("A80", "Dutch Defense", &["d4", "f5"]),
+];
+
+/// Name the opening of a game from its SAN moves, as (ECO code, name)
+///
+/// The longest known line the game starts with wins, so "1. e4 e5 2. Nf3
+/// Nc6 3. Bc4" is the Italian Game rather than just a King's Pawn Game.
+/// Check and mate markers are ignored.
+pub fn identify_opening(moves: &[String]) -> Option<(String, String)> {
+    let played: Vec<&str> = moves
+        .iter()
+        .map(|san| san.trim_end_matches(['+', '#']))
+        .collect();
+
+    OPENINGS
+        .iter()
+        .filter(|(_, _, line)| played.starts_with(line))
+        .max_by_key(|(_, _, line)| line.len())
+        .map(|(eco, name, _)| (eco.to_string(), name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opening_of(moves: &str) -> Option<(String, String)> {
+        let moves: Vec<String> = moves.split_whitespace().map(String::from).collect();
+        identify_opening(&moves)
+    }
+
+    #[test]
+    fn test_italian_game() {
+        assert_eq!(
+            opening_of("e4 e5 Nf3 Nc6 Bc4"),
+            Some(("C50".to_string(), "Italian Game".to_string()))
+        );
+        // Later moves outside the table keep the deepest match
+        assert_eq!(
+            opening_of("e4 e5 Nf3 Nc6 Bc4 Bc5 c3 Nf6 d4"),
+            Some(("C50".to_string(), "Italian Game: Giuoco Piano".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sicilian_defense() {
+        assert_eq!(
+            opening_of("e4 c5 Nf3 Nc6"),
+            Some(("B20".to_string(), "Sicilian Defense".to_string()))
+        );
+        assert_eq!(
+            opening_of("e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 a6 Be3"),
+            Some((
+                "B90".to_string(),
+                "Sicilian Defense: Najdorf Variation".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unknown_or_empty() {
+        assert_eq!(opening_of(""), None);
+        assert_eq!(opening_of("a3 e5"), None);
+    }
+
+    #[test]
+    fn test_every_line_is_legal() {
+        use chess::Board;
+        use shared::notation::san_to_move;
+
+        for (eco, name, line) in OPENINGS {
+            let mut board = Board::default();
+            for san in *line {
+                let chess_move = san_to_move(&board, san)
+                    .unwrap_or_else(|e| panic!("{} {}: {}: {}", eco, name, san, e));
+                board = board.make_move_new(chess_move);
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use chess::{Board, MoveGen};
+
+/// Count the leaf nodes of the legal move tree `depth` plies deep
+///
+/// Known counts for standard positions make this a check that move
+/// generation (castling, en passant, promotions, pins) is right.
+pub fn perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = MoveGen::new_legal(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .map(|chess_move| perft(&board.make_move_new(chess_move), depth - 1))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn test_perft_start_position() {
+        let board = Board::default();
+        assert_eq!(perft(&board, 0), 1);
+        assert_eq!(perft(&board, 1), 20);
+        assert_eq!(perft(&board, 2), 400);
+        assert_eq!(perft(&board, 3), 8_902);
+        assert_eq!(perft(&board, 4), 197_281);
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        let board = Board::from_str(KIWIPETE).unwrap();
+        assert_eq!(perft(&board, 1), 48);
+        assert_eq!(perft(&board, 2), 2_039);
+        assert_eq!(perft(&board, 3), 97_862);
+    }
+}
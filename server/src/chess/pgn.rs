@@ -0,0 +1,241 @@
+use anyhow::{anyhow, Result};
+
+use super::GameState;
+
+/// A game read from PGN, replayed and checked move by move
+#[derive(Debug, Clone)]
+pub struct ParsedGame {
+    /// Tag pairs in the order they appeared, e.g. ("White", "Carlsen")
+    pub headers: Vec<(String, String)>,
+    /// Position the moves start from (the FEN tag, or the standard start)
+    pub start: GameState,
+    pub moves: Vec<ParsedMove>,
+    /// Result token from the movetext: "1-0", "0-1", "1/2-1/2" or "*"
+    pub result: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedMove {
+    pub uci: String,
+    pub san: String,
+    pub fen_before: String,
+    pub fen_after: String,
+}
+
+impl ParsedGame {
+    /// Value of a tag pair, if present
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+/// Parse a single-game PGN and replay its moves
+///
+/// Comments (`{...}` and `;` to end of line), variations `(...)`, NAGs and
+/// move numbers are skipped. Every SAN move must be legal in the position
+/// reached so far.
+pub fn parse_pgn(pgn: &str) -> Result<ParsedGame> {
+    let mut headers = Vec::new();
+    let mut movetext = String::new();
+
+    for line in pgn.lines() {
+        let line = line.trim();
+        if let Some(tag) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            headers.push(parse_tag(tag)?);
+        } else {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+
+    let start = match headers.iter().find(|(key, _)| key == "FEN") {
+        Some((_, fen)) => GameState::from_fen(fen)?,
+        None => GameState::new(),
+    };
+
+    let mut state = start.clone();
+    let mut moves = Vec::new();
+    let mut result = None;
+
+    for token in movetext_tokens(&movetext)? {
+        if RESULTS.contains(&token.as_str()) {
+            result = Some(token);
+            break;
+        }
+
+        let uci = state
+            .san_to_uci(&token)
+            .map_err(|_| anyhow!("Illegal move {} at ply {}", token, moves.len() + 1))?;
+        let (next, san) = state.make_move(&uci)?;
+
+        moves.push(ParsedMove {
+            uci,
+            san,
+            fen_before: state.fen().to_string(),
+            fen_after: next.fen().to_string(),
+        });
+        state = next;
+    }
+
+    let result = result
+        .or_else(|| {
+            headers
+                .iter()
+                .find(|(key, _)| key == "Result")
+                .map(|(_, value)| value.clone())
+        })
+        .unwrap_or_else(|| "*".to_string());
+
+    Ok(ParsedGame {
+        headers,
+        start,
+        moves,
+        result,
+    })
+}
+
+/// Parse the inside of a `[Name "Value"]` tag pair
+fn parse_tag(tag: &str) -> Result<(String, String)> {
+    let (name, value) = tag
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| anyhow!("Malformed tag pair: [{}]", tag))?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(|| anyhow!("Tag value must be quoted: [{}]", tag))?;
+
+    Ok((name.to_string(), value.replace("\\\"", "\"")))
+}
+
+/// Split movetext into SAN moves and the result, dropping everything else
+fn movetext_tokens(movetext: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = movetext.chars();
+    let mut variation_depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                chars
+                    .by_ref()
+                    .find(|&c| c == '}')
+                    .ok_or_else(|| anyhow!("Unterminated comment"))?;
+            }
+            ';' => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '(' => variation_depth += 1,
+            ')' => {
+                if variation_depth == 0 {
+                    return Err(anyhow!("Unbalanced ')' in movetext"));
+                }
+                variation_depth -= 1;
+            }
+            _ if variation_depth > 0 => {}
+            c if c.is_whitespace() => push_token(&mut tokens, &mut current),
+            c => current.push(c),
+        }
+    }
+    push_token(&mut tokens, &mut current);
+
+    if variation_depth > 0 {
+        return Err(anyhow!("Unterminated variation"));
+    }
+
+    Ok(tokens)
+}
+
+/// Keep a finished token unless it's a NAG, move number ("12." / "12...")
+/// or a detached "e.p." marker
+fn push_token(tokens: &mut Vec<String>, current: &mut String) {
+    let token = std::mem::take(current);
+    if token.is_empty() || token.starts_with('$') || token == "e.p." {
+        return;
+    }
+
+    // Move numbers may be glued to the move ("1.e4"); castling with zeros
+    // ("0-0") has no dot, so it's left alone
+    let token = match token.rfind('.') {
+        Some(pos) if token[..pos].chars().all(|c| c.is_ascii_digit() || c == '.') => {
+            &token[pos + 1..]
+        }
+        _ => token.as_str(),
+    };
+
+    if !token.is_empty() {
+        tokens.push(token.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANNOTATED: &str = r#"[Event "Casual Game"]
+[Site "London"]
+[White "Anderssen"]
+[Black "Kieseritzky"]
+[Result "1-0"]
+
+1. e4 e5 2. f4 {King's Gambit} exf4 3. Bc4 Qh4+ $6 (3... Nf6 4. Nc3) 4. Kf1 b5
+; Bryan counter-gambit
+5. Bxb5 Nf6 6.Nf3 Qh6 1-0
+"#;
+
+    #[test]
+    fn test_parse_annotated_pgn() {
+        let game = parse_pgn(ANNOTATED).unwrap();
+
+        assert_eq!(game.header("White"), Some("Anderssen"));
+        assert_eq!(game.header("Site"), Some("London"));
+        assert_eq!(game.result, "1-0");
+
+        let sans: Vec<&str> = game.moves.iter().map(|m| m.san.as_str()).collect();
+        assert_eq!(
+            sans,
+            vec!["e4", "e5", "f4", "exf4", "Bc4", "Qh4+", "Kf1", "b5", "Bxb5", "Nf6", "Nf3", "Qh6"]
+        );
+        assert_eq!(game.moves[0].uci, "e2e4");
+        assert_eq!(game.moves[6].uci, "e1f1");
+
+        // Each move starts where the previous one ended
+        for pair in game.moves.windows(2) {
+            assert_eq!(pair[0].fen_after, pair[1].fen_before);
+        }
+    }
+
+    #[test]
+    fn test_parse_pgn_castling_and_fen_start() {
+        let pgn = r#"[FEN "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"]
+
+1. O-O 0-0-0 *"#;
+        let game = parse_pgn(pgn).unwrap();
+
+        assert_eq!(game.moves.len(), 2);
+        assert_eq!(game.moves[0].uci, "e1g1");
+        assert_eq!(game.moves[1].uci, "e8c8");
+        assert_eq!(
+            game.moves[0].fen_before,
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"
+        );
+        assert_eq!(game.result, "*");
+    }
+
+    #[test]
+    fn test_parse_pgn_rejects_illegal_move() {
+        assert!(parse_pgn("1. e4 e5 2. Ke3 *").is_err());
+    }
+
+    #[test]
+    fn test_parse_pgn_rejects_unterminated_comment() {
+        assert!(parse_pgn("1. e4 {oops e5 *").is_err());
+    }
+}
@@ -1,8 +1,9 @@
-use chess::ChessMove;
+use chess::{Board, ChessMove, Color, File, Piece, Rank, Square};
 use std::str::FromStr;
 use anyhow::{Result, anyhow};
+use thiserror::Error;
 
-use super::GameState;
+use super::{normalize_fen, GameState};
 
 /// Validate a UCI move string format
 pub fn validate_uci_format(move_uci: &str) -> Result<()> {
@@ -10,6 +11,13 @@ pub fn validate_uci_format(move_uci: &str) -> Result<()> {
         return Err(anyhow!("UCI move must be 4-5 characters"));
     }
 
+    // A fifth character names the promotion piece; never a king or pawn
+    if let Some(promotion) = move_uci.chars().nth(4) {
+        if !matches!(promotion, 'q' | 'r' | 'b' | 'n') {
+            return Err(anyhow!("Invalid promotion piece"));
+        }
+    }
+
     // Try to parse it as a ChessMove
     ChessMove::from_str(move_uci)
         .map_err(|_| anyhow!("Invalid UCI move format"))?;
@@ -17,17 +25,169 @@ pub fn validate_uci_format(move_uci: &str) -> Result<()> {
     Ok(())
 }
 
+/// Why a well-formed move can't be played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum IllegalMoveReason {
+    #[error("There is no piece on the source square")]
+    NoPiece,
+
+    #[error("That piece belongs to the side not on move")]
+    WrongTurn,
+
+    #[error("The destination square holds one of your own pieces")]
+    OwnPieceOnDestination,
+
+    #[error("That move leaves your king in check")]
+    LeavesKingInCheck,
+
+    #[error("That piece can't move that way")]
+    NotAPattern,
+}
+
 /// Validate that a move is legal in the given game state
+///
+/// An illegal move's error is an [`IllegalMoveReason`] saying why.
 pub fn validate_move(game_state: &GameState, move_uci: &str) -> Result<()> {
     // First validate the format
     validate_uci_format(move_uci)?;
 
-    // Check if the move is legal
-    if !game_state.is_legal_move(move_uci)? {
-        return Err(anyhow!("Illegal move"));
+    let chess_move =
+        ChessMove::from_str(move_uci).map_err(|_| anyhow!("Invalid UCI move format"))?;
+    let board = game_state.board()?;
+
+    if let Err(reason) = check_pieces(&board, chess_move) {
+        return Err(reason.into());
     }
 
-    Ok(())
+    // A pawn reaching the last rank has to say what it becomes
+    if move_uci.len() == 4 {
+        let last_rank = matches!(chess_move.get_dest().get_rank(), Rank::First | Rank::Eighth);
+        if last_rank && board.piece_on(chess_move.get_source()) == Some(Piece::Pawn) {
+            return Err(anyhow!("Promotion piece required"));
+        }
+    }
+
+    if board.legal(chess_move) {
+        return Ok(());
+    }
+
+    // Only a move the piece could otherwise make is stopped by the king
+    let reason = if follows_piece_pattern(&board, chess_move) {
+        IllegalMoveReason::LeavesKingInCheck
+    } else {
+        IllegalMoveReason::NotAPattern
+    };
+    Err(reason.into())
+}
+
+/// Check the move picks up one of the mover's pieces and doesn't land on another
+fn check_pieces(board: &Board, chess_move: ChessMove) -> Result<(), IllegalMoveReason> {
+    let mover = board.side_to_move();
+    match board.color_on(chess_move.get_source()) {
+        None => Err(IllegalMoveReason::NoPiece),
+        Some(color) if color != mover => Err(IllegalMoveReason::WrongTurn),
+        _ if board.color_on(chess_move.get_dest()) == Some(mover) => {
+            Err(IllegalMoveReason::OwnPieceOnDestination)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether the move is one the piece could make if checks didn't matter
+///
+/// Assumes [`check_pieces`] passed: the mover's piece is on the source
+/// square and the destination is empty or holds an enemy piece.
+fn follows_piece_pattern(board: &Board, chess_move: ChessMove) -> bool {
+    let (source, dest) = (chess_move.get_source(), chess_move.get_dest());
+    let Some(piece) = board.piece_on(source) else {
+        return false;
+    };
+
+    let file_step = dest.get_file().to_index() as i32 - source.get_file().to_index() as i32;
+    let rank_step = dest.get_rank().to_index() as i32 - source.get_rank().to_index() as i32;
+    let (files, ranks) = (file_step.abs(), rank_step.abs());
+
+    let last_rank = matches!(dest.get_rank(), Rank::First | Rank::Eighth);
+    if chess_move.get_promotion().is_some() && !(piece == Piece::Pawn && last_rank) {
+        return false;
+    }
+
+    match piece {
+        Piece::Knight => (files, ranks) == (1, 2) || (files, ranks) == (2, 1),
+        Piece::King => (files <= 1 && ranks <= 1) || castles(board, source, dest),
+        Piece::Rook => (files == 0 || ranks == 0) && path_is_clear(board, source, dest),
+        Piece::Bishop => files == ranks && path_is_clear(board, source, dest),
+        Piece::Queen => {
+            (files == 0 || ranks == 0 || files == ranks) && path_is_clear(board, source, dest)
+        }
+        Piece::Pawn => pawn_can_move(board, source, dest, file_step, rank_step),
+    }
+}
+
+/// Whether every square strictly between `source` and `dest` is empty
+///
+/// Only meaningful for squares on a shared rank, file or diagonal.
+fn path_is_clear(board: &Board, source: Square, dest: Square) -> bool {
+    let file = source.get_file().to_index() as i32;
+    let rank = source.get_rank().to_index() as i32;
+    let file_step = (dest.get_file().to_index() as i32 - file).signum();
+    let rank_step = (dest.get_rank().to_index() as i32 - rank).signum();
+
+    (1..)
+        .map(|i| {
+            Square::make_square(
+                Rank::from_index((rank + i * rank_step) as usize),
+                File::from_index((file + i * file_step) as usize),
+            )
+        })
+        .take_while(|&square| square != dest)
+        .all(|square| board.piece_on(square).is_none())
+}
+
+fn pawn_can_move(
+    board: &Board,
+    source: Square,
+    dest: Square,
+    file_step: i32,
+    rank_step: i32,
+) -> bool {
+    let (forward, start_rank) = match board.side_to_move() {
+        Color::White => (1, 1),
+        Color::Black => (-1, 6),
+    };
+    let empty = |square: Square| board.piece_on(square).is_none();
+
+    match (file_step.abs(), rank_step * forward) {
+        (0, 1) => empty(dest),
+        (0, 2) => {
+            source.get_rank().to_index() == start_rank
+                && empty(dest)
+                && path_is_clear(board, source, dest)
+        }
+        // A capture, or en passant past the pawn that just moved two squares
+        (1, 1) => {
+            board.piece_on(dest).is_some()
+                || board.en_passant()
+                    == Some(Square::make_square(source.get_rank(), dest.get_file()))
+        }
+        _ => false,
+    }
+}
+
+/// Whether the king move is castling with the right intact and nothing in between
+fn castles(board: &Board, source: Square, dest: Square) -> bool {
+    let back_rank = board.side_to_move().to_my_backrank();
+    if source != Square::make_square(back_rank, File::E) || dest.get_rank() != back_rank {
+        return false;
+    }
+
+    let rights = board.my_castle_rights();
+    let (allowed, rook) = match dest.get_file() {
+        File::G => (rights.has_kingside(), File::H),
+        File::C => (rights.has_queenside(), File::A),
+        _ => return false,
+    };
+    allowed && path_is_clear(board, source, Square::make_square(back_rank, rook))
 }
 
 /// Check if a game has ended and return the result
@@ -42,19 +202,165 @@ pub fn check_game_result(game_state: &GameState) -> Result<Option<GameResult>> {
             Some(GameResult::Checkmate)
         }
         BoardStatus::Stalemate => Some(GameResult::Stalemate),
+        BoardStatus::Ongoing if is_insufficient_material(&game_state.board()?) => {
+            Some(GameResult::InsufficientMaterial)
+        }
+        // Checkmate on the last halfmove still counts, so this comes last
+        BoardStatus::Ongoing if halfmove_clock(game_state.fen())? >= 150 => {
+            Some(GameResult::SeventyFiveMoveRule)
+        }
         BoardStatus::Ongoing => None,
     })
 }
 
+/// Check whether neither side has enough material to ever deliver mate
+///
+/// Covers lone kings, a single minor piece against a lone king, and one
+/// bishop each on the same square colour. Anything else (including two
+/// knights) is treated as sufficient, since mate is still possible.
+pub fn is_insufficient_material(board: &Board) -> bool {
+    let decisive = board.pieces(Piece::Pawn).popcnt()
+        + board.pieces(Piece::Rook).popcnt()
+        + board.pieces(Piece::Queen).popcnt();
+    if decisive > 0 {
+        return false;
+    }
+
+    let knights = board.pieces(Piece::Knight).popcnt();
+    let bishops = *board.pieces(Piece::Bishop);
+
+    match (knights, bishops.popcnt()) {
+        (0, 0) | (1, 0) | (0, 1) => true,
+        (0, 2) => {
+            let white_bishops = (bishops & board.color_combined(Color::White)).popcnt();
+            let light_squares = bishops
+                .filter(|sq| (sq.get_rank().to_index() + sq.get_file().to_index()) % 2 == 1)
+                .count();
+            white_bishops == 1 && light_squares != 1
+        }
+        _ => false,
+    }
+}
+
+/// Read the halfmove clock (the 5th FEN field), treating a missing field as 0
+fn halfmove_clock(fen: &str) -> Result<u32> {
+    match fen.split_whitespace().nth(4) {
+        Some(clock) => clock
+            .parse()
+            .map_err(|_| anyhow!("Invalid halfmove clock in FEN")),
+        None => Ok(0),
+    }
+}
+
+/// Check if a game has ended, also considering the positions that came before
+///
+/// `history` holds the FENs of every earlier position in the game (not
+/// including `game_state`). The fifth occurrence of the same position ends
+/// the game; a threefold repetition only lets a player claim a draw (see
+/// `claimable_draw`).
+pub fn check_game_result_with_history(
+    game_state: &GameState,
+    history: &[String],
+) -> Result<Option<GameResult>> {
+    if let Some(result) = check_game_result(game_state)? {
+        return Ok(Some(result));
+    }
+
+    if repetitions(game_state, history) >= 5 {
+        return Ok(Some(GameResult::FivefoldRepetition));
+    }
+
+    Ok(None)
+}
+
+/// The draw the player to move may claim in `game_state`, if any
+///
+/// A position seen for the third time can be claimed as threefold
+/// repetition, and fifty moves by each side without a capture or pawn move
+/// under the fifty-move rule. `history` is as for
+/// `check_game_result_with_history`.
+pub fn claimable_draw(game_state: &GameState, history: &[String]) -> Result<Option<GameResult>> {
+    if repetitions(game_state, history) >= 3 {
+        return Ok(Some(GameResult::ThreefoldRepetition));
+    }
+
+    if halfmove_clock(game_state.fen())? >= 100 {
+        return Ok(Some(GameResult::FiftyMoveRule));
+    }
+
+    Ok(None)
+}
+
+/// How many times the position has occurred, counting this time
+///
+/// Positions are compared without their move counters.
+fn repetitions(game_state: &GameState, history: &[String]) -> usize {
+    let key = game_state.position_key();
+    history
+        .iter()
+        .filter(|fen| normalize_fen(fen) == key)
+        .count()
+        + 1
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameResult {
     Checkmate,
     Stalemate,
+    /// Claimed by a player
+    ThreefoldRepetition,
+    /// Claimed by a player
+    FiftyMoveRule,
+    FivefoldRepetition,
+    SeventyFiveMoveRule,
+    InsufficientMaterial,
 }
 
 impl GameResult {
+    /// Short human-readable description of why the game ended
+    pub fn reason(&self) -> &'static str {
+        match self {
+            GameResult::Checkmate => "checkmate",
+            GameResult::Stalemate => "stalemate",
+            GameResult::ThreefoldRepetition => "threefold repetition",
+            GameResult::FiftyMoveRule => "fifty-move rule",
+            GameResult::FivefoldRepetition => "fivefold repetition",
+            GameResult::SeventyFiveMoveRule => "seventy-five-move rule",
+            GameResult::InsufficientMaterial => "insufficient material",
+        }
+    }
+
+    /// The `draw_reason` stored for a drawn game, or None if this isn't a draw
+    pub fn draw_reason(&self) -> Option<&'static str> {
+        match self {
+            GameResult::Checkmate => None,
+            GameResult::Stalemate => Some("stalemate"),
+            GameResult::ThreefoldRepetition => Some("threefold"),
+            GameResult::FiftyMoveRule => Some("fifty_move"),
+            GameResult::FivefoldRepetition => Some("fivefold"),
+            GameResult::SeventyFiveMoveRule => Some("seventy_five_move"),
+            GameResult::InsufficientMaterial => Some("insufficient_material"),
+        }
+    }
+
+    /// The `win_reason` stored for a decisive game, or None for a draw
+    pub fn win_reason(&self) -> Option<&'static str> {
+        match self {
+            GameResult::Checkmate => Some("checkmate"),
+            _ => None,
+        }
+    }
+
     pub fn is_draw(&self) -> bool {
-        matches!(self, GameResult::Stalemate)
+        matches!(
+            self,
+            GameResult::Stalemate
+                | GameResult::ThreefoldRepetition
+                | GameResult::FiftyMoveRule
+                | GameResult::FivefoldRepetition
+                | GameResult::SeventyFiveMoveRule
+                | GameResult::InsufficientMaterial
+        )
     }
 }
 
@@ -71,6 +377,15 @@ mod tests {
         assert!(validate_uci_format("e2e4e5").is_err());
     }
 
+    #[test]
+    fn test_validate_uci_format_promotion_pieces() {
+        for uci in ["e7e8q", "e7e8r", "e7e8b", "e7e8n"] {
+            assert!(validate_uci_format(uci).is_ok(), "{}", uci);
+        }
+        assert!(validate_uci_format("e7e8k").is_err());
+        assert!(validate_uci_format("e7e8p").is_err());
+    }
+
     #[test]
     fn test_validate_move() {
         let game_state = GameState::new();
@@ -78,6 +393,57 @@ mod tests {
         assert!(validate_move(&game_state, "e2e5").is_err());
     }
 
+    fn illegal_reason(fen: &str, move_uci: &str) -> IllegalMoveReason {
+        let game_state = GameState::from_fen(fen).unwrap();
+        let err = validate_move(&game_state, move_uci).unwrap_err();
+        *err.downcast_ref::<IllegalMoveReason>()
+            .unwrap_or_else(|| panic!("{}: {}", move_uci, err))
+    }
+
+    #[test]
+    fn test_pinned_piece_leaves_king_in_check() {
+        // The bishop on e2 is pinned by the rook on e7
+        let reason = illegal_reason("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1", "e2d3");
+        assert_eq!(reason, IllegalMoveReason::LeavesKingInCheck);
+        assert_eq!(reason.to_string(), "That move leaves your king in check");
+
+        // Stepping into the rook's file is the same
+        let reason = illegal_reason("4k3/3r4/8/8/8/8/8/4K3 w - - 0 1", "e1d1");
+        assert_eq!(reason, IllegalMoveReason::LeavesKingInCheck);
+    }
+
+    #[test]
+    fn test_illegal_move_reasons() {
+        let start = GameState::new().fen().to_string();
+        let cases = [
+            ("e3e4", IllegalMoveReason::NoPiece),
+            ("e7e5", IllegalMoveReason::WrongTurn),
+            ("d1d2", IllegalMoveReason::OwnPieceOnDestination),
+            ("e2e5", IllegalMoveReason::NotAPattern),
+            ("f1c4", IllegalMoveReason::NotAPattern),
+            ("g1g3", IllegalMoveReason::NotAPattern),
+        ];
+        for (move_uci, reason) in cases {
+            assert_eq!(illegal_reason(&start, move_uci), reason, "{}", move_uci);
+        }
+
+        // Castling through an attacked square is stopped by the king's safety
+        let reason = illegal_reason("4k3/8/8/8/8/8/5r2/4K2R w K - 0 1", "e1g1");
+        assert_eq!(reason, IllegalMoveReason::LeavesKingInCheck);
+    }
+
+    #[test]
+    fn test_validate_move_requires_promotion_piece() {
+        let game_state = GameState::from_fen("8/4P3/7k/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let err = validate_move(&game_state, "e7e8").unwrap_err();
+        assert_eq!(err.to_string(), "Promotion piece required");
+        assert!(validate_move(&game_state, "e7e8q").is_ok());
+
+        // Other pieces reaching the last rank need no suffix
+        let game_state = GameState::from_fen("8/8/7k/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert!(validate_move(&game_state, "a1a8").is_ok());
+    }
+
     #[test]
     fn test_check_game_result() {
         let game_state = GameState::new();
@@ -91,4 +457,149 @@ mod tests {
             Some(GameResult::Checkmate)
         );
     }
+
+    /// Play `ucis` from the start, returning the final state and every earlier FEN
+    fn play(ucis: &[&str]) -> (GameState, Vec<String>) {
+        let mut state = GameState::new();
+        let mut history = Vec::new();
+        for uci in ucis {
+            let (next, _) = state.make_move(uci).unwrap();
+            history.push(state.fen().to_string());
+            state = next;
+        }
+        (state, history)
+    }
+
+    #[test]
+    fn test_threefold_repetition_is_claimable() {
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        // Start position seen twice: nothing to claim yet
+        let (state, history) = play(&shuffle);
+        assert_eq!(claimable_draw(&state, &history).unwrap(), None);
+
+        // Third time back at the start position (with different move counters)
+        let moves: Vec<&str> = shuffle.iter().chain(shuffle.iter()).copied().collect();
+        let (state, history) = play(&moves);
+        assert_eq!(
+            claimable_draw(&state, &history).unwrap(),
+            Some(GameResult::ThreefoldRepetition)
+        );
+        assert!(GameResult::ThreefoldRepetition.is_draw());
+
+        // ...but the game goes on unless someone claims it
+        assert_eq!(
+            check_game_result_with_history(&state, &history).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fivefold_repetition_ends_the_game() {
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        let moves: Vec<&str> = shuffle.iter().cycle().take(12).copied().collect();
+        let (state, history) = play(&moves);
+        assert_eq!(
+            check_game_result_with_history(&state, &history).unwrap(),
+            None
+        );
+
+        let moves: Vec<&str> = shuffle.iter().cycle().take(16).copied().collect();
+        let (state, history) = play(&moves);
+        assert_eq!(
+            check_game_result_with_history(&state, &history).unwrap(),
+            Some(GameResult::FivefoldRepetition)
+        );
+        assert_eq!(
+            GameResult::FivefoldRepetition.draw_reason(),
+            Some("fivefold")
+        );
+    }
+
+    #[test]
+    fn test_repetition_requires_same_side_to_move() {
+        // Same placement twice with White to move and once with Black is not threefold
+        let history = vec![
+            "4k3/8/8/8/8/8/8/R3K3 w - - 0 1".to_string(),
+            "4k3/8/8/8/8/8/8/R3K3 b - - 1 1".to_string(),
+        ];
+        let state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 4 3").unwrap();
+        assert_eq!(claimable_draw(&state, &history).unwrap(), None);
+    }
+
+    #[test]
+    fn test_fifty_move_rule_boundary() {
+        let history = Vec::new();
+        let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert_eq!(claimable_draw(&game_state, &history).unwrap(), None);
+
+        let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert_eq!(
+            claimable_draw(&game_state, &history).unwrap(),
+            Some(GameResult::FiftyMoveRule)
+        );
+        assert_eq!(check_game_result(&game_state).unwrap(), None);
+        assert!(GameResult::FiftyMoveRule.is_draw());
+    }
+
+    #[test]
+    fn test_seventy_five_move_rule_boundary() {
+        let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 80").unwrap();
+        assert_eq!(check_game_result(&game_state).unwrap(), None);
+
+        let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 80").unwrap();
+        assert_eq!(
+            check_game_result(&game_state).unwrap(),
+            Some(GameResult::SeventyFiveMoveRule)
+        );
+        assert!(GameResult::SeventyFiveMoveRule.is_draw());
+    }
+
+    #[test]
+    fn test_checkmate_takes_precedence_over_seventy_five_move_rule() {
+        let game_state = GameState::from_fen("R3k3/8/4K3/8/8/8/8/8 b - - 150 80").unwrap();
+        assert_eq!(
+            check_game_result(&game_state).unwrap(),
+            Some(GameResult::Checkmate)
+        );
+    }
+
+    fn board(fen: &str) -> Board {
+        GameState::from_fen(fen).unwrap().board().unwrap()
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        // King vs king
+        assert!(is_insufficient_material(&board("4k3/8/8/8/8/8/8/4K3 w - - 0 1")));
+        // King and bishop vs king
+        assert!(is_insufficient_material(&board("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1")));
+        // King and knight vs king
+        assert!(is_insufficient_material(&board("4k3/8/8/8/8/8/8/1N2K3 b - - 0 1")));
+        // Bishops on the same colour (c1 and f8 are both dark)
+        assert!(is_insufficient_material(&board("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1")));
+    }
+
+    #[test]
+    fn test_sufficient_material() {
+        // Bishops on opposite colours (c1 dark, c8 light)
+        assert!(!is_insufficient_material(&board("2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1")));
+        // Two knights can still mate with help
+        assert!(!is_insufficient_material(&board("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1")));
+        // Bishop vs knight
+        assert!(!is_insufficient_material(&board("4kn2/8/8/8/8/8/8/2B1K3 w - - 0 1")));
+        // A single pawn
+        assert!(!is_insufficient_material(&board("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1")));
+        assert!(!is_insufficient_material(&GameState::new().board().unwrap()));
+    }
+
+    #[test]
+    fn test_check_game_result_insufficient_material() {
+        let game_state = GameState::from_fen("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1").unwrap();
+        assert_eq!(
+            check_game_result(&game_state).unwrap(),
+            Some(GameResult::InsufficientMaterial)
+        );
+    }
 }
@@ -0,0 +1,168 @@
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+use anyhow::Result;
+
+use crate::models::{Challenge, ChallengeWithPlayers, NewChallenge};
+
+/// Create a pending challenge
+pub async fn create_challenge(pool: &PgPool, new_challenge: &NewChallenge) -> Result<Challenge> {
+    let challenge = sqlx::query_as::<_, Challenge>(
+        r#"
+        INSERT INTO challenges (
+            challenger_id, challenged_id, color_preference,
+            takebacks_allowed, time_control, is_public, starting_fen
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, challenger_id, challenged_id, color_preference,
+                  takebacks_allowed, time_control, is_public, starting_fen, status,
+                  game_id, created_at, responded_at
+        "#,
+    )
+    .bind(new_challenge.challenger_id)
+    .bind(new_challenge.challenged_id)
+    .bind(&new_challenge.color_preference)
+    .bind(new_challenge.takebacks_allowed)
+    .bind(&new_challenge.time_control)
+    .bind(new_challenge.is_public)
+    .bind(&new_challenge.starting_fen)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(challenge)
+}
+
+/// Find a challenge by ID
+pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Challenge>> {
+    let challenge = sqlx::query_as::<_, Challenge>(
+        r#"
+        SELECT id, challenger_id, challenged_id, color_preference,
+               takebacks_allowed, time_control, is_public, starting_fen, status,
+               game_id, created_at, responded_at
+        FROM challenges
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(challenge)
+}
+
+/// Find a challenge by ID along with both players' usernames
+pub async fn find_with_players(pool: &PgPool, id: Uuid) -> Result<Option<ChallengeWithPlayers>> {
+    let challenge = sqlx::query_as::<_, ChallengeWithPlayers>(
+        r#"
+        SELECT
+            c.id, c.challenger_id, c.challenged_id,
+            cr.username as challenger_username,
+            cd.username as challenged_username,
+            c.color_preference, c.time_control, c.status, c.game_id, c.created_at
+        FROM challenges c
+        JOIN users cr ON c.challenger_id = cr.id
+        JOIN users cd ON c.challenged_id = cd.id
+        WHERE c.id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(challenge)
+}
+
+/// List pending challenges a user sent or received, newest first
+pub async fn list_pending_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<ChallengeWithPlayers>> {
+    let challenges = sqlx::query_as::<_, ChallengeWithPlayers>(
+        r#"
+        SELECT
+            c.id, c.challenger_id, c.challenged_id,
+            cr.username as challenger_username,
+            cd.username as challenged_username,
+            c.color_preference, c.time_control, c.status, c.game_id, c.created_at
+        FROM challenges c
+        JOIN users cr ON c.challenger_id = cr.id
+        JOIN users cd ON c.challenged_id = cd.id
+        WHERE c.status = 'pending'
+          AND (c.challenger_id = $1 OR c.challenged_id = $1)
+        ORDER BY c.created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(challenges)
+}
+
+/// Answer a pending challenge; false if it was already answered
+pub async fn respond(executor: impl PgExecutor<'_>, id: Uuid, status: &str) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE challenges
+        SET status = $2, responded_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record the game an accepted challenge started
+pub async fn set_game(executor: impl PgExecutor<'_>, id: Uuid, game_id: Uuid) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE challenges
+        SET game_id = $2
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(game_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Find pending challenges created more than `hours` ago
+pub async fn find_expired(pool: &PgPool, hours: i64) -> Result<Vec<Challenge>> {
+    let challenges = sqlx::query_as::<_, Challenge>(
+        r#"
+        SELECT id, challenger_id, challenged_id, color_preference,
+               takebacks_allowed, time_control, is_public, starting_fen, status,
+               game_id, created_at, responded_at
+        FROM challenges
+        WHERE status = 'pending'
+          AND created_at < NOW() - INTERVAL '1 hour' * $1
+        "#,
+    )
+    .bind(hours)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(challenges)
+}
+
+/// Mark a pending challenge as expired; false if it was answered meanwhile
+pub async fn expire(pool: &PgPool, id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE challenges
+        SET status = 'expired', responded_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,98 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::Result;
+
+use crate::models::ConditionalMove;
+
+/// Plan `move_uci` for a position, replacing any earlier plan for it
+pub async fn upsert(
+    pool: &PgPool,
+    game_id: Uuid,
+    user_id: Uuid,
+    position_fen: &str,
+    move_uci: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO conditional_moves (game_id, user_id, position_fen, move_uci)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (game_id, user_id, position_fen)
+        DO UPDATE SET move_uci = EXCLUDED.move_uci, created_at = NOW()
+        "#,
+    )
+    .bind(game_id)
+    .bind(user_id)
+    .bind(position_fen)
+    .bind(move_uci)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A player's planned moves in a game, oldest first
+pub async fn list_for_player(
+    pool: &PgPool,
+    game_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<ConditionalMove>> {
+    let conditional_moves = sqlx::query_as::<_, ConditionalMove>(
+        r#"
+        SELECT id, game_id, user_id, position_fen, move_uci, created_at
+        FROM conditional_moves
+        WHERE game_id = $1 AND user_id = $2
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(game_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(conditional_moves)
+}
+
+/// Both players' planned moves in a game
+pub async fn list_by_game(pool: &PgPool, game_id: Uuid) -> Result<Vec<ConditionalMove>> {
+    let conditional_moves = sqlx::query_as::<_, ConditionalMove>(
+        r#"
+        SELECT id, game_id, user_id, position_fen, move_uci, created_at
+        FROM conditional_moves
+        WHERE game_id = $1
+        "#,
+    )
+    .bind(game_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(conditional_moves)
+}
+
+/// Remove one planned move, e.g. once it has been played
+pub async fn delete(pool: &PgPool, id: Uuid) -> Result<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM conditional_moves WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove all of a player's planned moves in a game
+pub async fn clear_for_player(pool: &PgPool, game_id: Uuid, user_id: Uuid) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM conditional_moves WHERE game_id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(game_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
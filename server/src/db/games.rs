@@ -1,23 +1,25 @@
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde_json::Value as JsonValue;
 use anyhow::Result;
 
-use crate::models::{Game, NewGame, GameWithPlayers};
+use crate::models::{ArchivedGame, Game, NewGame, GameDetails, GameWithPlayers, UserStats};
 
 /// Create a new game
-pub async fn create_game(pool: &PgPool, new_game: &NewGame) -> Result<Game> {
+pub async fn create_game(executor: impl PgExecutor<'_>, new_game: &NewGame) -> Result<Game> {
     let game = sqlx::query_as::<_, Game>(
         r#"
         INSERT INTO games (
             white_player_id, black_player_id, current_position,
-            game_state, status, current_turn
+            game_state, status, current_turn, takebacks_allowed,
+            move_deadline_hours, is_public
         )
-        VALUES ($1, $2, $3, $4, $5, $6)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         RETURNING id, white_player_id, black_player_id, current_position,
                   game_state, status, current_turn, move_deadline,
-                  created_at, completed_at
+                  created_at, completed_at, takebacks_allowed, move_deadline_hours,
+                  is_public, draw_reason, win_reason, version
         "#,
     )
     .bind(new_game.white_player_id)
@@ -26,32 +28,119 @@ pub async fn create_game(pool: &PgPool, new_game: &NewGame) -> Result<Game> {
     .bind(&new_game.game_state)
     .bind(&new_game.status)
     .bind(&new_game.current_turn)
-    .fetch_one(pool)
+    .bind(new_game.takebacks_allowed)
+    .bind(new_game.move_deadline_hours)
+    .bind(new_game.is_public)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(game)
+}
+
+/// Create an already-finished game imported from PGN
+pub async fn create_imported_game(
+    executor: impl PgExecutor<'_>,
+    new_game: &NewGame,
+) -> Result<Game> {
+    let game = sqlx::query_as::<_, Game>(
+        r#"
+        INSERT INTO games (
+            white_player_id, black_player_id, current_position,
+            game_state, status, current_turn, takebacks_allowed,
+            move_deadline_hours, is_public, completed_at, imported
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), TRUE)
+        RETURNING id, white_player_id, black_player_id, current_position,
+                  game_state, status, current_turn, move_deadline,
+                  created_at, completed_at, takebacks_allowed, move_deadline_hours,
+                  is_public, draw_reason, win_reason, version
+        "#,
+    )
+    .bind(new_game.white_player_id)
+    .bind(new_game.black_player_id)
+    .bind(&new_game.current_position)
+    .bind(&new_game.game_state)
+    .bind(&new_game.status)
+    .bind(&new_game.current_turn)
+    .bind(new_game.takebacks_allowed)
+    .bind(new_game.move_deadline_hours)
+    .bind(new_game.is_public)
+    .fetch_one(executor)
     .await?;
 
     Ok(game)
 }
 
+/// IDs of every game, oldest first
+pub async fn list_ids(pool: &PgPool) -> Result<Vec<Uuid>> {
+    let ids: Vec<(Uuid,)> = sqlx::query_as(
+        r#"
+        SELECT id FROM games ORDER BY created_at
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids.into_iter().map(|(id,)| id).collect())
+}
+
 /// Find a game by ID
-pub async fn find_by_id(pool: &PgPool, game_id: Uuid) -> Result<Option<Game>> {
+pub async fn find_by_id(executor: impl PgExecutor<'_>, game_id: Uuid) -> Result<Option<Game>> {
     let game = sqlx::query_as::<_, Game>(
         r#"
         SELECT id, white_player_id, black_player_id, current_position,
                game_state, status, current_turn, move_deadline,
-               created_at, completed_at
+               created_at, completed_at, takebacks_allowed, move_deadline_hours,
+               is_public, draw_reason, win_reason, version
         FROM games
         WHERE id = $1
         "#,
     )
     .bind(game_id)
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await?;
 
     Ok(game)
 }
 
-/// List games for a user
-pub async fn list_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<GameWithPlayers>> {
+/// Find a game with its players and number of moves, in one query
+pub async fn get_game_with_players_and_move_count(
+    pool: &PgPool,
+    game_id: Uuid,
+) -> Result<Option<GameDetails>> {
+    let details = sqlx::query_as::<_, GameDetails>(
+        r#"
+        SELECT
+            g.id, g.white_player_id, g.black_player_id, g.current_position,
+            g.game_state, g.status, g.current_turn, g.move_deadline,
+            g.created_at, g.completed_at, g.takebacks_allowed, g.move_deadline_hours,
+            g.is_public, g.draw_reason, g.win_reason, g.version,
+            w.username as white_player_username,
+            w.last_seen as white_player_last_seen,
+            b.username as black_player_username,
+            b.last_seen as black_player_last_seen,
+            (SELECT COUNT(*) FROM moves m WHERE m.game_id = g.id) as move_count
+        FROM games g
+        JOIN users w ON g.white_player_id = w.id
+        JOIN users b ON g.black_player_id = b.id
+        WHERE g.id = $1
+        "#,
+    )
+    .bind(game_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(details)
+}
+
+/// List a page of games for a user, newest first, optionally only those with `status`
+pub async fn list_by_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    status: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<GameWithPlayers>> {
     let games = sqlx::query_as::<_, GameWithPlayers>(
         r#"
         SELECT
@@ -63,17 +152,109 @@ pub async fn list_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<GameWithPl
         FROM games g
         JOIN users w ON g.white_player_id = w.id
         JOIN users b ON g.black_player_id = b.id
-        WHERE g.white_player_id = $1 OR g.black_player_id = $1
-        ORDER BY g.created_at DESC
+        WHERE (g.white_player_id = $1 OR g.black_player_id = $1)
+          AND ($2::text IS NULL OR g.status = $2)
+        ORDER BY g.created_at DESC, g.id
+        LIMIT $3 OFFSET $4
         "#,
     )
     .bind(user_id)
+    .bind(status)
+    .bind(limit)
+    .bind(offset)
     .fetch_all(pool)
     .await?;
 
     Ok(games)
 }
 
+/// List a user's finished (non-imported) games, most recently finished first
+pub async fn list_completed_by_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<GameWithPlayers>> {
+    let games = sqlx::query_as::<_, GameWithPlayers>(
+        r#"
+        SELECT
+            g.id, g.white_player_id, g.black_player_id,
+            w.username as white_player_username,
+            b.username as black_player_username,
+            g.current_position, g.status, g.current_turn,
+            g.move_deadline, g.created_at
+        FROM games g
+        JOIN users w ON g.white_player_id = w.id
+        JOIN users b ON g.black_player_id = b.id
+        WHERE (g.white_player_id = $1 OR g.black_player_id = $1)
+          AND g.status IN ('white_won', 'black_won', 'draw')
+          AND NOT g.imported
+        ORDER BY g.completed_at DESC NULLS LAST, g.id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games)
+}
+
+/// A user's finished games with their players, oldest first, for PGN export
+///
+/// `color` ("white" or "black") keeps only the games the user played with it.
+pub async fn list_completed_for_export(
+    pool: &PgPool,
+    user_id: Uuid,
+    color: Option<&str>,
+) -> Result<Vec<ArchivedGame>> {
+    let games = sqlx::query_as::<_, ArchivedGame>(
+        r#"
+        SELECT
+            g.id, g.white_player_id, g.black_player_id, g.current_position,
+            g.game_state, g.status, g.current_turn, g.move_deadline,
+            g.created_at, g.completed_at, g.takebacks_allowed, g.move_deadline_hours,
+            g.is_public, g.draw_reason, g.win_reason, g.version,
+            w.username as white_player_username,
+            b.username as black_player_username
+        FROM games g
+        JOIN users w ON g.white_player_id = w.id
+        JOIN users b ON g.black_player_id = b.id
+        WHERE ((g.white_player_id = $1 AND ($2::text IS NULL OR $2 = 'white'))
+            OR (g.black_player_id = $1 AND ($2::text IS NULL OR $2 = 'black')))
+          AND g.status IN ('white_won', 'black_won', 'draw')
+          AND NOT g.imported
+        ORDER BY g.completed_at ASC NULLS LAST, g.id
+        "#,
+    )
+    .bind(user_id)
+    .bind(color)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games)
+}
+
+/// Count a user's games, optionally only those with `status`
+pub async fn count_by_user(pool: &PgPool, user_id: Uuid, status: Option<&str>) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*)
+        FROM games
+        WHERE (white_player_id = $1 OR black_player_id = $1)
+          AND ($2::text IS NULL OR status = $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(status)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.0)
+}
+
 /// List active games for a user
 pub async fn list_active_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<GameWithPlayers>> {
     let games = sqlx::query_as::<_, GameWithPlayers>(
@@ -99,23 +280,164 @@ pub async fn list_active_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<Gam
     Ok(games)
 }
 
+/// List active games where it's the user's turn, most urgent deadline first
+pub async fn list_where_turn(pool: &PgPool, user_id: Uuid) -> Result<Vec<GameWithPlayers>> {
+    let games = sqlx::query_as::<_, GameWithPlayers>(
+        r#"
+        SELECT
+            g.id, g.white_player_id, g.black_player_id,
+            w.username as white_player_username,
+            b.username as black_player_username,
+            g.current_position, g.status, g.current_turn,
+            g.move_deadline, g.created_at
+        FROM games g
+        JOIN users w ON g.white_player_id = w.id
+        JOIN users b ON g.black_player_id = b.id
+        WHERE g.status = 'active'
+          AND ((g.white_player_id = $1 AND g.current_turn = 'white')
+            OR (g.black_player_id = $1 AND g.current_turn = 'black'))
+        ORDER BY g.move_deadline ASC NULLS LAST, g.created_at
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games)
+}
+
+/// Whether two players already have an active game, with either one as White
+pub async fn active_game_exists_between(
+    executor: impl PgExecutor<'_>,
+    a: Uuid,
+    b: Uuid,
+) -> Result<bool> {
+    let (low, high) = player_pair(a, b);
+    let exists: (bool,) = sqlx::query_as(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM games
+            WHERE status = 'active'
+              AND LEAST(white_player_id, black_player_id) = $1
+              AND GREATEST(white_player_id, black_player_id) = $2
+        )
+        "#,
+    )
+    .bind(low)
+    .bind(high)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(exists.0)
+}
+
+/// Two players as an unordered pair: the lower id first, whoever is White
+///
+/// Postgres orders uuids bytewise, the same as `Uuid`, so this matches
+/// LEAST/GREATEST over the two player columns.
+fn player_pair(a: Uuid, b: Uuid) -> (Uuid, Uuid) {
+    (a.min(b), a.max(b))
+}
+
+/// Count active games for a user
+pub async fn count_active_by_user(executor: impl PgExecutor<'_>, user_id: Uuid) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM games
+        WHERE (white_player_id = $1 OR black_player_id = $1)
+          AND status = 'active'
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Count finished games (won, lost, or drawn) for a user
+pub async fn count_completed_by_user(pool: &PgPool, user_id: Uuid) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM games
+        WHERE (white_player_id = $1 OR black_player_id = $1)
+          AND status IN ('white_won', 'black_won', 'draw')
+          AND NOT imported
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.0)
+}
+
+/// Win/loss/draw totals over a user's finished (non-imported) games
+pub async fn stats_for_user(pool: &PgPool, user_id: Uuid) -> Result<UserStats> {
+    // One branch per color so each side can use its player index
+    let counts: Vec<(String, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT status, 'white', COUNT(*) FROM games
+        WHERE white_player_id = $1
+          AND status IN ('white_won', 'black_won', 'draw')
+          AND NOT imported
+        GROUP BY status
+        UNION ALL
+        SELECT status, 'black', COUNT(*) FROM games
+        WHERE black_player_id = $1
+          AND status IN ('white_won', 'black_won', 'draw')
+          AND NOT imported
+        GROUP BY status
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tally_stats(&counts))
+}
+
+/// Fold (status, color played, count) rows into a user's stats
+fn tally_stats(counts: &[(String, String, i64)]) -> UserStats {
+    let mut stats = UserStats::default();
+    for (status, color, games) in counts {
+        match (status.as_str(), color.as_str()) {
+            ("white_won", "white") | ("black_won", "black") => stats.won += games,
+            ("white_won", "black") | ("black_won", "white") => stats.lost += games,
+            ("draw", _) => stats.drawn += games,
+            _ => continue,
+        }
+        stats.played += games;
+    }
+    stats
+}
+
 /// Update game state after a move
+///
+/// Only applies if the game is still active and at `expected_version`, the
+/// version the move was validated against; returns false if another change
+/// got there first.
 pub async fn update_after_move(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     game_id: Uuid,
+    expected_version: i32,
     new_position: &str,
     new_state: &JsonValue,
     new_turn: &str,
     deadline: DateTime<Utc>,
-) -> Result<()> {
-    sqlx::query(
+) -> Result<bool> {
+    let result = sqlx::query(
         r#"
         UPDATE games
         SET current_position = $1,
             game_state = $2,
             current_turn = $3,
-            move_deadline = $4
+            move_deadline = $4,
+            takeback_requested_by = NULL,
+            version = version + 1
         WHERE id = $5
+          AND status = 'active'
+          AND version = $6
         "#,
     )
     .bind(new_position)
@@ -123,32 +445,45 @@ pub async fn update_after_move(
     .bind(new_turn)
     .bind(deadline)
     .bind(game_id)
-    .execute(pool)
+    .bind(expected_version)
+    .execute(executor)
     .await?;
 
-    Ok(())
+    Ok(result.rows_affected() > 0)
 }
 
-/// Update game status (for game over, forfeit, etc.)
+/// End an active game (checkmate, resignation, a draw, etc.)
+///
+/// `draw_reason` says why a drawn game was drawn and `win_reason` how a
+/// decisive one was won; the other is None. Returns false (and changes
+/// nothing) if the game had already finished.
 pub async fn update_status(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     game_id: Uuid,
     new_status: &str,
-) -> Result<()> {
-    sqlx::query(
+    draw_reason: Option<&str>,
+    win_reason: Option<&str>,
+) -> Result<bool> {
+    let result = sqlx::query(
         r#"
         UPDATE games
         SET status = $1,
-            completed_at = NOW()
-        WHERE id = $2
+            draw_reason = $2,
+            win_reason = $3,
+            completed_at = NOW(),
+            version = version + 1
+        WHERE id = $4
+          AND status = 'active'
         "#,
     )
     .bind(new_status)
+    .bind(draw_reason)
+    .bind(win_reason)
     .bind(game_id)
-    .execute(pool)
+    .execute(executor)
     .await?;
 
-    Ok(())
+    Ok(result.rows_affected() > 0)
 }
 
 /// Find games with expired deadlines
@@ -157,7 +492,8 @@ pub async fn find_expired_deadlines(pool: &PgPool) -> Result<Vec<Game>> {
         r#"
         SELECT id, white_player_id, black_player_id, current_position,
                game_state, status, current_turn, move_deadline,
-               created_at, completed_at
+               created_at, completed_at, takebacks_allowed, move_deadline_hours,
+               is_public, draw_reason, win_reason, version
         FROM games
         WHERE status = 'active'
           AND move_deadline IS NOT NULL
@@ -170,6 +506,172 @@ pub async fn find_expired_deadlines(pool: &PgPool) -> Result<Vec<Game>> {
     Ok(games)
 }
 
+/// End an active game whose deadline has passed
+///
+/// Returns false (and changes nothing) if the game already finished or a
+/// move has extended the deadline since it was found, so repeated sweeps
+/// are harmless.
+pub async fn complete_expired(pool: &PgPool, game_id: Uuid, new_status: &str) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE games
+        SET status = $1,
+            win_reason = 'timeout',
+            completed_at = NOW(),
+            version = version + 1
+        WHERE id = $2
+          AND status = 'active'
+          AND move_deadline < NOW()
+        "#,
+    )
+    .bind(new_status)
+    .bind(game_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Find active games with no moves that were created more than `hours` ago
+pub async fn find_abandonable(pool: &PgPool, hours: i64) -> Result<Vec<Game>> {
+    let games = sqlx::query_as::<_, Game>(
+        r#"
+        SELECT id, white_player_id, black_player_id, current_position,
+               game_state, status, current_turn, move_deadline,
+               created_at, completed_at, takebacks_allowed, move_deadline_hours,
+               is_public, draw_reason, win_reason, version
+        FROM games
+        WHERE status = 'active'
+          AND created_at < NOW() - INTERVAL '1 hour' * $1
+          AND NOT EXISTS (SELECT 1 FROM moves WHERE moves.game_id = games.id)
+        "#,
+    )
+    .bind(hours)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games)
+}
+
+/// Mark an active game with no moves as abandoned
+///
+/// Returns false (and changes nothing) if the game finished or its first
+/// move was played since it was found.
+pub async fn abandon_unstarted(pool: &PgPool, game_id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE games
+        SET status = 'abandoned',
+            completed_at = NOW()
+        WHERE id = $1
+          AND status = 'active'
+          AND NOT EXISTS (SELECT 1 FROM moves WHERE moves.game_id = games.id)
+        "#,
+    )
+    .bind(game_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record a takeback request by `user_id` on an active game
+///
+/// Returns false if the game finished or the opponent already asked.
+pub async fn request_takeback(pool: &PgPool, game_id: Uuid, user_id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE games
+        SET takeback_requested_by = $2
+        WHERE id = $1
+          AND status = 'active'
+          AND (takeback_requested_by IS NULL OR takeback_requested_by = $2)
+        "#,
+    )
+    .bind(game_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the player with a pending takeback request, if any
+pub async fn find_takeback_request(pool: &PgPool, game_id: Uuid) -> Result<Option<Uuid>> {
+    let requested_by: Option<(Option<Uuid>,)> = sqlx::query_as(
+        r#"
+        SELECT takeback_requested_by FROM games WHERE id = $1
+        "#,
+    )
+    .bind(game_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(requested_by.and_then(|(user_id,)| user_id))
+}
+
+/// Clear a takeback request by `requested_by`
+///
+/// Returns false if there was no such request, e.g. it was already answered.
+pub async fn clear_takeback_request(
+    executor: impl PgExecutor<'_>,
+    game_id: Uuid,
+    requested_by: Uuid,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE games
+        SET takeback_requested_by = NULL
+        WHERE id = $1
+          AND takeback_requested_by = $2
+        "#,
+    )
+    .bind(game_id)
+    .bind(requested_by)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Put an active game back to an earlier position after a takeback
+///
+/// Like `update_after_move`, only applies at `expected_version` and returns
+/// false if the game changed or finished in the meantime.
+pub async fn restore_position(
+    executor: impl PgExecutor<'_>,
+    game_id: Uuid,
+    expected_version: i32,
+    position: &str,
+    state: &JsonValue,
+    turn: &str,
+    deadline: Option<DateTime<Utc>>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE games
+        SET current_position = $1,
+            game_state = $2,
+            current_turn = $3,
+            move_deadline = $4,
+            version = version + 1
+        WHERE id = $5
+          AND status = 'active'
+          AND version = $6
+        "#,
+    )
+    .bind(position)
+    .bind(state)
+    .bind(turn)
+    .bind(deadline)
+    .bind(game_id)
+    .bind(expected_version)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 /// Find games with approaching deadlines
 pub async fn find_approaching_deadlines(
     pool: &PgPool,
@@ -179,7 +681,8 @@ pub async fn find_approaching_deadlines(
         r#"
         SELECT id, white_player_id, black_player_id, current_position,
                game_state, status, current_turn, move_deadline,
-               created_at, completed_at
+               created_at, completed_at, takebacks_allowed, move_deadline_hours,
+               is_public, draw_reason, win_reason, version
         FROM games
         WHERE status = 'active'
           AND move_deadline IS NOT NULL
@@ -193,3 +696,45 @@ pub async fn find_approaching_deadlines(
 
     Ok(games)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_pair_ignores_colors() {
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+
+        assert_eq!(player_pair(alice, bob), player_pair(bob, alice));
+        let (low, high) = player_pair(alice, bob);
+        assert!(low < high);
+        assert_ne!(player_pair(alice, bob), player_pair(alice, Uuid::new_v4()));
+    }
+
+    #[test]
+    fn test_tally_stats_from_finished_games() {
+        // Seeded results: as White 3 wins, 1 loss, 2 draws; as Black 1 win, 2 losses
+        let counts: Vec<(String, String, i64)> = [
+            ("white_won", "white", 3),
+            ("black_won", "white", 1),
+            ("draw", "white", 2),
+            ("black_won", "black", 1),
+            ("white_won", "black", 2),
+        ]
+        .iter()
+        .map(|(status, color, n)| (status.to_string(), color.to_string(), *n))
+        .collect();
+
+        assert_eq!(
+            tally_stats(&counts),
+            UserStats {
+                played: 9,
+                won: 4,
+                lost: 3,
+                drawn: 2,
+            }
+        );
+        assert_eq!(tally_stats(&[]), UserStats::default());
+    }
+}
@@ -0,0 +1,69 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+use anyhow::Result;
+
+/// Find the response saved for a move submitted with `key` in the last day
+pub async fn find_move_response(
+    pool: &PgPool,
+    key: &str,
+    game_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<JsonValue>> {
+    let row: Option<(JsonValue,)> = sqlx::query_as(
+        r#"
+        SELECT response
+        FROM move_idempotency_keys
+        WHERE idempotency_key = $1
+          AND game_id = $2
+          AND user_id = $3
+          AND created_at > NOW() - INTERVAL '1 day'
+        "#,
+    )
+    .bind(key)
+    .bind(game_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(response,)| response))
+}
+
+/// Save the response to a move submitted with `key`, dropping expired keys
+///
+/// Meant to run in the transaction that saves the move, so the two are
+/// stored together. A key that is already saved keeps its first response.
+pub async fn save_move_response(
+    conn: &mut PgConnection,
+    key: &str,
+    game_id: Uuid,
+    user_id: Uuid,
+    move_id: Uuid,
+    response: &JsonValue,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM move_idempotency_keys
+        WHERE created_at < NOW() - INTERVAL '1 day'
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO move_idempotency_keys (idempotency_key, game_id, user_id, move_id, response)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (idempotency_key, game_id, user_id) DO NOTHING
+        "#,
+    )
+    .bind(key)
+    .bind(game_id)
+    .bind(user_id)
+    .bind(move_id)
+    .bind(response)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
@@ -1,7 +1,8 @@
 pub mod users;
 pub mod games;
 pub mod moves;
-
-pub use users::*;
-pub use games::*;
-pub use moves::*;
+pub mod tokens;
+pub mod challenges;
+pub mod idempotency;
+pub mod conditional_moves;
+pub mod tournaments;
@@ -1,20 +1,22 @@
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 use anyhow::Result;
 
-use crate::models::{MoveRecord, NewMove};
+use crate::models::{MoveRecord, NewMove, PositionContinuation};
 
 /// Insert a new move
-pub async fn create_move(pool: &PgPool, new_move: &NewMove) -> Result<MoveRecord> {
+pub async fn create_move(executor: impl PgExecutor<'_>, new_move: &NewMove) -> Result<MoveRecord> {
     let move_record = sqlx::query_as::<_, MoveRecord>(
         r#"
         INSERT INTO moves (
             game_id, move_number, player_color, move_uci,
-            move_san, position_before, position_after
+            move_san, position_before, position_after, time_taken_seconds,
+            comment, is_terminal
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING id, game_id, move_number, player_color, move_uci,
-                  move_san, position_before, position_after, timestamp
+                  move_san, position_before, position_after, timestamp,
+                  time_taken_seconds, comment, is_terminal
         "#,
     )
     .bind(new_move.game_id)
@@ -24,7 +26,10 @@ pub async fn create_move(pool: &PgPool, new_move: &NewMove) -> Result<MoveRecord
     .bind(&new_move.move_san)
     .bind(&new_move.position_before)
     .bind(&new_move.position_after)
-    .fetch_one(pool)
+    .bind(new_move.time_taken_seconds)
+    .bind(&new_move.comment)
+    .bind(new_move.is_terminal)
+    .fetch_one(executor)
     .await?;
 
     Ok(move_record)
@@ -35,10 +40,11 @@ pub async fn list_by_game(pool: &PgPool, game_id: Uuid) -> Result<Vec<MoveRecord
     let moves = sqlx::query_as::<_, MoveRecord>(
         r#"
         SELECT id, game_id, move_number, player_color, move_uci,
-               move_san, position_before, position_after, timestamp
+               move_san, position_before, position_after, timestamp,
+               time_taken_seconds, comment, is_terminal
         FROM moves
         WHERE game_id = $1
-        ORDER BY move_number ASC, player_color ASC
+        ORDER BY move_number ASC, player_color DESC -- 'white' before 'black'
         "#,
     )
     .bind(game_id)
@@ -48,15 +54,36 @@ pub async fn list_by_game(pool: &PgPool, game_id: Uuid) -> Result<Vec<MoveRecord
     Ok(moves)
 }
 
+/// Every move of several games in one query, grouped by game and in play
+/// order within each
+pub async fn list_by_games(pool: &PgPool, game_ids: &[Uuid]) -> Result<Vec<MoveRecord>> {
+    let moves = sqlx::query_as::<_, MoveRecord>(
+        r#"
+        SELECT id, game_id, move_number, player_color, move_uci,
+               move_san, position_before, position_after, timestamp,
+               time_taken_seconds, comment, is_terminal
+        FROM moves
+        WHERE game_id = ANY($1)
+        ORDER BY game_id, move_number ASC, player_color DESC -- 'white' before 'black'
+        "#,
+    )
+    .bind(game_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(moves)
+}
+
 /// Get the last move for a game
 pub async fn get_last_move(pool: &PgPool, game_id: Uuid) -> Result<Option<MoveRecord>> {
     let move_record = sqlx::query_as::<_, MoveRecord>(
         r#"
         SELECT id, game_id, move_number, player_color, move_uci,
-               move_san, position_before, position_after, timestamp
+               move_san, position_before, position_after, timestamp,
+               time_taken_seconds, comment, is_terminal
         FROM moves
         WHERE game_id = $1
-        ORDER BY move_number DESC, player_color DESC
+        ORDER BY move_number DESC, player_color ASC -- 'black' after 'white'
         LIMIT 1
         "#,
     )
@@ -67,16 +94,76 @@ pub async fn get_last_move(pool: &PgPool, game_id: Uuid) -> Result<Option<MoveRe
     Ok(move_record)
 }
 
+/// Delete the last `count` moves of a game, returning them in play order
+pub async fn delete_last(
+    executor: impl PgExecutor<'_>,
+    game_id: Uuid,
+    count: i64,
+) -> Result<Vec<MoveRecord>> {
+    let mut deleted = sqlx::query_as::<_, MoveRecord>(
+        r#"
+        DELETE FROM moves
+        WHERE id IN (
+            SELECT id FROM moves
+            WHERE game_id = $1
+            ORDER BY move_number DESC, player_color ASC -- 'black' after 'white'
+            LIMIT $2
+        )
+        RETURNING id, game_id, move_number, player_color, move_uci,
+                  move_san, position_before, position_after, timestamp,
+                  time_taken_seconds, comment, is_terminal
+        "#,
+    )
+    .bind(game_id)
+    .bind(count)
+    .fetch_all(executor)
+    .await?;
+
+    deleted.sort_by_key(|m| (m.move_number, m.player_color == "black"));
+    Ok(deleted)
+}
+
 /// Count moves for a game
-pub async fn count_by_game(pool: &PgPool, game_id: Uuid) -> Result<i64> {
+pub async fn count_by_game(executor: impl PgExecutor<'_>, game_id: Uuid) -> Result<i64> {
     let count: (i64,) = sqlx::query_as(
         r#"
         SELECT COUNT(*) FROM moves WHERE game_id = $1
         "#,
     )
     .bind(game_id)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     Ok(count.0)
 }
+
+/// List the moves played from a position across public finished games
+///
+/// Private games stay private, and imported games weren't played here.
+/// Stored FENs record the en passant square after every double push, so a
+/// key without one (no capture possible) matches whatever square was stored.
+pub async fn list_continuations(
+    pool: &PgPool,
+    position_key: &str,
+) -> Result<Vec<PositionContinuation>> {
+    let (board, en_passant) = position_key.rsplit_once(' ').unwrap_or((position_key, "-"));
+
+    let continuations = sqlx::query_as::<_, PositionContinuation>(
+        r#"
+        SELECT m.move_uci, m.move_san, g.status as game_status, g.is_public, g.imported
+        FROM moves m
+        JOIN games g ON m.game_id = g.id
+        WHERE g.status IN ('white_won', 'black_won', 'draw')
+          AND g.is_public
+          AND NOT g.imported
+          AND m.position_before LIKE $1 || ' %'
+          AND ($2 = '-' OR split_part(m.position_before, ' ', 4) = $2)
+        "#,
+    )
+    .bind(board)
+    .bind(en_passant)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(continuations)
+}
@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::Result;
+
+use crate::models::RefreshToken;
+
+/// Store a new refresh token by its hash
+pub async fn create_refresh_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<RefreshToken> {
+    let token = sqlx::query_as::<_, RefreshToken>(
+        r#"
+        INSERT INTO refresh_tokens (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        RETURNING id, user_id, token_hash, expires_at, created_at, revoked_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(token)
+}
+
+/// Find a refresh token by its hash, whether or not it is still usable
+pub async fn find_refresh_token(pool: &PgPool, token_hash: &str) -> Result<Option<RefreshToken>> {
+    let token = sqlx::query_as::<_, RefreshToken>(
+        r#"
+        SELECT id, user_id, token_hash, expires_at, created_at, revoked_at
+        FROM refresh_tokens
+        WHERE token_hash = $1
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(token)
+}
+
+/// Revoke a refresh token
+///
+/// Returns false (and changes nothing) if it was already revoked, so two
+/// concurrent refreshes with the same token can't both succeed.
+pub async fn revoke_refresh_token(pool: &PgPool, id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW()
+        WHERE id = $1 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke every outstanding refresh token of a user
+pub async fn revoke_all_for_user(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW()
+        WHERE user_id = $1 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Deny an access token until it expires
+pub async fn revoke_jti(pool: &PgPool, jti: &str, expires_at: DateTime<Utc>) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO revoked_tokens (jti, expires_at)
+        VALUES ($1, $2)
+        ON CONFLICT (jti) DO NOTHING
+        "#,
+    )
+    .bind(jti)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Check whether an access token has been revoked
+pub async fn is_jti_revoked(pool: &PgPool, jti: &str) -> Result<bool> {
+    let revoked: (bool,) = sqlx::query_as(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)
+        "#,
+    )
+    .bind(jti)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(revoked.0)
+}
+
+/// Forget revoked access tokens that have expired anyway
+pub async fn purge_expired_revocations(pool: &PgPool) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM revoked_tokens WHERE expires_at < NOW()
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,128 @@
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+use anyhow::Result;
+
+use crate::models::{GameWithPlayers, Tournament, TournamentPlayer};
+
+/// Create a tournament with no participants yet
+pub async fn create_tournament(
+    executor: impl PgExecutor<'_>,
+    name: &str,
+    format: &str,
+    created_by: Uuid,
+) -> Result<Tournament> {
+    let tournament = sqlx::query_as::<_, Tournament>(
+        r#"
+        INSERT INTO tournaments (name, format, created_by)
+        VALUES ($1, $2, $3)
+        RETURNING id, name, format, created_by, created_at
+        "#,
+    )
+    .bind(name)
+    .bind(format)
+    .bind(created_by)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(tournament)
+}
+
+/// Find a tournament by ID
+pub async fn find_by_id(pool: &PgPool, tournament_id: Uuid) -> Result<Option<Tournament>> {
+    let tournament = sqlx::query_as::<_, Tournament>(
+        r#"
+        SELECT id, name, format, created_by, created_at
+        FROM tournaments
+        WHERE id = $1
+        "#,
+    )
+    .bind(tournament_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(tournament)
+}
+
+pub async fn add_participant(
+    executor: impl PgExecutor<'_>,
+    tournament_id: Uuid,
+    user_id: Uuid,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tournament_participants (tournament_id, user_id)
+        VALUES ($1, $2)
+        "#,
+    )
+    .bind(tournament_id)
+    .bind(user_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn add_game(
+    executor: impl PgExecutor<'_>,
+    tournament_id: Uuid,
+    game_id: Uuid,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tournament_games (tournament_id, game_id)
+        VALUES ($1, $2)
+        "#,
+    )
+    .bind(tournament_id)
+    .bind(game_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// A tournament's participants, by username
+pub async fn list_participants(
+    pool: &PgPool,
+    tournament_id: Uuid,
+) -> Result<Vec<TournamentPlayer>> {
+    let players = sqlx::query_as::<_, TournamentPlayer>(
+        r#"
+        SELECT u.id as user_id, u.username
+        FROM tournament_participants tp
+        JOIN users u ON tp.user_id = u.id
+        WHERE tp.tournament_id = $1
+        ORDER BY u.username
+        "#,
+    )
+    .bind(tournament_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(players)
+}
+
+/// A tournament's games, in the order they were paired
+pub async fn list_games(pool: &PgPool, tournament_id: Uuid) -> Result<Vec<GameWithPlayers>> {
+    let games = sqlx::query_as::<_, GameWithPlayers>(
+        r#"
+        SELECT
+            g.id, g.white_player_id, g.black_player_id,
+            w.username as white_player_username,
+            b.username as black_player_username,
+            g.current_position, g.status, g.current_turn,
+            g.move_deadline, g.created_at
+        FROM tournament_games tg
+        JOIN games g ON tg.game_id = g.id
+        JOIN users w ON g.white_player_id = w.id
+        JOIN users b ON g.black_player_id = b.id
+        WHERE tg.tournament_id = $1
+        ORDER BY g.created_at, g.id
+        "#,
+    )
+    .bind(tournament_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(games)
+}
@@ -1,37 +1,46 @@
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 use anyhow::Result;
 
-use crate::models::{User, NewUser};
+use crate::models::{EmailVerificationToken, User, NewUser};
+
+/// Lookup key for a username: usernames match regardless of case or
+/// surrounding whitespace
+pub fn username_key(username: &str) -> String {
+    username.trim().to_lowercase()
+}
 
 /// Create a new user
 pub async fn create_user(pool: &PgPool, new_user: &NewUser) -> Result<User> {
     let user = sqlx::query_as::<_, User>(
         r#"
-        INSERT INTO users (username, password_hash, email)
-        VALUES ($1, $2, $3)
-        RETURNING id, username, password_hash, email, created_at, last_seen
+        INSERT INTO users (username, username_lower, password_hash, email, verified)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, username, password_hash, email, created_at, last_seen, verified
         "#,
     )
     .bind(&new_user.username)
+    .bind(username_key(&new_user.username))
     .bind(&new_user.password_hash)
     .bind(&new_user.email)
+    .bind(new_user.verified)
     .fetch_one(pool)
     .await?;
 
     Ok(user)
 }
 
-/// Find a user by username
+/// Find a user by username, ignoring case
 pub async fn find_by_username(pool: &PgPool, username: &str) -> Result<Option<User>> {
     let user = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, username, password_hash, email, created_at, last_seen
+        SELECT id, username, password_hash, email, created_at, last_seen, verified
         FROM users
-        WHERE username = $1
+        WHERE username_lower = $1
         "#,
     )
-    .bind(username)
+    .bind(username_key(username))
     .fetch_optional(pool)
     .await?;
 
@@ -39,26 +48,46 @@ pub async fn find_by_username(pool: &PgPool, username: &str) -> Result<Option<Us
 }
 
 /// Find a user by ID
-pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<User>> {
+pub async fn find_by_id(executor: impl PgExecutor<'_>, id: Uuid) -> Result<Option<User>> {
     let user = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, username, password_hash, email, created_at, last_seen
+        SELECT id, username, password_hash, email, created_at, last_seen, verified
         FROM users
         WHERE id = $1
         "#,
     )
     .bind(id)
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await?;
 
     Ok(user)
 }
 
+/// Lock the given users' rows until the surrounding transaction ends
+///
+/// Rows are locked in id order, so two transactions locking the same users
+/// wait for each other instead of deadlocking.
+pub async fn lock_for_update(executor: impl PgExecutor<'_>, ids: &[Uuid]) -> Result<()> {
+    sqlx::query(
+        r#"
+        SELECT id FROM users
+        WHERE id = ANY($1)
+        ORDER BY id
+        FOR UPDATE
+        "#,
+    )
+    .bind(ids)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(())
+}
+
 /// Find a user by email
 pub async fn find_by_email(pool: &PgPool, email: &str) -> Result<Option<User>> {
     let user = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, username, password_hash, email, created_at, last_seen
+        SELECT id, username, password_hash, email, created_at, last_seen, verified
         FROM users
         WHERE email = $1
         "#,
@@ -74,7 +103,7 @@ pub async fn find_by_email(pool: &PgPool, email: &str) -> Result<Option<User>> {
 pub async fn list_users(pool: &PgPool) -> Result<Vec<User>> {
     let users = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, username, password_hash, email, created_at, last_seen
+        SELECT id, username, password_hash, email, created_at, last_seen, verified
         FROM users
         ORDER BY username ASC
         "#,
@@ -100,3 +129,147 @@ pub async fn update_last_seen(pool: &PgPool, user_id: Uuid) -> Result<()> {
 
     Ok(())
 }
+
+/// Update a user's email address
+pub async fn update_email(pool: &PgPool, user_id: Uuid, email: &str) -> Result<User> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET email = $2
+        WHERE id = $1
+        RETURNING id, username, password_hash, email, created_at, last_seen, verified
+        "#,
+    )
+    .bind(user_id)
+    .bind(email)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(user)
+}
+
+/// Store a pending email change, replacing any earlier request from the user
+pub async fn set_pending_email(
+    pool: &PgPool,
+    user_id: Uuid,
+    new_email: &str,
+    token: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO pending_email_changes (user_id, new_email, token)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id)
+        DO UPDATE SET new_email = $2, token = $3, created_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(new_email)
+    .bind(token)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove and return the pending email for a user if the token matches
+pub async fn take_pending_email(
+    pool: &PgPool,
+    user_id: Uuid,
+    token: &str,
+) -> Result<Option<String>> {
+    let new_email = sqlx::query_scalar::<_, String>(
+        r#"
+        DELETE FROM pending_email_changes
+        WHERE user_id = $1 AND token = $2
+        RETURNING new_email
+        "#,
+    )
+    .bind(user_id)
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(new_email)
+}
+
+/// Store a token for verifying a new account's email address
+pub async fn create_verification_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    token: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO email_verification_tokens (token, user_id, expires_at)
+        VALUES ($1, $2, $3)
+        "#,
+    )
+    .bind(token)
+    .bind(user_id)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Find an email verification token
+pub async fn find_verification_token(
+    pool: &PgPool,
+    token: &str,
+) -> Result<Option<EmailVerificationToken>> {
+    let record = sqlx::query_as::<_, EmailVerificationToken>(
+        r#"
+        SELECT token, user_id, expires_at, created_at
+        FROM email_verification_tokens
+        WHERE token = $1
+        "#,
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record)
+}
+
+/// Mark a user's email as verified and drop their outstanding tokens
+pub async fn mark_verified(pool: &PgPool, user_id: Uuid) -> Result<User> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET verified = TRUE
+        WHERE id = $1
+        RETURNING id, username, password_hash, email, created_at, last_seen, verified
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM email_verification_tokens
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_username_key_ignores_case_and_whitespace() {
+        assert_eq!(username_key("Alice"), "alice");
+        assert_eq!(username_key("alice"), username_key("ALICE"));
+        assert_eq!(username_key("  Alice "), "alice");
+        assert_ne!(username_key("alice"), username_key("alice2"));
+    }
+}
@@ -1,9 +1,12 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde_json::json;
+use shared::protocol::ErrorResponse;
+use shared::types::{Color, GameStatus};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -17,12 +20,41 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Not found: {0}")]
     NotFound(String),
 
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Illegal move: {0}")]
+    IllegalMove(String),
+
+    /// The request lost a race with another change to the same resource
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// The game has already finished, so it can't be played on; tells the
+    /// client how it ended
+    #[error("Game is not active")]
+    GameNotActive {
+        status: GameStatus,
+        winner: Option<Color>,
+    },
+
+    /// A move was submitted out of turn; tells the client whose turn it is
+    #[error("It's not your turn")]
+    NotYourTurn {
+        current_turn: Color,
+        deadline: Option<DateTime<Utc>>,
+    },
+
+    /// Too many failed attempts; the client may retry after this many seconds
+    #[error("Too many attempts, try again later")]
+    TooManyRequests { retry_after_secs: u64 },
+
     #[error("Internal server error: {0}")]
     Internal(#[from] anyhow::Error),
 
@@ -33,37 +65,257 @@ pub enum AppError {
     Jwt(#[from] jsonwebtoken::errors::Error),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::Database(ref e) => {
+impl AppError {
+    /// Stable machine-readable code, sent as `code` in error responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => "database_error",
+            AppError::Auth(_) => "unauthorized",
+            AppError::Validation(_) => "validation_failed",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::NotFound(_) => "not_found",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::IllegalMove(_) => "illegal_move",
+            AppError::Conflict(_) => "conflict",
+            AppError::GameNotActive { .. } => "game_not_active",
+            AppError::NotYourTurn { .. } => "not_your_turn",
+            AppError::TooManyRequests { .. } => "too_many_requests",
+            AppError::Internal(_) => "internal_error",
+            AppError::Bcrypt(_) => "internal_error",
+            AppError::Jwt(_) => "invalid_token",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::Database(_) | AppError::Internal(_) | AppError::Bcrypt(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            AppError::Auth(_) | AppError::Jwt(_) => StatusCode::UNAUTHORIZED,
+            AppError::Validation(_) | AppError::BadRequest(_) | AppError::IllegalMove(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_)
+            | AppError::GameNotActive { .. }
+            | AppError::NotYourTurn { .. } => StatusCode::CONFLICT,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    /// Message shown to the user; server-side failures are logged, not exposed
+    fn message(&self) -> String {
+        match self {
+            AppError::Database(e) => {
                 tracing::error!("Database error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+                "Database error".to_string()
             }
-            AppError::Auth(ref msg) => (StatusCode::UNAUTHORIZED, msg.as_str()),
-            AppError::Validation(ref msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
-            AppError::NotFound(ref msg) => (StatusCode::NOT_FOUND, msg.as_str()),
-            AppError::BadRequest(ref msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
-            AppError::Internal(ref e) => {
+            AppError::Internal(e) => {
                 tracing::error!("Internal error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+                "Internal server error".to_string()
             }
-            AppError::Bcrypt(ref e) => {
+            AppError::Bcrypt(e) => {
                 tracing::error!("Bcrypt error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Password hashing error")
+                "Password hashing error".to_string()
             }
-            AppError::Jwt(ref e) => {
+            AppError::Jwt(e) => {
                 tracing::error!("JWT error: {:?}", e);
-                (StatusCode::UNAUTHORIZED, "Invalid token")
+                "Invalid token".to_string()
             }
+            AppError::Auth(msg)
+            | AppError::Validation(msg)
+            | AppError::Forbidden(msg)
+            | AppError::NotFound(msg)
+            | AppError::BadRequest(msg)
+            | AppError::IllegalMove(msg)
+            | AppError::Conflict(msg) => msg.clone(),
+            AppError::GameNotActive { .. }
+            | AppError::NotYourTurn { .. }
+            | AppError::TooManyRequests { .. } => self.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let response = ErrorResponse {
+            error: self.message(),
+            details: None,
+            code: self.code().to_string(),
         };
 
-        let body = Json(json!({
-            "error": error_message,
-        }));
+        let mut body = json!(response);
+        match &self {
+            AppError::NotYourTurn {
+                current_turn,
+                deadline,
+            } => {
+                body["current_turn"] = json!(current_turn);
+                body["deadline"] = json!(deadline);
+            }
+            AppError::GameNotActive { status, winner } => {
+                body["status"] = json!(status);
+                body["winner"] = json!(winner);
+            }
+            _ => {}
+        }
+
+        let mut response = (self.status(), Json(body)).into_response();
+        if let AppError::TooManyRequests { retry_after_secs } = &self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(*retry_after_secs));
+        }
 
-        (status, body).into_response()
+        response
     }
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_of(error: AppError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn test_codes_and_statuses() {
+        let cases = [
+            (
+                AppError::Database(sqlx::Error::RowNotFound),
+                "database_error",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                AppError::Auth("x".into()),
+                "unauthorized",
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                AppError::Validation("x".into()),
+                "validation_failed",
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                AppError::Forbidden("x".into()),
+                "forbidden",
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                AppError::NotFound("x".into()),
+                "not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                AppError::BadRequest("x".into()),
+                "bad_request",
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                AppError::IllegalMove("x".into()),
+                "illegal_move",
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                AppError::Conflict("x".into()),
+                "conflict",
+                StatusCode::CONFLICT,
+            ),
+            (
+                AppError::GameNotActive {
+                    status: GameStatus::Draw,
+                    winner: None,
+                },
+                "game_not_active",
+                StatusCode::CONFLICT,
+            ),
+            (
+                AppError::NotYourTurn {
+                    current_turn: Color::White,
+                    deadline: None,
+                },
+                "not_your_turn",
+                StatusCode::CONFLICT,
+            ),
+            (
+                AppError::TooManyRequests {
+                    retry_after_secs: 1,
+                },
+                "too_many_requests",
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            (
+                AppError::Internal(anyhow::anyhow!("x")),
+                "internal_error",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                AppError::Bcrypt(bcrypt::BcryptError::InvalidCost("x".into())),
+                "internal_error",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                AppError::Jwt(jsonwebtoken::errors::ErrorKind::InvalidToken.into()),
+                "invalid_token",
+                StatusCode::UNAUTHORIZED,
+            ),
+        ];
+
+        for (error, code, status) in cases {
+            assert_eq!(error.code(), code, "{:?}", error);
+            assert_eq!(error.status(), status, "{:?}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_keeps_message_and_hides_internals() {
+        let (status, body) = body_of(AppError::NotFound("Game not found".into())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Game not found");
+        assert_eq!(body["code"], "not_found");
+        assert!(body["details"].is_null());
+
+        let (_, body) = body_of(AppError::Internal(anyhow::anyhow!("secret"))).await;
+        assert_eq!(body["error"], "Internal server error");
+        assert_eq!(body["code"], "internal_error");
+    }
+
+    #[tokio::test]
+    async fn test_not_your_turn_body_has_turn_and_deadline() {
+        let deadline: DateTime<Utc> = "2026-10-20T12:00:00Z".parse().unwrap();
+        let (status, body) = body_of(AppError::NotYourTurn {
+            current_turn: Color::Black,
+            deadline: Some(deadline),
+        })
+        .await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "It's not your turn");
+        assert_eq!(body["code"], "not_your_turn");
+        assert_eq!(body["current_turn"], "black");
+        assert_eq!(body["deadline"], "2026-10-20T12:00:00Z");
+    }
+
+    #[tokio::test]
+    async fn test_game_not_active_body_has_status_and_winner() {
+        let (status, body) = body_of(AppError::GameNotActive {
+            status: GameStatus::WhiteWon,
+            winner: Some(Color::White),
+        })
+        .await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "game_not_active");
+        assert_eq!(body["status"], "white_won");
+        assert_eq!(body["winner"], "white");
+    }
+}
@@ -0,0 +1,258 @@
+use axum::{
+    extract::{FromRequest, FromRequestParts, Path, Request},
+    http::request::Parts,
+    Json,
+};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// Game id from the `{id}` path segment
+///
+/// Malformed ids are reported as `AppError::BadRequest` so clients get the
+/// same JSON error shape as every other failure, instead of axum's plain-text
+/// path rejection.
+#[derive(Debug, Clone, Copy)]
+pub struct GameId(pub Uuid);
+
+impl<S> FromRequestParts<S> for GameId
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        path_param(parts, state, "id")
+            .await
+            .map(GameId)
+            .ok_or_else(|| AppError::BadRequest("Invalid game id".to_string()))
+    }
+}
+
+/// Challenge id from the `{id}` path segment, rejected like `GameId`
+#[derive(Debug, Clone, Copy)]
+pub struct ChallengeId(pub Uuid);
+
+impl<S> FromRequestParts<S> for ChallengeId
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        path_param(parts, state, "id")
+            .await
+            .map(ChallengeId)
+            .ok_or_else(|| AppError::BadRequest("Invalid challenge id".to_string()))
+    }
+}
+
+/// Tournament id from the `{id}` path segment, rejected like `GameId`
+#[derive(Debug, Clone, Copy)]
+pub struct TournamentId(pub Uuid);
+
+impl<S> FromRequestParts<S> for TournamentId
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        path_param(parts, state, "id")
+            .await
+            .map(TournamentId)
+            .ok_or_else(|| AppError::BadRequest("Invalid tournament id".to_string()))
+    }
+}
+
+/// Half-move index from the `{ply}` path segment (0 is the starting position)
+#[derive(Debug, Clone, Copy)]
+pub struct Ply(pub usize);
+
+impl<S> FromRequestParts<S> for Ply
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        path_param(parts, state, "ply")
+            .await
+            .map(Ply)
+            .ok_or_else(|| AppError::BadRequest("Invalid ply".to_string()))
+    }
+}
+
+/// JSON request body, rejected like `GameId`
+///
+/// Malformed JSON, a missing `Content-Type` and bodies over the configured
+/// size limit all come back as `AppError::BadRequest` with axum's reason as
+/// the message.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonBody<T>(pub T);
+
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        Json::<T>::from_request(req, state)
+            .await
+            .map(|Json(value)| JsonBody(value))
+            .map_err(|rejection| AppError::BadRequest(rejection.body_text()))
+    }
+}
+
+/// Parse a named path parameter, returning None if it's missing or malformed
+async fn path_param<S, T>(parts: &mut Parts, state: &S, name: &str) -> Option<T>
+where
+    S: Send + Sync,
+    T: FromStr,
+{
+    let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+        .await
+        .ok()?;
+
+    params.get(name).and_then(|value| value.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{to_bytes, Body},
+        extract::DefaultBodyLimit,
+        http::{Request, StatusCode},
+        routing::{get, post},
+        Router,
+    };
+    use shared::protocol::ErrorResponse;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new().route(
+            "/api/games/{id}",
+            get(|GameId(id): GameId| async move { id.to_string() }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_invalid_game_id_returns_structured_error() {
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/games/not-a-uuid")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: shared::protocol::ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.error, "Invalid game id");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_ply_returns_structured_error() {
+        let app = Router::new().route(
+            "/api/games/{id}/review/{ply}",
+            get(|GameId(_): GameId, Ply(ply): Ply| async move { ply.to_string() }),
+        );
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/games/{}/review/-1", Uuid::new_v4()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: shared::protocol::ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.error, "Invalid ply");
+    }
+
+    async fn echo(JsonBody(body): JsonBody<serde_json::Value>) -> String {
+        body.to_string()
+    }
+
+    fn json_app() -> Router {
+        Router::new()
+            .route("/api/games", post(echo))
+            .layer(DefaultBodyLimit::max(16))
+    }
+
+    async fn post_json(body: &'static str) -> (StatusCode, Option<ErrorResponse>) {
+        let response = json_app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/games")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).ok())
+    }
+
+    #[tokio::test]
+    async fn test_malformed_json_returns_structured_error() {
+        let (status, error) = post_json("{\"a\": ").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.unwrap().code, "bad_request");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_returns_structured_error() {
+        let (status, error) = post_json("{\"a\": \"0123456789abcdef\"}").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error = error.unwrap();
+        assert_eq!(error.code, "bad_request");
+        assert!(error.error.contains("length limit"), "{}", error.error);
+    }
+
+    #[tokio::test]
+    async fn test_json_within_limit_is_extracted() {
+        let (status, _) = post_json("{\"a\": 1}").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_valid_game_id_is_extracted() {
+        let id = Uuid::new_v4();
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/games/{}", id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, id.to_string());
+    }
+}
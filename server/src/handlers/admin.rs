@@ -0,0 +1,23 @@
+use axum::{extract::State, Json};
+
+use crate::error::Result;
+use crate::extractors::GameId;
+use crate::AppState;
+use shared::protocol::{GameAuditResponse, GameVerificationResponse};
+
+/// Verify a game's stored positions by replaying its moves
+pub async fn verify_game(
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<Json<GameVerificationResponse>> {
+    let report = state.game_service.verify_game(&state.db, game_id).await?;
+
+    Ok(Json(report))
+}
+
+/// Verify every game, listing the ones whose stored positions are corrupt
+pub async fn audit_games(State(state): State<AppState>) -> Result<Json<GameAuditResponse>> {
+    let report = state.game_service.audit_games(&state.db).await?;
+
+    Ok(Json(report))
+}
@@ -1,51 +1,101 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    Json,
+};
 
 use crate::error::Result;
+use crate::extractors::JsonBody;
+use crate::middleware::AuthUser;
+use crate::models::User;
+use crate::services::Tokens;
 use crate::AppState;
-use shared::protocol::{AuthResponse, LoginRequest, RegisterRequest};
+use shared::protocol::{
+    AuthResponse, LoginRequest, LogoutRequest, RefreshRequest, RegisterRequest, VerifyEmailRequest,
+};
 
 /// Register a new user
 pub async fn register(
     State(state): State<AppState>,
-    Json(req): Json<RegisterRequest>,
+    JsonBody(req): JsonBody<RegisterRequest>,
 ) -> Result<(StatusCode, Json<AuthResponse>)> {
-    let (user, token) = state
+    let (user, tokens) = state
         .auth_service
         .register(&state.db, req.username, req.email, req.password)
         .await?;
 
-    let response = AuthResponse {
-        token,
-        user: shared::types::User {
-            id: user.id,
-            username: user.username,
-            email: user.email,
-            created_at: user.created_at,
-        },
-    };
-
-    Ok((StatusCode::CREATED, Json(response)))
+    Ok((StatusCode::CREATED, Json(auth_response(user, tokens))))
 }
 
 /// Login a user
 pub async fn login(
     State(state): State<AppState>,
-    Json(req): Json<LoginRequest>,
+    JsonBody(req): JsonBody<LoginRequest>,
 ) -> Result<Json<AuthResponse>> {
-    let (user, token) = state
+    let (user, tokens) = state
         .auth_service
         .login(&state.db, req.username, req.password)
         .await?;
 
-    let response = AuthResponse {
-        token,
+    Ok(Json(auth_response(user, tokens)))
+}
+
+/// Trade a refresh token for a new access token and refresh token
+pub async fn refresh(
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<RefreshRequest>,
+) -> Result<Json<AuthResponse>> {
+    let (user, tokens) = state
+        .auth_service
+        .refresh(&state.db, &req.refresh_token)
+        .await?;
+
+    Ok(Json(auth_response(user, tokens)))
+}
+
+/// Log out, revoking the access token used and the given refresh token
+pub async fn logout(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<LogoutRequest>,
+) -> Result<StatusCode> {
+    state
+        .auth_service
+        .logout(&state.db, &auth, req.refresh_token.as_deref())
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Verify a new account's email address
+pub async fn verify_email(
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<VerifyEmailRequest>,
+) -> Result<Json<shared::types::User>> {
+    let user = state
+        .auth_service
+        .verify_email(&state.db, &req.token)
+        .await?;
+
+    Ok(Json(shared::types::User {
+        id: user.id,
+        username: user.username,
+        email: user.email,
+        created_at: user.created_at,
+        verified: user.verified,
+    }))
+}
+
+fn auth_response(user: User, tokens: Tokens) -> AuthResponse {
+    AuthResponse {
+        token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
         user: shared::types::User {
             id: user.id,
             username: user.username,
             email: user.email,
             created_at: user.created_at,
+            verified: user.verified,
         },
-    };
-
-    Ok(Json(response))
+    }
 }
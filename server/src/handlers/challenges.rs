@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    Json,
+};
+
+use super::games::game_response;
+use crate::db::users;
+use crate::error::{AppError, Result};
+use crate::extractors::{ChallengeId, JsonBody};
+use crate::middleware::AuthUser;
+use crate::models::ChallengeWithPlayers;
+use crate::services::ensure_verified;
+use crate::AppState;
+use shared::protocol::{ChallengeListResponse, CreateGameRequest, GameResponse};
+use shared::types::{ChallengeInfo, ChallengeStatus, Color};
+
+/// Challenge another player to a game
+pub async fn send_challenge(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    JsonBody(request): JsonBody<CreateGameRequest>,
+) -> Result<(StatusCode, Json<ChallengeInfo>)> {
+    // Same requirements as starting a game directly
+    let user = users::find_by_id(&state.db, auth.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    ensure_verified(&user)?;
+    state
+        .game_service
+        .ensure_can_create_games(&state.db, auth.user_id)
+        .await?;
+
+    let challenge = state
+        .challenge_service
+        .create(&state.db, auth.user_id, request)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(challenge_info(challenge))))
+}
+
+/// List pending challenges sent to and by the authenticated user
+pub async fn list_challenges(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+) -> Result<Json<ChallengeListResponse>> {
+    let (incoming, outgoing) = state
+        .challenge_service
+        .list(&state.db, auth.user_id)
+        .await?;
+
+    Ok(Json(ChallengeListResponse {
+        incoming: incoming.into_iter().map(challenge_info).collect(),
+        outgoing: outgoing.into_iter().map(challenge_info).collect(),
+    }))
+}
+
+/// Accept a challenge, starting the game
+pub async fn accept_challenge(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    ChallengeId(challenge_id): ChallengeId,
+) -> Result<(StatusCode, Json<GameResponse>)> {
+    let game = state
+        .challenge_service
+        .accept(&state.db, challenge_id, auth.user_id)
+        .await?;
+
+    let response = game_response(&state, game, Some(auth.user_id)).await?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Decline a challenge
+pub async fn decline_challenge(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    ChallengeId(challenge_id): ChallengeId,
+) -> Result<StatusCode> {
+    state
+        .challenge_service
+        .decline(&state.db, challenge_id, auth.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn challenge_info(challenge: ChallengeWithPlayers) -> ChallengeInfo {
+    ChallengeInfo {
+        id: challenge.id,
+        challenger_username: challenge.challenger_username,
+        challenged_username: challenge.challenged_username,
+        color_preference: match challenge.color_preference.as_deref() {
+            Some("white") => Some(Color::White),
+            Some("black") => Some(Color::Black),
+            _ => None,
+        },
+        time_control: challenge.time_control.and_then(|tc| tc.parse().ok()),
+        status: challenge.status.parse().unwrap_or(ChallengeStatus::Pending),
+        game_id: challenge.game_id,
+        created_at: challenge.created_at,
+    }
+}
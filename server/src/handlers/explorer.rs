@@ -0,0 +1,29 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::chess::GameState;
+use crate::error::Result;
+use crate::AppState;
+use shared::protocol::ExplorerResponse;
+
+#[derive(Debug, Deserialize)]
+pub struct ExplorerQuery {
+    pub fen: Option<String>,
+}
+
+/// Get the most common continuations from a position (defaults to the start position)
+pub async fn explorer(
+    State(state): State<AppState>,
+    Query(query): Query<ExplorerQuery>,
+) -> Result<Json<ExplorerResponse>> {
+    let fen = query
+        .fen
+        .unwrap_or_else(|| GameState::new().fen().to_string());
+
+    let response = state.explorer_service.explore(&state.db, &fen).await?;
+
+    Ok(Json(response))
+}
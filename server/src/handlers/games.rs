@@ -1,61 +1,184 @@
 use axum::{
-    extract::{Extension, Path, State},
-    http::StatusCode,
+    body::Body,
+    extract::{Extension, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use chrono::Utc;
+use serde::Deserialize;
+use std::convert::Infallible;
 use uuid::Uuid;
 
-use crate::db::{games, moves as db_moves, users};
+use crate::chess::{captured_pieces, material_eval, GameState};
+use crate::db::{games, idempotency, moves as db_moves, users};
 use crate::error::{AppError, Result};
+use crate::extractors::{GameId, JsonBody, Ply};
 use crate::middleware::AuthUser;
+use crate::models::seen_recently;
+use crate::services::{
+    current_position, ensure_not_self, ensure_verified, move_response, your_color, GameOptions,
+};
 use crate::AppState;
+use shared::board::render_ascii;
 use shared::protocol::{
-    CreateGameRequest, GameListResponse, GameResponse, MoveListResponse, MoveResponse,
-    PgnResponse, SubmitMoveRequest,
+    AnalysisResponse, BoardResponse, ClaimDrawRequest, ConditionalMoveInfo,
+    ConditionalMoveListResponse, CreateGameRequest, GameHistoryResponse, GameListResponse,
+    GameResponse, ImportPgnRequest, LegalMovesResponse, MoveEvaluation, MoveListResponse,
+    MoveResponse, PgnResponse, RespondTakebackRequest, ReviewResponse, SetConditionalMoveRequest,
+    SubmitMoveRequest,
 };
-use shared::types::{Color, GameInfo, Move, UserProfile};
+use shared::types::{Color, GameInfo, GameStatus, Move, PastGameInfo, UserProfile};
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+/// Registered media type for PGN files
+const PGN_CONTENT_TYPE: &str = "application/x-chess-pgn";
+
+/// Header a client sets to make retrying a move safe
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// Longest idempotency key we store
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListGamesQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub status: Option<String>,
+}
+
+impl ListGamesQuery {
+    /// Resolve to `(status, limit, offset)`, applying defaults and the page size cap
+    fn page(&self) -> Result<(Option<GameStatus>, i64, i64)> {
+        let status = self
+            .status
+            .as_deref()
+            .map(|s| s.parse::<GameStatus>())
+            .transpose()
+            .map_err(AppError::BadRequest)?;
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let offset = self.offset.unwrap_or(0).max(0);
 
-/// List games for the authenticated user
+        Ok((status, limit, offset))
+    }
+}
+
+/// List games for the authenticated user, a page at a time
 pub async fn list_games(
     Extension(auth): Extension<AuthUser>,
     State(state): State<AppState>,
+    Query(query): Query<ListGamesQuery>,
 ) -> Result<Json<GameListResponse>> {
     let user_id = auth.user_id;
+    let (status, limit, offset) = query.page()?;
+    let status = status.map(|s| s.to_string());
 
-    let user_games = games::list_by_user(&state.db, user_id).await?;
+    let user_games =
+        games::list_by_user(&state.db, user_id, status.as_deref(), limit, offset).await?;
+    let total_count = games::count_by_user(&state.db, user_id, status.as_deref()).await?;
 
-    let games_info: Vec<GameInfo> = user_games
-        .into_iter()
-        .map(|g| GameInfo {
-            id: g.id,
-            white_player_username: g.white_player_username,
-            black_player_username: g.black_player_username,
-            status: g.status.parse().unwrap_or(shared::types::GameStatus::Active),
-            current_turn: if g.current_turn == "white" {
-                Color::White
-            } else {
-                Color::Black
-            },
-            move_deadline: g.move_deadline,
-            created_at: g.created_at,
-        })
-        .collect();
+    Ok(Json(GameListResponse {
+        games: user_games.into_iter().map(game_info).collect(),
+        total_count,
+    }))
+}
+
+/// Finished games for the authenticated user, each labelled won, lost or drawn
+pub async fn list_game_history(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Query(query): Query<ListGamesQuery>,
+) -> Result<Json<GameHistoryResponse>> {
+    let user_id = auth.user_id;
+    let (_, limit, offset) = query.page()?;
+
+    let finished = games::list_completed_by_user(&state.db, user_id, limit, offset).await?;
+    let total_count = games::count_completed_by_user(&state.db, user_id).await?;
 
-    Ok(Json(GameListResponse { games: games_info }))
+    Ok(Json(GameHistoryResponse {
+        games: finished
+            .into_iter()
+            .filter_map(|g| past_game_info(g, user_id))
+            .collect(),
+        total_count,
+    }))
+}
+
+/// A finished game with its result from `user_id`'s side
+fn past_game_info(g: crate::models::GameWithPlayers, user_id: Uuid) -> Option<PastGameInfo> {
+    let color = if g.white_player_id == user_id {
+        Color::White
+    } else {
+        Color::Black
+    };
+    let game = game_info(g);
+    let outcome_for_user = game.status.outcome_for(color)?;
+
+    Some(PastGameInfo {
+        game,
+        outcome_for_user,
+    })
+}
+
+/// Active games waiting on the authenticated user's move, most urgent first
+pub async fn list_awaiting_games(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+) -> Result<Json<GameListResponse>> {
+    let awaiting = games::list_where_turn(&state.db, auth.user_id).await?;
+
+    Ok(Json(GameListResponse {
+        total_count: awaiting.len() as i64,
+        games: awaiting.into_iter().map(game_info).collect(),
+    }))
+}
+
+pub(crate) fn game_info(g: crate::models::GameWithPlayers) -> GameInfo {
+    GameInfo {
+        id: g.id,
+        white_player_username: g.white_player_username,
+        black_player_username: g.black_player_username,
+        status: g.status.parse().unwrap_or(shared::types::GameStatus::Active),
+        current_turn: if g.current_turn == "white" {
+            Color::White
+        } else {
+            Color::Black
+        },
+        move_deadline: g.move_deadline,
+        created_at: g.created_at,
+    }
 }
 
 /// Create a new game
 pub async fn create_game(
     Extension(auth): Extension<AuthUser>,
     State(state): State<AppState>,
-    Json(request): Json<CreateGameRequest>,
+    JsonBody(request): JsonBody<CreateGameRequest>,
 ) -> Result<(StatusCode, Json<GameResponse>)> {
     let user_id = auth.user_id;
 
+    // Only verified accounts may challenge others
+    let user = users::find_by_id(&state.db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    ensure_verified(&user)?;
+
+    // Enforce the operator's game-creation policy
+    state
+        .game_service
+        .ensure_can_create_games(&state.db, user_id)
+        .await?;
+
     // Find opponent by username
     let opponent = users::find_by_username(&state.db, &request.opponent_username)
         .await?
         .ok_or_else(|| AppError::NotFound("Opponent not found".to_string()))?;
+    ensure_not_self(opponent.id, user_id)?;
 
     // Determine colors
     let (white_id, black_id) = match request.player_color.as_deref() {
@@ -75,39 +198,43 @@ pub async fn create_game(
     // Create game
     let game = state
         .game_service
-        .create_game(&state.db, white_id, black_id)
+        .create_game(
+            &state.db,
+            white_id,
+            black_id,
+            GameOptions {
+                takebacks_allowed: request.takebacks_allowed,
+                time_control: request.time_control,
+                is_public: request.is_public,
+                starting_fen: request.starting_fen,
+            },
+        )
         .await?;
 
     // Get player info
+    let now = Utc::now();
     let white_player = users::find_by_id(&state.db, white_id).await?.unwrap();
     let black_player = users::find_by_id(&state.db, black_id).await?.unwrap();
+    let white_player = user_profile(white_player, now);
+    let black_player = user_profile(black_player, now);
 
-    let response = GameResponse {
-        game: shared::types::Game {
-            id: game.id,
-            white_player_id: game.white_player_id,
-            black_player_id: game.black_player_id,
-            current_position: game.current_position,
-            status: game.status.parse().unwrap_or(shared::types::GameStatus::Active),
-            current_turn: if game.current_turn == "white" {
-                Color::White
-            } else {
-                Color::Black
-            },
-            move_deadline: game.move_deadline,
-            created_at: game.created_at,
-            completed_at: game.completed_at,
-        },
-        white_player: UserProfile {
-            id: white_player.id,
-            username: white_player.username,
-        },
-        black_player: UserProfile {
-            id: black_player.id,
-            username: black_player.username,
-        },
-        moves: vec![],
-    };
+    let response = assemble_game_response(game, white_player, black_player, vec![], Some(user_id));
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Import a finished game from PGN for analysis
+pub async fn import_game(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    JsonBody(request): JsonBody<ImportPgnRequest>,
+) -> Result<(StatusCode, Json<GameResponse>)> {
+    let game = state
+        .game_service
+        .import_pgn(&state.db, auth.user_id, &request.pgn)
+        .await?;
+
+    let response = game_response(&state, game, Some(auth.user_id)).await?;
 
     Ok((StatusCode::CREATED, Json(response)))
 }
@@ -116,137 +243,311 @@ pub async fn create_game(
 pub async fn get_game(
     Extension(auth): Extension<AuthUser>,
     State(state): State<AppState>,
-    Path(game_id): Path<Uuid>,
+    GameId(game_id): GameId,
 ) -> Result<Json<GameResponse>> {
     let user_id = auth.user_id;
 
-    // Get game
-    let game = games::find_by_id(&state.db, game_id)
+    // Game, players and move count in one query
+    let details = games::get_game_with_players_and_move_count(&state.db, game_id)
         .await?
         .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
 
-    // Verify user is a player
-    if game.white_player_id != user_id && game.black_player_id != user_id {
-        return Err(AppError::BadRequest(
-            "You are not a player in this game".to_string(),
-        ));
-    }
+    // Verify user is a player; spectators use the public endpoint
+    ensure_player(&details.game, user_id)?;
+    current_position(&details.game)?;
 
-    // Get players
-    let white_player = users::find_by_id(&state.db, game.white_player_id)
-        .await?
-        .unwrap();
-    let black_player = users::find_by_id(&state.db, game.black_player_id)
+    let moves = if details.move_count > 0 {
+        db_moves::list_by_game(&state.db, game_id).await?
+    } else {
+        Vec::new()
+    };
+    let moves = moves.into_iter().map(move_info).collect();
+
+    let response = details_response(details, moves, Some(user_id), Utc::now());
+
+    Ok(Json(response))
+}
+
+/// Get a public or finished game without logging in
+pub async fn get_public_game(
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<Json<GameResponse>> {
+    let game = games::find_by_id(&state.db, game_id)
         .await?
-        .unwrap();
+        .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
 
-    // Get moves
-    let game_moves = db_moves::list_by_game(&state.db, game_id).await?;
+    ensure_spectatable(&game)?;
 
-    let moves: Vec<Move> = game_moves
-        .into_iter()
-        .map(|m| Move {
-            id: m.id,
-            game_id: m.game_id,
-            move_number: m.move_number,
-            player_color: if m.player_color == "white" {
-                Color::White
-            } else {
-                Color::Black
-            },
-            move_uci: m.move_uci,
-            move_san: m.move_san,
-            position_before: m.position_before,
-            position_after: m.position_after,
-            timestamp: m.timestamp,
-        })
-        .collect();
+    let mut response = game_response(&state, game, None).await?;
+    redact_for_spectator(&mut response);
 
-    let response = GameResponse {
-        game: shared::types::Game {
-            id: game.id,
-            white_player_id: game.white_player_id,
-            black_player_id: game.black_player_id,
-            current_position: game.current_position,
-            status: game.status.parse().unwrap_or(shared::types::GameStatus::Active),
-            current_turn: if game.current_turn == "white" {
-                Color::White
-            } else {
-                Color::Black
-            },
-            move_deadline: game.move_deadline,
-            created_at: game.created_at,
-            completed_at: game.completed_at,
-        },
-        white_player: UserProfile {
-            id: white_player.id,
-            username: white_player.username,
-        },
-        black_player: UserProfile {
-            id: black_player.id,
-            username: black_player.username,
-        },
-        moves,
+    Ok(Json(response))
+}
+
+/// Resign a game, awarding it to the opponent
+pub async fn resign_game(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<Json<GameResponse>> {
+    let game = state
+        .game_service
+        .resign(&state.db, game_id, auth.user_id)
+        .await?;
+
+    let response = game_response(&state, game, Some(auth.user_id)).await?;
+
+    Ok(Json(response))
+}
+
+/// Claim a draw by threefold repetition or the fifty-move rule, optionally
+/// about the position after a move the player is about to make
+pub async fn claim_draw(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+    JsonBody(request): JsonBody<ClaimDrawRequest>,
+) -> Result<Json<GameResponse>> {
+    let move_uci = match request.move_san {
+        Some(san) => Some(
+            state
+                .game_service
+                .resolve_san(&state.db, game_id, &san)
+                .await?,
+        ),
+        None => request.move_uci,
     };
 
+    let game = state
+        .game_service
+        .claim_draw(&state.db, game_id, auth.user_id, move_uci)
+        .await?;
+
+    let response = game_response(&state, game, Some(auth.user_id)).await?;
+
     Ok(Json(response))
 }
 
+/// Abort a game nobody has moved in yet; nobody wins
+pub async fn abort_game(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<Json<GameResponse>> {
+    let game = state
+        .game_service
+        .abort_game(&state.db, game_id, auth.user_id)
+        .await?;
+
+    let response = game_response(&state, game, Some(auth.user_id)).await?;
+
+    Ok(Json(response))
+}
+
+/// Ask the opponent to take back your last move
+pub async fn request_takeback(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<StatusCode> {
+    state
+        .game_service
+        .request_takeback(&state.db, game_id, auth.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Accept or decline the opponent's takeback request
+pub async fn respond_to_takeback(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+    JsonBody(request): JsonBody<RespondTakebackRequest>,
+) -> Result<Json<GameResponse>> {
+    let game = state
+        .game_service
+        .respond_to_takeback(&state.db, game_id, auth.user_id, request.accept)
+        .await?;
+
+    let response = game_response(&state, game, Some(auth.user_id)).await?;
+
+    Ok(Json(response))
+}
+
+/// List your conditional moves in a game
+pub async fn list_conditional_moves(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<Json<ConditionalMoveListResponse>> {
+    let planned = state
+        .game_service
+        .conditional_moves(&state.db, game_id, auth.user_id)
+        .await?;
+
+    Ok(Json(conditional_move_list(planned)))
+}
+
+/// Plan a move to be played automatically if the game reaches a position
+pub async fn set_conditional_move(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+    JsonBody(request): JsonBody<SetConditionalMoveRequest>,
+) -> Result<Json<ConditionalMoveListResponse>> {
+    let planned = state
+        .game_service
+        .set_conditional_move(
+            &state.db,
+            game_id,
+            auth.user_id,
+            &request.position_fen,
+            &request.move_uci,
+        )
+        .await?;
+
+    Ok(Json(conditional_move_list(planned)))
+}
+
+/// Drop all your conditional moves in a game
+pub async fn clear_conditional_moves(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<StatusCode> {
+    state
+        .game_service
+        .clear_conditional_moves(&state.db, game_id, auth.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Submit a move
+///
+/// With an `Idempotency-Key` header, repeating the request within a day
+/// returns the first response instead of trying the move again. The saved
+/// response shows the game right after the move, before any conditional
+/// replies.
 pub async fn submit_move(
     Extension(auth): Extension<AuthUser>,
     State(state): State<AppState>,
-    Path(game_id): Path<Uuid>,
-    Json(request): Json<SubmitMoveRequest>,
+    GameId(game_id): GameId,
+    headers: HeaderMap,
+    JsonBody(request): JsonBody<SubmitMoveRequest>,
 ) -> Result<Json<MoveResponse>> {
     let user_id = auth.user_id;
 
-    let (move_record, game) = state
-        .game_service
-        .submit_move(&state.db, game_id, user_id, request.move_uci)
-        .await?;
+    // Dry runs change nothing, so they never need replaying
+    let idempotency_key = idempotency_key(&headers)?.filter(|_| !request.dry_run);
+    let Some(key) = idempotency_key else {
+        return play_move(&state, game_id, user_id, request, None)
+            .await
+            .map(Json);
+    };
 
-    let response = MoveResponse {
-        r#move: Move {
-            id: move_record.id,
-            game_id: move_record.game_id,
-            move_number: move_record.move_number,
-            player_color: if move_record.player_color == "white" {
-                Color::White
-            } else {
-                Color::Black
-            },
-            move_uci: move_record.move_uci,
-            move_san: move_record.move_san,
-            position_before: move_record.position_before,
-            position_after: move_record.position_after,
-            timestamp: move_record.timestamp,
-        },
-        game: shared::types::Game {
-            id: game.id,
-            white_player_id: game.white_player_id,
-            black_player_id: game.black_player_id,
-            current_position: game.current_position,
-            status: game.status.parse().unwrap_or(shared::types::GameStatus::Active),
-            current_turn: if game.current_turn == "white" {
-                Color::White
-            } else {
-                Color::Black
-            },
-            move_deadline: game.move_deadline,
-            created_at: game.created_at,
-            completed_at: game.completed_at,
-        },
+    if let Some(saved) = idempotency::find_move_response(&state.db, &key, game_id, user_id).await? {
+        return saved_response(saved).map(Json);
+    }
+
+    // A retry in flight alongside this request may play the move first, so
+    // this attempt fails; it gets the response that retry saved instead
+    match play_move(&state, game_id, user_id, request, Some(&key)).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            let saved = idempotency::find_move_response(&state.db, &key, game_id, user_id).await?;
+            replay_or(e, saved).map(Json)
+        }
+    }
+}
+
+/// Play (or preview) a move, saving its response under `idempotency_key`
+async fn play_move(
+    state: &AppState,
+    game_id: Uuid,
+    user_id: Uuid,
+    request: SubmitMoveRequest,
+    idempotency_key: Option<&str>,
+) -> Result<MoveResponse> {
+    // Accept SAN as an alternative to UCI
+    let move_uci = match request.move_san {
+        Some(san) => {
+            state
+                .game_service
+                .resolve_san(&state.db, game_id, &san)
+                .await?
+        }
+        None => request.move_uci,
     };
 
-    Ok(Json(response))
+    let (move_record, game) = if request.dry_run {
+        state
+            .game_service
+            .preview_move(&state.db, game_id, user_id, move_uci, request.comment)
+            .await?
+    } else {
+        state
+            .game_service
+            .submit_move(
+                &state.db,
+                game_id,
+                user_id,
+                move_uci,
+                request.comment,
+                idempotency_key,
+            )
+            .await?
+    };
+
+    move_response(move_record, game, user_id)
+}
+
+/// The response saved for a failed move's key, if another request played it
+fn replay_or(error: AppError, saved: Option<serde_json::Value>) -> Result<MoveResponse> {
+    match saved {
+        Some(saved) => saved_response(saved),
+        None => Err(error),
+    }
+}
+
+fn saved_response(saved: serde_json::Value) -> Result<MoveResponse> {
+    Ok(serde_json::from_value(saved).map_err(anyhow::Error::from)?)
+}
+
+/// Read the optional `Idempotency-Key` header
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY) else {
+        return Ok(None);
+    };
+
+    let key = value
+        .to_str()
+        .map(str::trim)
+        .map_err(|_| AppError::BadRequest("Invalid Idempotency-Key header".to_string()))?;
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Idempotency-Key must be 1 to {} characters",
+            MAX_IDEMPOTENCY_KEY_LEN
+        )));
+    }
+
+    Ok(Some(key.to_string()))
 }
 
 /// Get move history for a game
+#[derive(Debug, Deserialize)]
+pub struct MovesQuery {
+    /// Moves the client already has; only later ones are returned
+    pub since: Option<usize>,
+}
+
 pub async fn get_moves(
     Extension(auth): Extension<AuthUser>,
     State(state): State<AppState>,
-    Path(game_id): Path<Uuid>,
+    GameId(game_id): GameId,
+    Query(query): Query<MovesQuery>,
 ) -> Result<Json<MoveListResponse>> {
     let user_id = auth.user_id;
 
@@ -255,58 +556,897 @@ pub async fn get_moves(
         .await?
         .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
 
-    if game.white_player_id != user_id && game.black_player_id != user_id {
-        return Err(AppError::BadRequest(
-            "You are not a player in this game".to_string(),
-        ));
-    }
+    ensure_player(&game, user_id)?;
 
     // Get moves
     let game_moves = db_moves::list_by_game(&state.db, game_id).await?;
 
-    let moves: Vec<Move> = game_moves
-        .into_iter()
-        .map(|m| Move {
-            id: m.id,
-            game_id: m.game_id,
-            move_number: m.move_number,
-            player_color: if m.player_color == "white" {
-                Color::White
-            } else {
-                Color::Black
-            },
-            move_uci: m.move_uci,
-            move_san: m.move_san,
-            position_before: m.position_before,
-            position_after: m.position_after,
-            timestamp: m.timestamp,
+    let moves: Vec<Move> = game_moves.into_iter().map(move_info).collect();
+
+    let total_count = moves.len() as i64;
+    Ok(Json(MoveListResponse {
+        moves: moves_since(moves, query.since),
+        total_count,
+    }))
+}
+
+/// Material evaluation after each move, for drawing an evaluation graph
+pub async fn get_analysis(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<Json<AnalysisResponse>> {
+    let game = games::find_by_id(&state.db, game_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+    ensure_player(&game, auth.user_id)?;
+
+    let game_moves = db_moves::list_by_game(&state.db, game_id).await?;
+
+    Ok(Json(AnalysisResponse {
+        moves: move_evaluations(&game_moves)?,
+    }))
+}
+
+/// Material balance in each move's resulting position
+fn move_evaluations(game_moves: &[crate::models::MoveRecord]) -> Result<Vec<MoveEvaluation>> {
+    game_moves
+        .iter()
+        .map(|m| {
+            let board = GameState::from_fen(&m.position_after)?.board()?;
+            Ok(MoveEvaluation {
+                move_number: m.move_number,
+                color: if m.player_color == "white" {
+                    Color::White
+                } else {
+                    Color::Black
+                },
+                eval_centipawns: material_eval(&board),
+            })
         })
-        .collect();
+        .collect()
+}
+
+/// Get one move, numbered by ply from 1, with the positions either side of it
+pub async fn get_move(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+    Ply(ply): Ply,
+) -> Result<Json<Move>> {
+    let game = games::find_by_id(&state.db, game_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+    ensure_player(&game, auth.user_id)?;
+
+    let game_moves = db_moves::list_by_game(&state.db, game_id).await?;
+
+    Ok(Json(move_info(nth_move(game_moves, ply)?)))
+}
+
+/// The move at ply `ply` (1 is the first move)
+fn nth_move(
+    game_moves: Vec<crate::models::MoveRecord>,
+    ply: usize,
+) -> Result<crate::models::MoveRecord> {
+    ply.checked_sub(1)
+        .and_then(|index| game_moves.into_iter().nth(index))
+        .ok_or_else(|| AppError::NotFound("Move out of range".to_string()))
+}
+
+fn conditional_move_list(
+    planned: Vec<crate::models::ConditionalMove>,
+) -> ConditionalMoveListResponse {
+    ConditionalMoveListResponse {
+        moves: planned
+            .into_iter()
+            .map(|c| ConditionalMoveInfo {
+                position_fen: c.position_fen,
+                move_uci: c.move_uci,
+            })
+            .collect(),
+    }
+}
+
+fn move_info(m: crate::models::MoveRecord) -> Move {
+    Move {
+        id: m.id,
+        game_id: m.game_id,
+        move_number: m.move_number,
+        player_color: if m.player_color == "white" {
+            Color::White
+        } else {
+            Color::Black
+        },
+        move_uci: m.move_uci,
+        move_san: m.move_san,
+        position_before: m.position_before,
+        position_after: m.position_after,
+        timestamp: m.timestamp,
+        time_taken_seconds: m.time_taken_seconds,
+        comment: m.comment,
+        is_terminal: m.is_terminal,
+    }
+}
+
+/// The moves after the first `since`, in order
+fn moves_since(mut moves: Vec<Move>, since: Option<usize>) -> Vec<Move> {
+    let since = since.unwrap_or(0).min(moves.len());
+    moves.split_off(since)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LegalMovesQuery {
+    pub from: Option<String>,
+}
+
+/// Get the legal moves in a game's current position, optionally from one square
+pub async fn get_legal_moves(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+    Query(query): Query<LegalMovesQuery>,
+) -> Result<Json<LegalMovesResponse>> {
+    let user_id = auth.user_id;
+
+    // Verify game exists and user is a player
+    let game = games::find_by_id(&state.db, game_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+    ensure_player(&game, user_id)?;
+
+    let game_state = current_position(&game)?;
 
-    Ok(Json(MoveListResponse { moves }))
+    let mut moves = game_state.legal_moves_san()?;
+    if let Some(square) = query.from.as_deref() {
+        let from_square = game_state
+            .legal_moves_from(square)
+            .map_err(|_| AppError::BadRequest("Invalid square".to_string()))?;
+        moves.retain(|(uci, _)| from_square.contains(uci));
+    }
+    let (moves, san) = moves.into_iter().unzip();
+
+    Ok(Json(LegalMovesResponse {
+        moves,
+        san,
+        from_square: query.from,
+    }))
 }
 
 /// Export game as PGN
 pub async fn export_pgn(
     Extension(auth): Extension<AuthUser>,
     State(state): State<AppState>,
-    Path(game_id): Path<Uuid>,
+    GameId(game_id): GameId,
 ) -> Result<Json<PgnResponse>> {
-    let user_id = auth.user_id;
+    let pgn = player_pgn(&state, auth.user_id, game_id).await?;
+
+    Ok(Json(PgnResponse { pgn }))
+}
+
+/// Download a game as a raw `.pgn` file, for piping into chess tools
+pub async fn export_pgn_file(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<Response> {
+    let pgn = player_pgn(&state, auth.user_id, game_id).await?;
+    let filename = format!("game-{}.pgn", game_id);
+
+    Ok((pgn_file_headers(&filename), pgn).into_response())
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportGamesQuery {
+    /// "white" or "black" to keep only the games played with that color
+    pub color: Option<String>,
+}
+
+impl ExportGamesQuery {
+    fn color(&self) -> Result<Option<Color>> {
+        match self.color.as_deref() {
+            None => Ok(None),
+            Some("white") => Ok(Some(Color::White)),
+            Some("black") => Ok(Some(Color::Black)),
+            Some(other) => Err(AppError::BadRequest(format!(
+                "Invalid color '{}', expected white or black",
+                other
+            ))),
+        }
+    }
+}
+
+/// Download all of the user's finished games as one multi-game `.pgn` file,
+/// sent a game at a time
+pub async fn export_games_pgn(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Query(query): Query<ExportGamesQuery>,
+) -> Result<Response> {
+    let color = query.color()?;
+    let pgns = state
+        .game_service
+        .export_games_pgn(&state.db, auth.user_id, color)
+        .await?;
 
+    let body = Body::from_stream(futures::stream::iter(
+        pgns.into_iter().map(Ok::<_, Infallible>),
+    ));
+
+    Ok((pgn_file_headers("games.pgn"), body).into_response())
+}
+
+/// PGN of a game the user plays in
+async fn player_pgn(state: &AppState, user_id: Uuid, game_id: Uuid) -> Result<String> {
     // Verify game exists and user is a player
     let game = games::find_by_id(&state.db, game_id)
         .await?
         .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
 
+    ensure_player(&game, user_id)?;
+
+    // Generate PGN
+    state.game_service.generate_pgn(&state.db, game_id).await
+}
+
+fn pgn_file_headers(filename: &str) -> [(HeaderName, String); 2] {
+    [
+        (header::CONTENT_TYPE, PGN_CONTENT_TYPE.to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        ),
+    ]
+}
+
+/// Current position as a Unicode board, for quick inspection from a terminal
+pub async fn get_board(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+) -> Result<Json<BoardResponse>> {
+    let user_id = auth.user_id;
+
+    let game = games::find_by_id(&state.db, game_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+    ensure_player(&game, user_id)?;
+
+    let ascii = render_ascii(&game.current_position)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid stored position: {}", e)))?;
+
+    Ok(Json(BoardResponse { ascii }))
+}
+
+/// Explore the position at a ply of a game, with legal moves in SAN
+pub async fn review_position(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    GameId(game_id): GameId,
+    Ply(ply): Ply,
+) -> Result<Json<ReviewResponse>> {
+    let review = state
+        .game_service
+        .review_position(&state.db, game_id, auth.user_id, ply)
+        .await?;
+
+    Ok(Json(review))
+}
+
+/// Refuse anyone who isn't playing in the game
+fn ensure_player(game: &crate::models::Game, user_id: Uuid) -> Result<()> {
     if game.white_player_id != user_id && game.black_player_id != user_id {
-        return Err(AppError::BadRequest(
+        return Err(AppError::Forbidden(
             "You are not a player in this game".to_string(),
         ));
     }
 
-    // Generate PGN
-    let pgn = state.game_service.generate_pgn(&state.db, game_id).await?;
+    Ok(())
+}
 
-    Ok(Json(PgnResponse { pgn }))
+/// Spectators may watch public games, and any game once it's over
+fn ensure_spectatable(game: &crate::models::Game) -> Result<()> {
+    if !game.is_public && game.status == "active" {
+        return Err(AppError::Forbidden("This game is private".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Hide what only the players should see from a spectator's view
+fn redact_for_spectator(response: &mut GameResponse) {
+    response.game.move_deadline = None;
+}
+
+/// Build the full game response: game, both players and the move list
+///
+/// `viewer` is the user asking, to fill in `your_color`.
+pub(crate) async fn game_response(
+    state: &AppState,
+    game: crate::models::Game,
+    viewer: Option<Uuid>,
+) -> Result<GameResponse> {
+    // Get players
+    let white_player = users::find_by_id(&state.db, game.white_player_id)
+        .await?
+        .unwrap();
+    let black_player = users::find_by_id(&state.db, game.black_player_id)
+        .await?
+        .unwrap();
+
+    // Get moves
+    let game_moves = db_moves::list_by_game(&state.db, game.id).await?;
+
+    let moves: Vec<Move> = game_moves.into_iter().map(move_info).collect();
+
+    let now = Utc::now();
+    let white_player = user_profile(white_player, now);
+    let black_player = user_profile(black_player, now);
+
+    let response = assemble_game_response(game, white_player, black_player, moves, viewer);
+
+    Ok(response)
+}
+
+fn user_profile(user: crate::models::User, now: chrono::DateTime<Utc>) -> UserProfile {
+    UserProfile {
+        id: user.id,
+        online: user.is_online(now),
+        username: user.username,
+    }
+}
+
+/// Build a game's detail view from the combined game and players query
+fn details_response(
+    details: crate::models::GameDetails,
+    moves: Vec<Move>,
+    viewer: Option<Uuid>,
+    now: chrono::DateTime<Utc>,
+) -> GameResponse {
+    let white_player = UserProfile {
+        id: details.game.white_player_id,
+        username: details.white_player_username,
+        online: seen_recently(details.white_player_last_seen, now),
+    };
+    let black_player = UserProfile {
+        id: details.game.black_player_id,
+        username: details.black_player_username,
+        online: seen_recently(details.black_player_last_seen, now),
+    };
+
+    assemble_game_response(details.game, white_player, black_player, moves, viewer)
+}
+
+fn assemble_game_response(
+    game: crate::models::Game,
+    white_player: UserProfile,
+    black_player: UserProfile,
+    moves: Vec<Move>,
+    viewer: Option<Uuid>,
+) -> GameResponse {
+    let your_color = viewer.and_then(|user_id| your_color(&game, user_id));
+    let start = match moves.first() {
+        Some(first) => &first.position_before,
+        None => &game.current_position,
+    };
+    let (white_captured, black_captured) =
+        captured_pieces(start, &game.current_position).unwrap_or_default();
+
+    GameResponse {
+        game: shared::types::Game {
+            id: game.id,
+            white_player_id: game.white_player_id,
+            black_player_id: game.black_player_id,
+            current_position: game.current_position,
+            status: game.status.parse().unwrap_or(shared::types::GameStatus::Active),
+            current_turn: if game.current_turn == "white" {
+                Color::White
+            } else {
+                Color::Black
+            },
+            move_deadline: game.move_deadline,
+            created_at: game.created_at,
+            completed_at: game.completed_at,
+            takebacks_allowed: game.takebacks_allowed,
+            move_deadline_hours: game.move_deadline_hours,
+            is_public: game.is_public,
+            draw_reason: game.draw_reason,
+        },
+        white_player,
+        black_player,
+        moves,
+        your_color,
+        white_captured: piece_names(white_captured),
+        black_captured: piece_names(black_captured),
+    }
+}
+
+fn piece_names(pieces: Vec<chess::Piece>) -> Vec<String> {
+    pieces
+        .into_iter()
+        .map(|piece| {
+            match piece {
+                chess::Piece::Queen => "queen",
+                chess::Piece::Rook => "rook",
+                chess::Piece::Bishop => "bishop",
+                chess::Piece::Knight => "knight",
+                chess::Piece::Pawn => "pawn",
+                chess::Piece::King => "king",
+            }
+            .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::types::Outcome;
+
+    fn test_game(status: &str, is_public: bool) -> crate::models::Game {
+        crate::models::Game {
+            id: Uuid::new_v4(),
+            white_player_id: Uuid::new_v4(),
+            black_player_id: Uuid::new_v4(),
+            current_position: String::new(),
+            game_state: serde_json::Value::Null,
+            status: status.to_string(),
+            current_turn: "white".to_string(),
+            move_deadline: Some(Utc::now()),
+            created_at: Utc::now(),
+            completed_at: None,
+            takebacks_allowed: false,
+            move_deadline_hours: 72,
+            is_public,
+            draw_reason: None,
+            win_reason: None,
+            version: 0,
+        }
+    }
+
+    #[test]
+    fn test_non_player_is_forbidden_but_may_spectate_public_game() {
+        let game = test_game("active", true);
+        let outsider = Uuid::new_v4();
+
+        let err = ensure_player(&game, outsider).unwrap_err();
+        assert_eq!(err.status(), axum::http::StatusCode::FORBIDDEN);
+        assert!(ensure_player(&game, game.white_player_id).is_ok());
+        assert!(ensure_spectatable(&game).is_ok());
+    }
+
+    #[test]
+    fn test_private_games_are_spectatable_once_finished() {
+        let err = ensure_spectatable(&test_game("active", false)).unwrap_err();
+        assert_eq!(err.status(), axum::http::StatusCode::FORBIDDEN);
+        assert!(ensure_spectatable(&test_game("white_won", false)).is_ok());
+        assert!(ensure_spectatable(&test_game("abandoned", false)).is_ok());
+    }
+
+    #[test]
+    fn test_nth_move_has_the_recorded_positions() {
+        let start = GameState::new();
+        let (after_e4, _) = start.make_move("e2e4").unwrap();
+        let (after_e5, _) = after_e4.make_move("e7e5").unwrap();
+        let record = |uci: &str, color: &str, before: &GameState, after: &GameState| {
+            crate::models::MoveRecord {
+                id: Uuid::new_v4(),
+                game_id: Uuid::nil(),
+                move_number: 1,
+                player_color: color.to_string(),
+                move_uci: uci.to_string(),
+                move_san: String::new(),
+                position_before: before.fen().to_string(),
+                position_after: after.fen().to_string(),
+                timestamp: Utc::now(),
+                time_taken_seconds: None,
+                comment: None,
+                is_terminal: false,
+            }
+        };
+        let game_moves = vec![
+            record("e2e4", "white", &start, &after_e4),
+            record("e7e5", "black", &after_e4, &after_e5),
+        ];
+
+        let second = move_info(nth_move(game_moves.clone(), 2).unwrap());
+        assert_eq!(second.move_uci, "e7e5");
+        assert_eq!(second.player_color, Color::Black);
+        assert_eq!(second.position_before, after_e4.fen());
+        assert_eq!(second.position_after, after_e5.fen());
+
+        for ply in [0, 3] {
+            let err = nth_move(game_moves.clone(), ply).unwrap_err();
+            assert_eq!(err.status(), axum::http::StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_in_flight_gets_the_saved_response() {
+        let start = GameState::new();
+        let (after_e4, san) = start.make_move("e2e4").unwrap();
+        let mut game = test_game("active", false);
+        game.current_position = after_e4.fen().to_string();
+        game.current_turn = "black".to_string();
+        let player = game.white_player_id;
+        let played = crate::models::MoveRecord {
+            id: Uuid::new_v4(),
+            game_id: game.id,
+            move_number: 1,
+            player_color: "white".to_string(),
+            move_uci: "e2e4".to_string(),
+            move_san: san,
+            position_before: start.fen().to_string(),
+            position_after: after_e4.fen().to_string(),
+            timestamp: Utc::now(),
+            time_taken_seconds: None,
+            comment: None,
+            is_terminal: false,
+        };
+        let saved = serde_json::to_value(move_response(played, game, player).unwrap()).unwrap();
+
+        // The other request played the move first, so this one's turn check failed
+        let lost_race = || AppError::NotYourTurn {
+            current_turn: Color::Black,
+            deadline: None,
+        };
+        let replayed = replay_or(lost_race(), Some(saved.clone())).unwrap();
+        assert_eq!(serde_json::to_value(&replayed).unwrap(), saved);
+        assert_eq!(replayed.r#move.move_uci, "e2e4");
+
+        // Without a saved response the failure stands
+        let err = replay_or(lost_race(), None).unwrap_err();
+        assert_eq!(err.code(), "not_your_turn");
+    }
+
+    #[test]
+    fn test_queen_capture_swings_the_evaluation() {
+        // 1. e4 d5 2. exd5 Qxd5 3. Nc3 Qe5+?? 4. Qe2 Qxe2+ 5. Bxe2
+        let ucis = [
+            "e2e4", "d7d5", "e4d5", "d8d5", "b1c3", "d5e5", "d1e2", "e5e2", "f1e2",
+        ];
+        let mut state = GameState::new();
+        let mut game_moves = Vec::new();
+        for (i, uci) in ucis.iter().enumerate() {
+            let (next, _) = state.make_move(uci).unwrap();
+            game_moves.push(crate::models::MoveRecord {
+                id: Uuid::new_v4(),
+                game_id: Uuid::nil(),
+                move_number: i as i32 / 2 + 1,
+                player_color: if i % 2 == 0 { "white" } else { "black" }.to_string(),
+                move_uci: uci.to_string(),
+                move_san: String::new(),
+                position_before: state.fen().to_string(),
+                position_after: next.fen().to_string(),
+                timestamp: Utc::now(),
+                time_taken_seconds: None,
+                comment: None,
+                is_terminal: false,
+            });
+            state = next;
+        }
+
+        let evals = move_evaluations(&game_moves).unwrap();
+        let centipawns: Vec<i32> = evals.iter().map(|e| e.eval_centipawns).collect();
+        assert_eq!(centipawns, [0, 0, 100, 0, 0, 0, 0, -900, 0]);
+
+        assert_eq!(evals[7].move_number, 4);
+        assert_eq!(evals[7].color, Color::Black);
+        assert_eq!(evals[8].color, Color::White);
+    }
+
+    #[test]
+    fn test_list_games_defaults() {
+        let (status, limit, offset) = ListGamesQuery::default().page().unwrap();
+        assert_eq!(status, None);
+        assert_eq!(limit, DEFAULT_PAGE_SIZE);
+        assert_eq!(offset, 0);
+    }
+
+    #[test]
+    fn test_list_games_limit_and_offset_are_bounded() {
+        let query = ListGamesQuery {
+            limit: Some(1000),
+            offset: Some(-5),
+            status: None,
+        };
+        assert_eq!(query.page().unwrap(), (None, MAX_PAGE_SIZE, 0));
+
+        let query = ListGamesQuery {
+            limit: Some(0),
+            offset: Some(40),
+            status: None,
+        };
+        assert_eq!(query.page().unwrap(), (None, 1, 40));
+    }
+
+    #[test]
+    fn test_list_games_status_filter() {
+        let query = ListGamesQuery {
+            status: Some("active".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query.page().unwrap().0, Some(GameStatus::Active));
+
+        let query = ListGamesQuery {
+            status: Some("finished".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(query.page(), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_idempotency_key_header() {
+        let mut headers = HeaderMap::new();
+        assert!(idempotency_key(&headers).unwrap().is_none());
+
+        headers.insert(IDEMPOTENCY_KEY, " move-42 ".parse().unwrap());
+        assert_eq!(
+            idempotency_key(&headers).unwrap().as_deref(),
+            Some("move-42")
+        );
+
+        for bad in [String::new(), "k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1)] {
+            headers.insert(IDEMPOTENCY_KEY, bad.parse().unwrap());
+            assert!(matches!(
+                idempotency_key(&headers),
+                Err(AppError::BadRequest(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_saved_move_response_replays_unchanged() {
+        let now = Utc::now();
+        let response = MoveResponse {
+            r#move: Move {
+                id: Uuid::new_v4(),
+                game_id: Uuid::new_v4(),
+                move_number: 1,
+                player_color: Color::White,
+                move_uci: "e2e4".to_string(),
+                move_san: "e4".to_string(),
+                position_before: GameState::new().fen().to_string(),
+                position_after: "after".to_string(),
+                timestamp: now,
+                time_taken_seconds: Some(12),
+                comment: Some("best by test".to_string()),
+                is_terminal: false,
+            },
+            game: shared::types::Game {
+                id: Uuid::new_v4(),
+                white_player_id: Uuid::new_v4(),
+                black_player_id: Uuid::new_v4(),
+                current_position: "after".to_string(),
+                status: GameStatus::Active,
+                current_turn: Color::Black,
+                move_deadline: Some(now),
+                created_at: now,
+                completed_at: None,
+                takebacks_allowed: false,
+                move_deadline_hours: 72,
+                is_public: false,
+                draw_reason: None,
+            },
+            gives_check: false,
+            opponent_legal_move_count: 20,
+            your_color: Some(Color::White),
+        };
+
+        // What gets stored is what a retry gets back
+        let saved = serde_json::to_value(&response).unwrap();
+        let replayed: MoveResponse = serde_json::from_value(saved.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&replayed).unwrap(), saved);
+    }
+
+    #[test]
+    fn test_moves_since() {
+        let moves: Vec<Move> = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"]
+            .iter()
+            .enumerate()
+            .map(|(ply, uci)| Move {
+                id: Uuid::new_v4(),
+                game_id: Uuid::nil(),
+                move_number: ply as i32 / 2 + 1,
+                player_color: if ply % 2 == 0 {
+                    Color::White
+                } else {
+                    Color::Black
+                },
+                move_uci: uci.to_string(),
+                move_san: String::new(),
+                position_before: String::new(),
+                position_after: String::new(),
+                timestamp: Utc::now(),
+                time_taken_seconds: None,
+                comment: None,
+                is_terminal: false,
+            })
+            .collect();
+
+        let ucis = |since| -> Vec<String> {
+            moves_since(moves.clone(), since)
+                .into_iter()
+                .map(|m| m.move_uci)
+                .collect()
+        };
+        assert_eq!(ucis(None).len(), 6);
+        assert_eq!(ucis(Some(3)), ["b8c6", "f1c4", "g8f6"]);
+        assert!(ucis(Some(6)).is_empty());
+        assert!(ucis(Some(100)).is_empty());
+    }
+
+    #[test]
+    fn test_game_info_from_listed_game() {
+        let listed = crate::models::GameWithPlayers {
+            id: Uuid::new_v4(),
+            white_player_id: Uuid::new_v4(),
+            black_player_id: Uuid::new_v4(),
+            white_player_username: "alice".to_string(),
+            black_player_username: "bob".to_string(),
+            current_position: String::new(),
+            status: "active".to_string(),
+            current_turn: "black".to_string(),
+            move_deadline: Some(Utc::now()),
+            created_at: Utc::now(),
+        };
+
+        let info = game_info(listed.clone());
+        assert_eq!(info.id, listed.id);
+        assert_eq!(info.black_player_username, "bob");
+        assert_eq!(info.status, GameStatus::Active);
+        assert_eq!(info.current_turn, Color::Black);
+        assert_eq!(info.move_deadline, listed.move_deadline);
+    }
+
+    #[test]
+    fn test_past_games_are_labelled_for_the_requester() {
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let finished = |status: &str| crate::models::GameWithPlayers {
+            id: Uuid::new_v4(),
+            white_player_id: alice,
+            black_player_id: bob,
+            white_player_username: "alice".to_string(),
+            black_player_username: "bob".to_string(),
+            current_position: String::new(),
+            status: status.to_string(),
+            current_turn: "white".to_string(),
+            move_deadline: None,
+            created_at: Utc::now(),
+        };
+        let outcome = |status: &str, user_id: Uuid| {
+            past_game_info(finished(status), user_id).map(|past| past.outcome_for_user)
+        };
+
+        assert_eq!(outcome("white_won", alice), Some(Outcome::Won));
+        assert_eq!(outcome("white_won", bob), Some(Outcome::Lost));
+        assert_eq!(outcome("black_won", alice), Some(Outcome::Lost));
+        assert_eq!(outcome("black_won", bob), Some(Outcome::Won));
+        assert_eq!(outcome("draw", bob), Some(Outcome::Draw));
+        assert_eq!(outcome("active", alice), None);
+    }
+
+    #[test]
+    fn test_pgn_file_headers() {
+        let filename = format!("game-{}.pgn", Uuid::nil());
+        let [(content_type, media_type), (disposition, attachment)] = pgn_file_headers(&filename);
+
+        assert_eq!(content_type, header::CONTENT_TYPE);
+        assert_eq!(media_type, "application/x-chess-pgn");
+        assert_eq!(disposition, header::CONTENT_DISPOSITION);
+        assert_eq!(
+            attachment,
+            "attachment; filename=\"game-00000000-0000-0000-0000-000000000000.pgn\""
+        );
+    }
+
+    #[test]
+    fn test_export_games_color_filter() {
+        let query = |color: Option<&str>| ExportGamesQuery {
+            color: color.map(String::from),
+        };
+
+        assert_eq!(query(None).color().unwrap(), None);
+        assert_eq!(query(Some("white")).color().unwrap(), Some(Color::White));
+        assert_eq!(query(Some("black")).color().unwrap(), Some(Color::Black));
+        assert!(matches!(
+            query(Some("red")).color(),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_details_response_puts_each_player_on_their_side() {
+        let now = Utc::now();
+        let details = crate::models::GameDetails {
+            game: test_game("active", false),
+            white_player_username: "alice".to_string(),
+            white_player_last_seen: Some(now),
+            black_player_username: "bob".to_string(),
+            black_player_last_seen: None,
+            move_count: 0,
+        };
+        let game = details.game.clone();
+
+        let response = details_response(details, Vec::new(), None, now);
+        assert_eq!(response.game.id, game.id);
+        assert_eq!(response.white_player.id, game.white_player_id);
+        assert_eq!(response.white_player.username, "alice");
+        assert!(response.white_player.online);
+        assert_eq!(response.black_player.id, game.black_player_id);
+        assert_eq!(response.black_player.username, "bob");
+        assert!(!response.black_player.online);
+        assert!(response.moves.is_empty());
+    }
+
+    #[test]
+    fn test_your_color_follows_the_caller() {
+        let game = test_game("active", false);
+        let white = game.white_player_id;
+        let black = game.black_player_id;
+        let player = |id| UserProfile {
+            id,
+            username: String::new(),
+            online: false,
+        };
+        let response_for = |viewer| {
+            assemble_game_response(game.clone(), player(white), player(black), vec![], viewer)
+        };
+
+        assert_eq!(response_for(Some(white)).your_color, Some(Color::White));
+        assert_eq!(response_for(Some(black)).your_color, Some(Color::Black));
+        assert_eq!(response_for(Some(Uuid::new_v4())).your_color, None);
+        assert_eq!(response_for(None).your_color, None);
+    }
+
+    #[test]
+    fn test_game_response_lists_captured_pieces() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let mut game = test_game("active", false);
+        game.current_position =
+            "rnb1kbnr/pp1ppppp/8/8/8/8/PPPPPPPP/R1BQKBNR w KQkq - 0 5".to_string();
+        let first = Move {
+            id: Uuid::new_v4(),
+            game_id: game.id,
+            move_number: 1,
+            player_color: Color::White,
+            move_uci: "e2e4".to_string(),
+            move_san: "e4".to_string(),
+            position_before: start.to_string(),
+            position_after: String::new(),
+            timestamp: Utc::now(),
+            time_taken_seconds: None,
+            comment: None,
+            is_terminal: false,
+        };
+        let player = |id| UserProfile {
+            id,
+            username: String::new(),
+            online: false,
+        };
+
+        let response = assemble_game_response(
+            game.clone(),
+            player(game.white_player_id),
+            player(game.black_player_id),
+            vec![first],
+            None,
+        );
+        assert_eq!(response.white_captured, vec!["queen", "pawn"]);
+        assert_eq!(response.black_captured, vec!["knight"]);
+
+        let fresh = assemble_game_response(
+            game.clone(),
+            player(game.white_player_id),
+            player(game.black_player_id),
+            vec![],
+            None,
+        );
+        assert!(fresh.white_captured.is_empty());
+        assert!(fresh.black_captured.is_empty());
+    }
 }
@@ -1,5 +1,17 @@
+pub mod admin;
 pub mod auth;
+pub mod challenges;
+pub mod explorer;
 pub mod games;
+pub mod tournaments;
+pub mod users;
+pub mod ws;
 
+pub use admin::*;
 pub use auth::*;
+pub use challenges::*;
+pub use explorer::*;
 pub use games::*;
+pub use tournaments::*;
+pub use users::*;
+pub use ws::*;
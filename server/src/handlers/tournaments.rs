@@ -0,0 +1,88 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    Json,
+};
+
+use super::games::game_info;
+use crate::error::Result;
+use crate::extractors::{JsonBody, TournamentId};
+use crate::middleware::AuthUser;
+use crate::models::Tournament;
+use crate::AppState;
+use shared::protocol::{
+    CreateTournamentRequest, TournamentGamesResponse, TournamentStandingsResponse,
+};
+use shared::types::{TournamentFormat, TournamentInfo};
+
+/// Create a tournament and start all its games (admin)
+pub async fn create_tournament(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    JsonBody(request): JsonBody<CreateTournamentRequest>,
+) -> Result<(StatusCode, Json<TournamentGamesResponse>)> {
+    let tournament = state
+        .tournament_service
+        .create_tournament(
+            &state.db,
+            auth.user_id,
+            &request.name,
+            &request.participant_ids,
+            request.format,
+        )
+        .await?;
+
+    let (tournament, games) = state
+        .tournament_service
+        .games(&state.db, tournament.id)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(TournamentGamesResponse {
+            tournament: tournament_info(tournament),
+            games: games.into_iter().map(game_info).collect(),
+        }),
+    ))
+}
+
+/// List a tournament's games
+pub async fn list_tournament_games(
+    State(state): State<AppState>,
+    TournamentId(tournament_id): TournamentId,
+) -> Result<Json<TournamentGamesResponse>> {
+    let (tournament, games) = state
+        .tournament_service
+        .games(&state.db, tournament_id)
+        .await?;
+
+    Ok(Json(TournamentGamesResponse {
+        tournament: tournament_info(tournament),
+        games: games.into_iter().map(game_info).collect(),
+    }))
+}
+
+/// Current standings of a tournament
+pub async fn get_tournament_standings(
+    State(state): State<AppState>,
+    TournamentId(tournament_id): TournamentId,
+) -> Result<Json<TournamentStandingsResponse>> {
+    let (tournament, standings) = state
+        .tournament_service
+        .standings(&state.db, tournament_id)
+        .await?;
+
+    Ok(Json(TournamentStandingsResponse {
+        tournament: tournament_info(tournament),
+        standings,
+    }))
+}
+
+fn tournament_info(t: Tournament) -> TournamentInfo {
+    TournamentInfo {
+        id: t.id,
+        name: t.name,
+        format: t.format.parse().unwrap_or(TournamentFormat::RoundRobin),
+        created_at: t.created_at,
+    }
+}
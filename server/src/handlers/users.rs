@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+use chrono::Utc;
+
+use crate::db::{games, users};
+use crate::error::{AppError, Result};
+use crate::extractors::JsonBody;
+use crate::middleware::AuthUser;
+use crate::AppState;
+use shared::protocol::{
+    ChangeEmailRequest, ConfirmEmailRequest, EmailChangeResponse, StandingsResponse,
+    UserProfileResponse,
+};
+use shared::types::UserStats;
+
+/// Public profile and game stats for a user
+pub async fn get_user_profile(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+) -> Result<Json<UserProfileResponse>> {
+    let user = users::find_by_username(&state.db, &username)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let stats = games::stats_for_user(&state.db, user.id).await?;
+
+    Ok(Json(UserProfileResponse {
+        online: user.is_online(Utc::now()),
+        username: user.username,
+        member_since: user.created_at,
+        stats: UserStats {
+            played: stats.played,
+            won: stats.won,
+            lost: stats.lost,
+            drawn: stats.drawn,
+        },
+    }))
+}
+
+/// Count the authenticated user's active games by material standing
+pub async fn get_standings(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+) -> Result<Json<StandingsResponse>> {
+    let standings = state
+        .game_service
+        .standings(&state.db, auth.user_id)
+        .await?;
+
+    Ok(Json(standings))
+}
+
+/// Change the authenticated user's email (pending until confirmed if verification is on)
+pub async fn change_email(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<ChangeEmailRequest>,
+) -> Result<Json<EmailChangeResponse>> {
+    let (user, pending_email) = state
+        .auth_service
+        .change_email(&state.db, auth.user_id, req.new_email, req.password)
+        .await?;
+
+    Ok(Json(EmailChangeResponse {
+        user: shared::types::User {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            created_at: user.created_at,
+            verified: user.verified,
+        },
+        pending_email,
+    }))
+}
+
+/// Confirm a pending email change
+pub async fn confirm_email(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<AppState>,
+    JsonBody(req): JsonBody<ConfirmEmailRequest>,
+) -> Result<Json<EmailChangeResponse>> {
+    let user = state
+        .auth_service
+        .confirm_email(&state.db, auth.user_id, req.token)
+        .await?;
+
+    Ok(Json(EmailChangeResponse {
+        user: shared::types::User {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            created_at: user.created_at,
+            verified: user.verified,
+        },
+        pending_email: None,
+    }))
+}
@@ -0,0 +1,484 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{header, HeaderMap},
+    response::Response,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc,
+};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::db::games;
+use crate::error::{AppError, Result};
+use crate::services::ConnectionGuard;
+use crate::AppState;
+use shared::protocol::{ClientMessage, ServerMessage};
+
+/// Subprotocol a browser offers alongside its token, as
+/// `Sec-WebSocket-Protocol: bearer, <JWT>`
+const BEARER_PROTOCOL: &str = "bearer";
+
+#[derive(Debug, Deserialize)]
+pub struct WsQuery {
+    pub token: Option<String>,
+}
+
+/// Upgrade to a WebSocket for live game events
+///
+/// Browsers can't set headers on WebSocket requests, so the JWT may be
+/// passed as `?token=` or through `Sec-WebSocket-Protocol` instead of the
+/// usual Authorization header. Without a valid token the upgrade fails
+/// with 401, and a user who already has as many connections open as
+/// allowed gets 429.
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(query): Query<WsQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let token = query
+        .token
+        .or_else(|| bearer_token(&headers))
+        .or_else(|| subprotocol_token(&headers))
+        .ok_or_else(|| AppError::Auth("Missing token".to_string()))?;
+    let user_id = authenticate_ws(&state, &token).await?;
+    let connection = open_connection(&state, user_id)?;
+
+    // Browsers drop the connection unless the offered subprotocol is echoed
+    Ok(ws
+        .protocols([BEARER_PROTOCOL])
+        .on_upgrade(move |socket| async move {
+            handle_socket(socket, state, user_id).await;
+            drop(connection);
+        }))
+}
+
+/// Count a connection against the user's cap, refusing it if they're at the cap
+fn open_connection(state: &AppState, user_id: Uuid) -> Result<ConnectionGuard> {
+    state
+        .game_events
+        .connect(user_id, state.ws_max_connections_per_user)
+        .ok_or(AppError::TooManyRequests {
+            retry_after_secs: state.ws_heartbeat.as_secs(),
+        })
+}
+
+/// Check a WebSocket client's JWT the way `auth_middleware` does,
+/// returning the user it belongs to
+pub async fn authenticate_ws(state: &AppState, token: &str) -> Result<Uuid> {
+    let claims = state.auth_service.validate_token(&state.db, token).await?;
+
+    Uuid::parse_str(&claims.sub).map_err(|_| AppError::Auth("Invalid user ID in token".to_string()))
+}
+
+/// Pings a client may leave unanswered before the connection is closed
+const MAX_MISSED_PONGS: u32 = 2;
+
+/// What to do when the heartbeat interval comes round
+#[derive(Debug, PartialEq, Eq)]
+enum Heartbeat {
+    /// The client was heard from recently
+    Idle,
+    /// Silent for a while: ping it
+    Ping,
+    /// Pings went unanswered: give up on the connection
+    Close,
+}
+
+/// Tracks whether a client is still there, one interval at a time
+#[derive(Debug, Default)]
+struct Liveness {
+    heard_from: bool,
+    missed: u32,
+}
+
+impl Liveness {
+    /// Any frame from the client, including a pong, shows it's alive
+    fn heard(&mut self) {
+        self.heard_from = true;
+        self.missed = 0;
+    }
+
+    fn tick(&mut self) -> Heartbeat {
+        if std::mem::take(&mut self.heard_from) {
+            Heartbeat::Idle
+        } else if self.missed >= MAX_MISSED_PONGS {
+            Heartbeat::Close
+        } else {
+            self.missed += 1;
+            Heartbeat::Ping
+        }
+    }
+}
+
+/// Serve one connection until the client goes away
+async fn handle_socket(mut socket: WebSocket, state: AppState, user_id: Uuid) {
+    // Subscription tasks forward game events here to be written to the socket
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel::<ServerMessage>();
+    let mut subscriptions: HashMap<Uuid, JoinHandle<()>> = HashMap::new();
+
+    // Events addressed to this user (e.g. deadline warnings) need no subscription
+    let user_events = forward(state.game_events.subscribe_user(user_id), events_tx.clone());
+
+    // Proxies can drop idle connections without either side noticing
+    let mut heartbeat = tokio::time::interval_at(
+        tokio::time::Instant::now() + state.ws_heartbeat,
+        state.ws_heartbeat,
+    );
+    let mut liveness = Liveness::default();
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let text = match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(Message::Text(text))) => {
+                        liveness.heard();
+                        text
+                    }
+                    Some(Ok(_)) => {
+                        liveness.heard();
+                        continue;
+                    }
+                };
+
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Subscribe { game_id }) => {
+                        subscribe(&state, user_id, game_id, &events_tx, &mut subscriptions).await
+                    }
+                    Ok(ClientMessage::Unsubscribe { game_id }) => {
+                        if let Some(task) = subscriptions.remove(&game_id) {
+                            task.abort();
+                        }
+                        None
+                    }
+                    Ok(ClientMessage::Ping) => Some(ServerMessage::Pong),
+                    Err(_) => Some(ServerMessage::Error {
+                        message: "Invalid message".to_string(),
+                    }),
+                };
+
+                if let Some(reply) = reply {
+                    if send(&mut socket, &reply).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            Some(event) = events_rx.recv() => {
+                if send(&mut socket, &event).await.is_err() {
+                    break;
+                }
+            }
+            _ = heartbeat.tick() => match liveness.tick() {
+                Heartbeat::Idle => {}
+                Heartbeat::Ping => {
+                    if socket.send(Message::Ping(Default::default())).await.is_err() {
+                        break;
+                    }
+                }
+                Heartbeat::Close => {
+                    tracing::debug!("Closing unresponsive WebSocket for user {}", user_id);
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                }
+            },
+        }
+    }
+
+    user_events.abort();
+    for task in subscriptions.into_values() {
+        task.abort();
+    }
+}
+
+/// Subscribe the connection to a game, returning an error message to send back if refused
+async fn subscribe(
+    state: &AppState,
+    user_id: Uuid,
+    game_id: Uuid,
+    events_tx: &mpsc::UnboundedSender<ServerMessage>,
+    subscriptions: &mut HashMap<Uuid, JoinHandle<()>>,
+) -> Option<ServerMessage> {
+    let allowed = match games::find_by_id(&state.db, game_id).await {
+        Ok(game) => may_subscribe(game.as_ref(), user_id),
+        Err(e) => {
+            tracing::error!("Failed to load game {} for subscription: {:?}", game_id, e);
+            false
+        }
+    };
+
+    if !allowed {
+        return Some(ServerMessage::Error {
+            message: "You are not a player in this game".to_string(),
+        });
+    }
+
+    let task = forward(state.game_events.subscribe(game_id), events_tx.clone());
+
+    if let Some(previous) = subscriptions.insert(game_id, task) {
+        previous.abort();
+    }
+
+    None
+}
+
+/// Players may follow their own games, and anyone may follow a public one;
+/// unknown games are refused like foreign ones so ids can't be probed
+fn may_subscribe(game: Option<&crate::models::Game>, user_id: Uuid) -> bool {
+    game.is_some_and(|game| {
+        game.is_public || game.white_player_id == user_id || game.black_player_id == user_id
+    })
+}
+
+/// Copy events from a broadcast channel into the connection's outgoing queue
+fn forward(
+    mut events: broadcast::Receiver<ServerMessage>,
+    events_tx: mpsc::UnboundedSender<ServerMessage>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if events_tx.send(event).is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> std::result::Result<(), ()> {
+    let text = serde_json::to_string(message).map_err(|_| ())?;
+    socket
+        .send(Message::Text(text.into()))
+        .await
+        .map_err(|_| ())
+}
+
+/// Token from an `Authorization: Bearer ...` header, if present
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|token| token.to_string())
+}
+
+/// Token offered as `Sec-WebSocket-Protocol: bearer, <JWT>`, if present
+fn subprotocol_token(headers: &HeaderMap) -> Option<String> {
+    let protocols = headers
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|h| h.to_str().ok())?;
+
+    let mut offered = protocols.split(',').map(str::trim);
+    offered.find(|protocol| *protocol == BEARER_PROTOCOL)?;
+    offered
+        .next()
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{
+        AuthService, ChallengeService, CreationPolicy, ExplorerService, GameEvents, GameService,
+        JwtPolicy, TournamentService,
+    };
+
+    /// State whose database can't be reached
+    fn test_state() -> AppState {
+        let db = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(100))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        let game_events = GameEvents::new();
+        let game_service = GameService::new(
+            72,
+            CreationPolicy::default(),
+            false,
+            None,
+            true,
+            game_events.clone(),
+        );
+
+        AppState {
+            db,
+            auth_service: AuthService::new(
+                "secret".to_string(),
+                JwtPolicy::default(),
+                7,
+                30,
+                Vec::new(),
+                false,
+                4,
+            ),
+            challenge_service: ChallengeService::new(game_service.clone(), game_events.clone()),
+            tournament_service: TournamentService::new(game_service.clone()),
+            game_service,
+            explorer_service: ExplorerService::new(),
+            game_events,
+            ws_heartbeat: std::time::Duration::from_secs(30),
+            ws_max_connections_per_user: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_token_is_unauthorized() {
+        let state = test_state();
+
+        for token in ["", "not.a.jwt"] {
+            let err = authenticate_ws(&state, token).await.unwrap_err();
+            assert_eq!(err.status(), axum::http::StatusCode::UNAUTHORIZED);
+        }
+
+        // Signed with another secret
+        let other = AuthService::new(
+            "other".to_string(),
+            JwtPolicy::default(),
+            7,
+            30,
+            Vec::new(),
+            false,
+            4,
+        );
+        let forged = other.generate_token(Uuid::new_v4(), "mallory").unwrap();
+        let err = authenticate_ws(&state, &forged).await.unwrap_err();
+        assert_eq!(err.status(), axum::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_connection_over_cap_is_refused_before_upgrade() {
+        let state = test_state();
+        let user_id = Uuid::new_v4();
+
+        let _first = open_connection(&state, user_id).unwrap();
+        let second = open_connection(&state, user_id).unwrap();
+        let err = open_connection(&state, user_id).err().unwrap();
+        assert_eq!(err.status(), axum::http::StatusCode::TOO_MANY_REQUESTS);
+
+        drop(second);
+        assert!(open_connection(&state, user_id).is_ok());
+    }
+
+    #[test]
+    fn test_subscribe_only_to_own_or_public_games() {
+        let now = chrono::Utc::now();
+        let mut game = crate::models::Game {
+            id: Uuid::new_v4(),
+            white_player_id: Uuid::new_v4(),
+            black_player_id: Uuid::new_v4(),
+            current_position: String::new(),
+            game_state: serde_json::Value::Null,
+            status: "active".to_string(),
+            current_turn: "white".to_string(),
+            move_deadline: Some(now),
+            created_at: now,
+            completed_at: None,
+            takebacks_allowed: false,
+            move_deadline_hours: 72,
+            is_public: false,
+            draw_reason: None,
+            win_reason: None,
+            version: 0,
+        };
+        let outsider = Uuid::new_v4();
+
+        assert!(may_subscribe(Some(&game), game.white_player_id));
+        assert!(may_subscribe(Some(&game), game.black_player_id));
+        assert!(!may_subscribe(Some(&game), outsider));
+        assert!(!may_subscribe(None, outsider));
+
+        game.is_public = true;
+        assert!(may_subscribe(Some(&game), outsider));
+    }
+
+    #[tokio::test]
+    async fn test_refused_subscription_gets_error_and_no_events() {
+        let state = test_state();
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let mut subscriptions = HashMap::new();
+        let game_id = Uuid::new_v4();
+
+        // The lazy pool can't connect, so the lookup fails and is refused
+        let reply = subscribe(
+            &state,
+            Uuid::new_v4(),
+            game_id,
+            &events_tx,
+            &mut subscriptions,
+        )
+        .await;
+        assert!(matches!(reply, Some(ServerMessage::Error { .. })));
+        assert!(subscriptions.is_empty());
+
+        state.game_events.publish(game_id, ServerMessage::Pong);
+        assert!(events_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_silent_connection_is_closed_after_two_missed_pongs() {
+        let mut liveness = Liveness::default();
+        assert_eq!(liveness.tick(), Heartbeat::Ping);
+        assert_eq!(liveness.tick(), Heartbeat::Ping);
+        assert_eq!(liveness.tick(), Heartbeat::Close);
+    }
+
+    #[test]
+    fn test_activity_keeps_connection_open() {
+        let mut liveness = Liveness::default();
+        for _ in 0..5 {
+            liveness.heard();
+            assert_eq!(liveness.tick(), Heartbeat::Idle);
+        }
+
+        // A pong after one missed interval resets the count
+        assert_eq!(liveness.tick(), Heartbeat::Ping);
+        liveness.heard();
+        assert_eq!(liveness.tick(), Heartbeat::Idle);
+        assert_eq!(liveness.tick(), Heartbeat::Ping);
+        assert_eq!(liveness.tick(), Heartbeat::Ping);
+        assert_eq!(liveness.tick(), Heartbeat::Close);
+    }
+
+    #[test]
+    fn test_subprotocol_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(subprotocol_token(&headers), None);
+
+        headers.insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            "bearer, abc.def".parse().unwrap(),
+        );
+        assert_eq!(subprotocol_token(&headers), Some("abc.def".to_string()));
+
+        // Other protocols alone carry no token
+        headers.insert(header::SEC_WEBSOCKET_PROTOCOL, "chat, abc".parse().unwrap());
+        assert_eq!(subprotocol_token(&headers), None);
+
+        headers.insert(header::SEC_WEBSOCKET_PROTOCOL, "bearer".parse().unwrap());
+        assert_eq!(subprotocol_token(&headers), None);
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, "Bearer abc.def".parse().unwrap());
+        assert_eq!(bearer_token(&headers), Some("abc.def".to_string()));
+
+        headers.insert(header::AUTHORIZATION, "Basic abc".parse().unwrap());
+        assert_eq!(bearer_token(&headers), None);
+    }
+}
@@ -3,16 +3,29 @@ pub mod models;
 pub mod db;
 pub mod config;
 pub mod error;
+pub mod extractors;
 pub mod services;
 pub mod middleware;
 pub mod handlers;
+pub mod shutdown;
 
 use sqlx::PgPool;
-use services::{AuthService, GameService};
+use std::time::Duration;
+use services::{
+    AuthService, ChallengeService, ExplorerService, GameEvents, GameService, TournamentService,
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub auth_service: AuthService,
     pub game_service: GameService,
+    pub challenge_service: ChallengeService,
+    pub explorer_service: ExplorerService,
+    pub tournament_service: TournamentService,
+    pub game_events: GameEvents,
+    /// How long a WebSocket may stay silent before it's pinged
+    pub ws_heartbeat: Duration,
+    /// Most WebSocket connections one user may have open at once
+    pub ws_max_connections_per_user: usize,
 }
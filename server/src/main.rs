@@ -1,6 +1,7 @@
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use sqlx::postgres::PgPoolOptions;
@@ -10,9 +11,12 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use rusty_chess_server::{
     config::Config,
     handlers,
-    middleware::auth_middleware,
-    services::{AuthService, GameService},
-    AppState,
+    middleware::{admin_middleware, auth_middleware, login_rate_limit, LoginRateLimiter},
+    services::{
+        AuthService, ChallengeService, CreationPolicy, ExplorerService, GameEvents, GameService,
+        JwtPolicy, TournamentService,
+    },
+    shutdown, AppState,
 };
 
 #[tokio::main]
@@ -31,6 +35,10 @@ async fn main() -> anyhow::Result<()> {
 
     // Load configuration
     let config = Config::from_env();
+    if let Err(e) = config.validate() {
+        tracing::error!("Invalid configuration: {}", e);
+        return Err(e.into());
+    }
     tracing::info!("Starting Rusty Chess Server");
     tracing::info!("Server address: {}", config.server_address());
 
@@ -49,43 +57,186 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Migrations complete");
 
     // Create services
-    let auth_service = AuthService::new(config.jwt_secret.clone(), config.jwt_expiry_days);
-    let game_service = GameService::new(config.move_deadline_hours);
+    let auth_service = AuthService::new(
+        config.jwt_secret.clone(),
+        JwtPolicy {
+            issuer: config.jwt_issuer.clone(),
+            audience: config.jwt_audience.clone(),
+            leeway_seconds: config.jwt_leeway_seconds,
+        },
+        config.jwt_expiry_days,
+        config.refresh_token_expiry_days,
+        config.admin_usernames.clone(),
+        config.email_verification_enabled,
+        config.bcrypt_cost,
+    );
+    let game_events = GameEvents::new();
+    let game_service = GameService::new(
+        config.move_deadline_hours,
+        CreationPolicy {
+            min_account_age_hours: config.min_account_age_hours,
+            min_completed_games: config.min_completed_games,
+        },
+        config.takebacks_allowed_default,
+        config.max_active_games_per_user,
+        config.allow_duplicate_active_games,
+        game_events.clone(),
+    );
+    let challenge_service = ChallengeService::new(game_service.clone(), game_events.clone());
+    let explorer_service = ExplorerService::new();
+    let tournament_service = TournamentService::new(game_service.clone());
 
     // Create app state
     let state = AppState {
         db: pool,
         auth_service,
         game_service,
+        challenge_service,
+        explorer_service,
+        tournament_service,
+        game_events,
+        ws_heartbeat: std::time::Duration::from_secs(config.ws_heartbeat_seconds.max(1)),
+        ws_max_connections_per_user: config.max_ws_connections_per_user,
     };
 
+    // Background tasks stop between runs once shutdown starts
+    let (shutdown_trigger, shutdown) = shutdown::channel();
+
+    // Adjudicate games that run out of time
+    let background_tasks = vec![
+        tokio::spawn(state.game_service.clone().run_deadline_sweeper(
+            state.db.clone(),
+            std::time::Duration::from_secs(config.deadline_sweep_seconds.max(1)),
+            config.abandon_after_hours,
+            shutdown.clone(),
+        )),
+        tokio::spawn(state.game_service.clone().run_deadline_warnings(
+            state.db.clone(),
+            std::time::Duration::from_secs(config.deadline_sweep_seconds.max(1)),
+            config.deadline_warning_hours,
+            shutdown.clone(),
+        )),
+        // Expire challenges nobody answered
+        tokio::spawn(state.challenge_service.clone().run_expiry_sweeper(
+            state.db.clone(),
+            std::time::Duration::from_secs(config.deadline_sweep_seconds.max(1)),
+            config.challenge_expiry_hours,
+            shutdown.clone(),
+        )),
+        // Forget revocations of tokens that have expired anyway
+        tokio::spawn(state.auth_service.clone().run_revocation_cleanup(
+            state.db.clone(),
+            std::time::Duration::from_secs(3600),
+            shutdown,
+        )),
+    ];
+
     // Build router
     let protected_routes = Router::new()
         .route("/api/games", get(handlers::list_games))
         .route("/api/games", post(handlers::create_game))
-        .route("/api/games/:id", get(handlers::get_game))
-        .route("/api/games/:id/moves", post(handlers::submit_move))
-        .route("/api/games/:id/moves", get(handlers::get_moves))
-        .route("/api/games/:id/pgn", get(handlers::export_pgn))
+        .route("/api/games/import", post(handlers::import_game))
+        .route("/api/games/awaiting", get(handlers::list_awaiting_games))
+        .route("/api/games/history", get(handlers::list_game_history))
+        .route("/api/games/{id}", get(handlers::get_game))
+        .route("/api/games/{id}/moves", post(handlers::submit_move))
+        .route("/api/games/{id}/moves", get(handlers::get_moves))
+        .route("/api/games/{id}/moves/{ply}", get(handlers::get_move))
+        .route("/api/games/{id}/analysis", get(handlers::get_analysis))
+        .route("/api/games/{id}/legal_moves", get(handlers::get_legal_moves))
+        .route("/api/games/{id}/board", get(handlers::get_board))
+        .route("/api/games/{id}/pgn", get(handlers::export_pgn))
+        .route("/api/games/{id}/pgn.txt", get(handlers::export_pgn_file))
+        .route("/api/games/{id}/resign", post(handlers::resign_game))
+        .route("/api/games/{id}/abort", post(handlers::abort_game))
+        .route("/api/games/{id}/claim-draw", post(handlers::claim_draw))
+        .route("/api/games/{id}/takeback", post(handlers::request_takeback))
+        .route("/api/games/{id}/takeback/respond", post(handlers::respond_to_takeback))
+        .route("/api/games/{id}/review/{ply}", get(handlers::review_position))
+        .route("/api/games/{id}/conditional_moves", get(handlers::list_conditional_moves))
+        .route("/api/games/{id}/conditional_moves", post(handlers::set_conditional_move))
+        .route("/api/games/{id}/conditional_moves", delete(handlers::clear_conditional_moves))
+        .route("/api/challenges", get(handlers::list_challenges))
+        .route("/api/challenges", post(handlers::send_challenge))
+        .route("/api/challenges/{id}/accept", post(handlers::accept_challenge))
+        .route("/api/challenges/{id}/decline", post(handlers::decline_challenge))
+        .route("/api/explorer", get(handlers::explorer))
+        .route("/api/users/{username}", get(handlers::get_user_profile))
+        .route("/api/tournaments/{id}/games", get(handlers::list_tournament_games))
+        .route("/api/tournaments/{id}/standings", get(handlers::get_tournament_standings))
+        .route("/api/me/standings", get(handlers::get_standings))
+        .route("/api/me/games.pgn", get(handlers::export_games_pgn))
+        .route("/api/users/me/games.pgn", get(handlers::export_games_pgn))
+        .route("/api/me/email", post(handlers::change_email))
+        .route("/api/me/email/confirm", post(handlers::confirm_email))
+        .route("/api/auth/logout", post(handlers::logout))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ));
+
+    let admin_routes = Router::new()
+        .route("/api/admin/games/verify", get(handlers::audit_games))
+        .route("/api/admin/games/{id}/verify", get(handlers::verify_game))
+        .route("/api/admin/tournaments", post(handlers::create_tournament))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            admin_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
         ));
 
+    // Throttle password guessing
+    let login_limiter = LoginRateLimiter::new(
+        config.login_max_failures,
+        std::time::Duration::from_secs(config.login_failure_window_seconds),
+    );
+
     let app = Router::new()
         // Public routes
         .route("/api/auth/register", post(handlers::register))
-        .route("/api/auth/login", post(handlers::login))
+        .route(
+            "/api/auth/login",
+            post(handlers::login).layer(middleware::from_fn_with_state(
+                login_limiter,
+                login_rate_limit,
+            )),
+        )
+        .route("/api/auth/refresh", post(handlers::refresh))
+        .route("/api/auth/verify", post(handlers::verify_email))
+        .route("/api/games/{id}/public", get(handlers::get_public_game))
+        // Authenticates itself, since browsers can't send headers on upgrade
+        .route("/api/ws", get(handlers::ws_handler))
         // Merge protected routes
         .merge(protected_routes)
+        .merge(admin_routes)
         .with_state(state)
+        .layer(DefaultBodyLimit::max(config.max_request_body_bytes))
         .layer(TraceLayer::new_for_http());
 
     // Start server
     let listener = tokio::net::TcpListener::bind(&config.server_address()).await?;
     tracing::info!("Server listening on {}", config.server_address());
 
-    axum::serve(listener, app).await?;
+    // Connect info gives the rate limiter the client's IP. On Ctrl-C or
+    // SIGTERM, stop accepting connections and let in-flight requests finish.
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        shutdown::signal().await;
+        tracing::info!("Shutting down");
+        shutdown_trigger.trigger();
+    })
+    .await?;
+
+    for task in background_tasks {
+        let _ = task.await;
+    }
+    tracing::info!("Shutdown complete");
 
     Ok(())
 }
@@ -4,6 +4,7 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use crate::error::{AppError, Result};
@@ -12,6 +13,9 @@ use crate::AppState;
 #[derive(Clone)]
 pub struct AuthUser {
     pub user_id: Uuid,
+    /// ID and expiry of the token used, so logout can revoke it
+    pub jti: String,
+    pub token_expires_at: DateTime<Utc>,
 }
 
 /// Extract user ID from Authorization header
@@ -30,14 +34,42 @@ pub async fn auth_middleware(
         .strip_prefix("Bearer ")
         .ok_or_else(|| AppError::Auth("Invalid authorization header format".to_string()))?;
 
-    let claims = state.auth_service.validate_token(token)?;
+    let claims = state.auth_service.validate_token(&state.db, token).await?;
 
     // Parse user ID from claims
     let user_id = Uuid::parse_str(&claims.sub)
         .map_err(|_| AppError::Auth("Invalid user ID in token".to_string()))?;
+    let token_expires_at = DateTime::from_timestamp(claims.exp, 0)
+        .ok_or_else(|| AppError::Auth("Invalid expiry in token".to_string()))?;
+
+    // Keep last_seen fresh for online indicators; failing to isn't worth failing the request
+    if let Err(e) = state.auth_service.record_activity(&state.db, user_id).await {
+        tracing::warn!("Failed to record activity for user {}: {}", user_id, e);
+    }
 
     // Insert AuthUser into request extensions
-    req.extensions_mut().insert(AuthUser { user_id });
+    req.extensions_mut().insert(AuthUser {
+        user_id,
+        jti: claims.jti,
+        token_expires_at,
+    });
+
+    Ok(next.run(req).await)
+}
+
+/// Reject requests from users who aren't configured as admins
+///
+/// Must run after `auth_middleware`, which provides the `AuthUser`.
+pub async fn admin_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response> {
+    let user_id = get_user_id(&req)?;
+
+    if !state.auth_service.is_admin(&state.db, user_id).await? {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
 
     Ok(next.run(req).await)
 }
@@ -0,0 +1,191 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use shared::protocol::LoginRequest;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::db::users::username_key;
+use crate::error::AppError;
+
+/// Largest login body we'll buffer to read the username from
+const MAX_LOGIN_BODY_BYTES: usize = 16 * 1024;
+
+/// Failed logins counted in a fixed window
+struct Window {
+    started: Instant,
+    failures: u32,
+}
+
+/// Counts failed logins per username and client IP, in memory
+///
+/// Once a key has `max_failures` failures inside `window`, further attempts
+/// are refused until the window runs out. A successful login clears the key.
+#[derive(Clone)]
+pub struct LoginRateLimiter {
+    max_failures: u32,
+    window: Duration,
+    attempts: Arc<Mutex<HashMap<String, Window>>>,
+}
+
+impl LoginRateLimiter {
+    pub fn new(max_failures: u32, window: Duration) -> Self {
+        Self {
+            max_failures,
+            window,
+            attempts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Err with the time left in the window if `key` is locked out
+    fn check(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut attempts = self.attempts.lock().unwrap();
+        let Some(window) = attempts.get(key) else {
+            return Ok(());
+        };
+
+        let elapsed = now.duration_since(window.started);
+        if elapsed >= self.window {
+            attempts.remove(key);
+            Ok(())
+        } else if window.failures >= self.max_failures {
+            Err(self.window - elapsed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn record_failure(&self, key: &str, now: Instant) {
+        let mut attempts = self.attempts.lock().unwrap();
+        let window = attempts.entry(key.to_string()).or_insert(Window {
+            started: now,
+            failures: 0,
+        });
+        if now.duration_since(window.started) >= self.window {
+            window.started = now;
+            window.failures = 0;
+        }
+        window.failures += 1;
+
+        // Drop expired windows so the map doesn't grow without bound
+        let length = self.window;
+        attempts.retain(|_, w| now.duration_since(w.started) < length);
+    }
+
+    fn reset(&self, key: &str) {
+        self.attempts.lock().unwrap().remove(key);
+    }
+}
+
+/// Throttle failed logins, answering 429 with `Retry-After` when locked out
+pub async fn login_rate_limit(
+    State(limiter): State<LoginRateLimiter>,
+    req: Request,
+    next: Next,
+) -> Response {
+    // The peer address is only there when served with connect info
+    let ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_default();
+
+    // Read the username out of the body, then hand the body on
+    let (parts, body) = req.into_parts();
+    let bytes = match to_bytes(body, MAX_LOGIN_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return AppError::BadRequest("Request body too large".to_string()).into_response()
+        }
+    };
+    let username = serde_json::from_slice::<LoginRequest>(&bytes)
+        .map(|login| username_key(&login.username))
+        .unwrap_or_default();
+    let key = format!("{}|{}", username, ip);
+
+    if let Err(retry_after) = limiter.check(&key, Instant::now()) {
+        tracing::warn!("Login throttled for {:?} from {:?}", username, ip);
+        return AppError::TooManyRequests {
+            // Round up so clients never retry a moment too early
+            retry_after_secs: retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0),
+        }
+        .into_response();
+    }
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    match response.status() {
+        StatusCode::UNAUTHORIZED => limiter.record_failure(&key, Instant::now()),
+        status if status.is_success() => limiter.reset(&key),
+        _ => {}
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::header, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn login_request(username: &str) -> Request {
+        Request::post("/login")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!(
+                r#"{{"username":"{}","password":"wrong"}}"#,
+                username
+            )))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_attempt_after_max_failures_is_rejected() {
+        let limiter = LoginRateLimiter::new(3, Duration::from_secs(60));
+        let app = Router::new()
+            .route("/login", post(|| async { StatusCode::UNAUTHORIZED }))
+            .layer(middleware::from_fn_with_state(limiter, login_rate_limit));
+
+        for _ in 0..3 {
+            let response = app.clone().oneshot(login_request("alice")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let response = app.clone().oneshot(login_request("alice")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+
+        // Other usernames have their own counter
+        let response = app.oneshot(login_request("bob")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_window_expiry_and_success_clear_failures() {
+        let limiter = LoginRateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        limiter.record_failure("alice|", start);
+        limiter.record_failure("alice|", start);
+        assert_eq!(
+            limiter.check("alice|", start + Duration::from_secs(20)),
+            Err(Duration::from_secs(40))
+        );
+        assert_eq!(
+            limiter.check("alice|", start + Duration::from_secs(60)),
+            Ok(())
+        );
+
+        limiter.record_failure("bob|", start);
+        limiter.reset("bob|");
+        limiter.record_failure("bob|", start);
+        assert_eq!(limiter.check("bob|", start), Ok(()));
+    }
+}
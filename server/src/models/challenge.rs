@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Challenge {
+    pub id: Uuid,
+    pub challenger_id: Uuid,
+    pub challenged_id: Uuid,
+    pub color_preference: Option<String>,
+    pub takebacks_allowed: Option<bool>,
+    pub time_control: Option<String>,
+    pub is_public: bool,
+    pub starting_fen: Option<String>,
+    pub status: String,
+    pub game_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewChallenge {
+    pub challenger_id: Uuid,
+    pub challenged_id: Uuid,
+    pub color_preference: Option<String>,
+    pub takebacks_allowed: Option<bool>,
+    pub time_control: Option<String>,
+    pub is_public: bool,
+    pub starting_fen: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ChallengeWithPlayers {
+    pub id: Uuid,
+    pub challenger_id: Uuid,
+    pub challenged_id: Uuid,
+    pub challenger_username: String,
+    pub challenged_username: String,
+    pub color_preference: Option<String>,
+    pub time_control: Option<String>,
+    pub status: String,
+    pub game_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A reply entered in advance, played when the game reaches `position_fen`
+#[derive(Debug, Clone, FromRow)]
+pub struct ConditionalMove {
+    pub id: Uuid,
+    pub game_id: Uuid,
+    pub user_id: Uuid,
+    /// Normalized FEN (see `normalize_fen`) with `user_id` to move
+    pub position_fen: String,
+    pub move_uci: String,
+    pub created_at: DateTime<Utc>,
+}
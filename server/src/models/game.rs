@@ -15,6 +15,15 @@ pub struct Game {
     pub move_deadline: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub takebacks_allowed: bool,
+    pub move_deadline_hours: i64,
+    pub is_public: bool,
+    /// Why a drawn game was drawn, e.g. "stalemate" or "agreement"
+    pub draw_reason: Option<String>,
+    /// How a decisive game was won: "checkmate", "resignation" or "timeout"
+    pub win_reason: Option<String>,
+    /// Incremented on every change of position
+    pub version: i32,
 }
 
 #[derive(Debug, Clone)]
@@ -25,6 +34,30 @@ pub struct NewGame {
     pub game_state: JsonValue,
     pub status: String,
     pub current_turn: String,
+    pub takebacks_allowed: bool,
+    pub move_deadline_hours: i64,
+    pub is_public: bool,
+}
+
+/// A game with what its detail view needs from other tables, read in one query
+#[derive(Debug, Clone, FromRow)]
+pub struct GameDetails {
+    #[sqlx(flatten)]
+    pub game: Game,
+    pub white_player_username: String,
+    pub white_player_last_seen: Option<DateTime<Utc>>,
+    pub black_player_username: String,
+    pub black_player_last_seen: Option<DateTime<Utc>>,
+    pub move_count: i64,
+}
+
+/// A finished game with its players' names, for PGN export
+#[derive(Debug, Clone, FromRow)]
+pub struct ArchivedGame {
+    #[sqlx(flatten)]
+    pub game: Game,
+    pub white_player_username: String,
+    pub black_player_username: String,
 }
 
 #[derive(Debug, Clone, FromRow)]
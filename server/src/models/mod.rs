@@ -1,7 +1,15 @@
 pub mod user;
 pub mod game;
 pub mod move_record;
+pub mod token;
+pub mod challenge;
+pub mod conditional_move;
+pub mod tournament;
 
 pub use user::*;
 pub use game::*;
 pub use move_record::*;
+pub use token::*;
+pub use challenge::*;
+pub use conditional_move::*;
+pub use tournament::*;
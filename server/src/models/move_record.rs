@@ -13,6 +13,11 @@ pub struct MoveRecord {
     pub position_before: String,
     pub position_after: String,
     pub timestamp: DateTime<Utc>,
+    /// Seconds since the previous move (or game creation); None for imported moves
+    pub time_taken_seconds: Option<i64>,
+    pub comment: Option<String>,
+    /// The move ended the game (mate or a draw); resignations and timeouts aren't moves
+    pub is_terminal: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -24,4 +29,16 @@ pub struct NewMove {
     pub move_san: String,
     pub position_before: String,
     pub position_after: String,
+    pub time_taken_seconds: Option<i64>,
+    pub comment: Option<String>,
+    pub is_terminal: bool,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct PositionContinuation {
+    pub move_uci: String,
+    pub move_san: String,
+    pub game_status: String,
+    pub is_public: bool,
+    pub imported: bool,
 }
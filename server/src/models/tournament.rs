@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Tournament {
+    pub id: Uuid,
+    pub name: String,
+    /// Only "round_robin" so far
+    pub format: String,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TournamentPlayer {
+    pub user_id: Uuid,
+    pub username: String,
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use sqlx::FromRow;
 use uuid::Uuid;
 
@@ -10,6 +10,22 @@ pub struct User {
     pub email: String,
     pub created_at: DateTime<Utc>,
     pub last_seen: Option<DateTime<Utc>>,
+    /// Whether the email address has been confirmed
+    pub verified: bool,
+}
+
+/// Users seen this recently are shown as online
+const ONLINE_WINDOW_MINUTES: i64 = 5;
+
+impl User {
+    pub fn is_online(&self, now: DateTime<Utc>) -> bool {
+        seen_recently(self.last_seen, now)
+    }
+}
+
+/// Whether a user last seen at `last_seen` counts as online at `now`
+pub fn seen_recently(last_seen: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    last_seen.is_some_and(|seen| now - seen < Duration::minutes(ONLINE_WINDOW_MINUTES))
 }
 
 #[derive(Debug, Clone)]
@@ -17,4 +33,22 @@ pub struct NewUser {
     pub username: String,
     pub password_hash: String,
     pub email: String,
+    pub verified: bool,
+}
+
+/// Results of a user's finished games
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UserStats {
+    pub played: i64,
+    pub won: i64,
+    pub lost: i64,
+    pub drawn: i64,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct EmailVerificationToken {
+    pub token: String,
+    pub user_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
 }
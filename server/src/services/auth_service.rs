@@ -1,13 +1,27 @@
-use bcrypt::{hash, verify, DEFAULT_COST};
-use chrono::{Duration, Utc};
+use bcrypt::{hash, verify};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::errors::ErrorKind;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use uuid::Uuid;
 
-use crate::db::users;
+use crate::db::{tokens, users};
 use crate::error::{AppError, Result};
-use crate::models::NewUser;
+use crate::middleware::AuthUser;
+use crate::models::{EmailVerificationToken, NewUser, RefreshToken, User};
+use crate::shutdown::Shutdown;
+
+/// How long a new account has to verify its email address
+const VERIFICATION_TOKEN_HOURS: i64 = 48;
+
+/// Write a user's `last_seen` at most this often, however busy they are
+const LAST_SEEN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
@@ -15,25 +29,104 @@ pub struct Claims {
     pub username: String, // Username
     pub exp: i64,         // Expiry timestamp
     pub iat: i64,         // Issued at timestamp
+    pub jti: String,      // Token ID, for revocation on logout
+    pub iss: String,      // Issuer
+    pub aud: String,      // Audience
+}
+
+/// Tokens handed out on registration, login and refresh
+#[derive(Debug, Clone)]
+pub struct Tokens {
+    /// Short-lived JWT sent with every request
+    pub access_token: String,
+    /// Opaque, single-use token for getting a new access token
+    pub refresh_token: String,
+}
+
+/// Who access tokens come from and who they're for
+///
+/// Both are checked on every token, so one minted by another service that
+/// happens to share the secret is refused.
+#[derive(Debug, Clone)]
+pub struct JwtPolicy {
+    pub issuer: String,
+    pub audience: String,
+    /// Grace period after `exp`, for clients whose clocks run behind
+    pub leeway_seconds: i64,
+}
+
+impl Default for JwtPolicy {
+    fn default() -> Self {
+        Self {
+            issuer: "rusty-chess".to_string(),
+            audience: "rusty-chess".to_string(),
+            leeway_seconds: 60,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct AuthService {
     jwt_secret: String,
+    jwt_policy: JwtPolicy,
     jwt_expiry_days: i64,
+    refresh_token_expiry_days: i64,
+    admin_usernames: Vec<String>,
+    email_verification_enabled: bool,
+    bcrypt_cost: u32,
+    /// When each recently active user's `last_seen` was last written
+    last_seen_writes: Arc<Mutex<HashMap<Uuid, Instant>>>,
+}
+
+/// Outcome of looking up a presented refresh token
+#[derive(Debug, PartialEq, Eq)]
+enum RefreshCheck {
+    /// Usable: revoke it and issue a new pair
+    Valid { id: Uuid, user_id: Uuid },
+    /// Already rotated or revoked. Presenting it again suggests it was
+    /// stolen, so all of the user's refresh tokens get revoked.
+    Reused { user_id: Uuid },
+    /// Unknown or expired
+    Invalid,
+}
+
+/// What to do with a requested email change
+#[derive(Debug, PartialEq, Eq)]
+enum EmailChange {
+    /// Update the account right away
+    Immediate,
+    /// Hold the new address until the token sent to it is confirmed
+    Pending { token: String },
 }
 
 impl AuthService {
-    pub fn new(jwt_secret: String, jwt_expiry_days: i64) -> Self {
+    pub fn new(
+        jwt_secret: String,
+        jwt_policy: JwtPolicy,
+        jwt_expiry_days: i64,
+        refresh_token_expiry_days: i64,
+        admin_usernames: Vec<String>,
+        email_verification_enabled: bool,
+        bcrypt_cost: u32,
+    ) -> Self {
         Self {
             jwt_secret,
+            jwt_policy,
             jwt_expiry_days,
+            refresh_token_expiry_days,
+            admin_usernames,
+            email_verification_enabled,
+            bcrypt_cost,
+            last_seen_writes: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    /// Hash a password using bcrypt
+    /// Hash a password using bcrypt at the configured cost
+    ///
+    /// The cost is stored in the hash, so changing it leaves existing
+    /// passwords verifiable.
     pub fn hash_password(&self, password: &str) -> Result<String> {
-        let hashed = hash(password, DEFAULT_COST)?;
+        let hashed = hash(password, self.bcrypt_cost)?;
         Ok(hashed)
     }
 
@@ -45,7 +138,15 @@ impl AuthService {
 
     /// Generate a JWT token for a user
     pub fn generate_token(&self, user_id: Uuid, username: &str) -> Result<String> {
-        let now = Utc::now();
+        self.generate_token_at(user_id, username, Utc::now())
+    }
+
+    fn generate_token_at(
+        &self,
+        user_id: Uuid,
+        username: &str,
+        now: DateTime<Utc>,
+    ) -> Result<String> {
         let expiry = now + Duration::days(self.jwt_expiry_days);
 
         let claims = Claims {
@@ -53,6 +154,9 @@ impl AuthService {
             username: username.to_string(),
             exp: expiry.timestamp(),
             iat: now.timestamp(),
+            jti: Uuid::new_v4().simple().to_string(),
+            iss: self.jwt_policy.issuer.clone(),
+            aud: self.jwt_policy.audience.clone(),
         };
 
         let token = encode(
@@ -64,17 +168,162 @@ impl AuthService {
         Ok(token)
     }
 
-    /// Validate a JWT token and extract claims
-    pub fn validate_token(&self, token: &str) -> Result<Claims> {
+    /// Check a JWT's signature, expiry, issuer and audience and extract its claims
+    ///
+    /// Doesn't consult the revocation list; use `validate_token` to
+    /// authenticate requests.
+    pub fn decode_token(&self, token: &str) -> Result<Claims> {
+        self.decode_token_at(token, Utc::now())
+    }
+
+    fn decode_token_at(&self, token: &str, now: DateTime<Utc>) -> Result<Claims> {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&self.jwt_policy.issuer]);
+        validation.set_audience(&[&self.jwt_policy.audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+        // Expiry is checked below against `now`, with our own leeway
+        validation.validate_exp = false;
+
         let token_data = decode::<Claims>(
             token,
             &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
-            &Validation::default(),
+            &validation,
         )?;
 
+        // Expired from `exp` plus the leeway onwards, to the second
+        if now.timestamp() >= token_data.claims.exp + self.jwt_policy.leeway_seconds {
+            let expired = jsonwebtoken::errors::Error::from(ErrorKind::ExpiredSignature);
+            return Err(expired.into());
+        }
+
         Ok(token_data.claims)
     }
 
+    /// Validate a JWT token and extract claims, rejecting logged-out tokens
+    pub async fn validate_token(&self, pool: &PgPool, token: &str) -> Result<Claims> {
+        let claims = self.decode_token(token)?;
+        let revoked = tokens::is_jti_revoked(pool, &claims.jti).await?;
+
+        reject_revoked(claims, revoked)
+    }
+
+    /// Issue an access token and a new refresh token for a user
+    async fn issue_tokens(&self, pool: &PgPool, user: &crate::models::User) -> Result<Tokens> {
+        let access_token = self.generate_token(user.id, &user.username)?;
+
+        let refresh_token = generate_refresh_token();
+        let expires_at = Utc::now() + Duration::days(self.refresh_token_expiry_days);
+        tokens::create_refresh_token(
+            pool,
+            user.id,
+            &hash_refresh_token(&refresh_token),
+            expires_at,
+        )
+        .await?;
+
+        Ok(Tokens {
+            access_token,
+            refresh_token,
+        })
+    }
+
+    /// Exchange a refresh token for a new access token and refresh token
+    ///
+    /// The presented token is revoked, so each refresh token works once.
+    pub async fn refresh(
+        &self,
+        pool: &PgPool,
+        refresh_token: &str,
+    ) -> Result<(crate::models::User, Tokens)> {
+        let record = tokens::find_refresh_token(pool, &hash_refresh_token(refresh_token)).await?;
+
+        match check_refresh_token(record.as_ref(), Utc::now()) {
+            RefreshCheck::Valid { id, user_id } => {
+                // Losing a race with a concurrent refresh counts as reuse
+                if !tokens::revoke_refresh_token(pool, id).await? {
+                    tokens::revoke_all_for_user(pool, user_id).await?;
+                    return Err(AppError::Auth("Refresh token has been revoked".to_string()));
+                }
+
+                let user = users::find_by_id(pool, user_id)
+                    .await?
+                    .ok_or_else(|| AppError::Auth("User not found".to_string()))?;
+                let tokens = self.issue_tokens(pool, &user).await?;
+
+                Ok((user, tokens))
+            }
+            RefreshCheck::Reused { user_id } => {
+                tracing::warn!("Revoked refresh token reused for user {}", user_id);
+                tokens::revoke_all_for_user(pool, user_id).await?;
+                Err(AppError::Auth("Refresh token has been revoked".to_string()))
+            }
+            RefreshCheck::Invalid => Err(AppError::Auth(
+                "Invalid or expired refresh token".to_string(),
+            )),
+        }
+    }
+
+    /// Log out: deny the access token until it expires, and revoke the
+    /// refresh token if one is given
+    ///
+    /// Refresh tokens belonging to someone else are left alone.
+    pub async fn logout(
+        &self,
+        pool: &PgPool,
+        user: &AuthUser,
+        refresh_token: Option<&str>,
+    ) -> Result<()> {
+        tokens::revoke_jti(pool, &user.jti, user.token_expires_at).await?;
+
+        if let Some(refresh_token) = refresh_token {
+            let record =
+                tokens::find_refresh_token(pool, &hash_refresh_token(refresh_token)).await?;
+
+            if let Some(record) = record.filter(|r| r.user_id == user.user_id) {
+                tokens::revoke_refresh_token(pool, record.id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Periodically drop revocations of access tokens that have expired
+    pub async fn run_revocation_cleanup(
+        self,
+        pool: PgPool,
+        every: std::time::Duration,
+        mut shutdown: Shutdown,
+    ) {
+        let mut ticker = tokio::time::interval(every);
+
+        loop {
+            // Only stop between runs, so a run in progress always completes
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.requested() => break,
+            }
+
+            match tokens::purge_expired_revocations(&pool).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Purged {} expired token revocations", count),
+                Err(e) => tracing::error!("Token revocation cleanup failed: {:?}", e),
+            }
+        }
+    }
+
+    /// Check whether a user is configured as an admin
+    pub async fn is_admin(&self, pool: &PgPool, user_id: Uuid) -> Result<bool> {
+        if self.admin_usernames.is_empty() {
+            return Ok(false);
+        }
+
+        let user = users::find_by_id(pool, user_id)
+            .await?
+            .ok_or_else(|| AppError::Auth("User not found".to_string()))?;
+
+        Ok(is_admin_username(&self.admin_usernames, &user.username))
+    }
+
     /// Register a new user
     pub async fn register(
         &self,
@@ -82,7 +331,9 @@ impl AuthService {
         username: String,
         email: String,
         password: String,
-    ) -> Result<(crate::models::User, String)> {
+    ) -> Result<(crate::models::User, Tokens)> {
+        let username = username.trim().to_string();
+
         // Validate input
         if username.is_empty() || username.len() > 50 {
             return Err(AppError::Validation(
@@ -90,9 +341,7 @@ impl AuthService {
             ));
         }
 
-        if email.is_empty() || !email.contains('@') {
-            return Err(AppError::Validation("Invalid email address".to_string()));
-        }
+        validate_email(&email)?;
 
         if password.len() < 6 {
             return Err(AppError::Validation(
@@ -100,32 +349,48 @@ impl AuthService {
             ));
         }
 
-        // Check if username already exists
-        if let Some(_) = users::find_by_username(pool, &username).await? {
+        // Check if username already exists, in any case
+        if users::find_by_username(pool, &username).await?.is_some() {
             return Err(AppError::Validation("Username already taken".to_string()));
         }
 
         // Check if email already exists
-        if let Some(_) = users::find_by_email(pool, &email).await? {
+        if users::find_by_email(pool, &email).await?.is_some() {
             return Err(AppError::Validation("Email already registered".to_string()));
         }
 
         // Hash password
         let password_hash = self.hash_password(&password)?;
 
-        // Create user
+        // Create user; without verification the address is taken on trust
         let new_user = NewUser {
             username: username.clone(),
             password_hash,
             email,
+            verified: !self.email_verification_enabled,
         };
 
         let user = users::create_user(pool, &new_user).await?;
 
-        // Generate token
-        let token = self.generate_token(user.id, &user.username)?;
+        if self.email_verification_enabled {
+            let token = Uuid::new_v4().simple().to_string();
+            let expires_at = Utc::now() + Duration::hours(VERIFICATION_TOKEN_HOURS);
+            users::create_verification_token(pool, user.id, &token, expires_at).await?;
 
-        Ok((user, token))
+            // No mail transport is configured yet, so the token is logged
+            // for whoever operates the server to pass on.
+            tracing::info!(
+                "Email verification token for {} ({}): {}",
+                user.username,
+                user.email,
+                token
+            );
+        }
+
+        // Generate tokens
+        let tokens = self.issue_tokens(pool, &user).await?;
+
+        Ok((user, tokens))
     }
 
     /// Login a user
@@ -134,7 +399,7 @@ impl AuthService {
         pool: &PgPool,
         username: String,
         password: String,
-    ) -> Result<(crate::models::User, String)> {
+    ) -> Result<(crate::models::User, Tokens)> {
         // Find user
         let user = users::find_by_username(pool, &username)
             .await?
@@ -145,12 +410,570 @@ impl AuthService {
             return Err(AppError::Auth("Invalid username or password".to_string()));
         }
 
-        // Generate token
-        let token = self.generate_token(user.id, &user.username)?;
+        // Generate tokens
+        let tokens = self.issue_tokens(pool, &user).await?;
 
         // Update last seen
         users::update_last_seen(pool, user.id).await?;
 
-        Ok((user, token))
+        Ok((user, tokens))
+    }
+
+    /// Note that a user made a request, updating `last_seen` if it's due
+    pub async fn record_activity(&self, pool: &PgPool, user_id: Uuid) -> Result<()> {
+        if self.last_seen_due(user_id, Instant::now()) {
+            users::update_last_seen(pool, user_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether `last_seen` should be written now, claiming the write if so
+    fn last_seen_due(&self, user_id: Uuid, now: Instant) -> bool {
+        let mut writes = self.last_seen_writes.lock().unwrap();
+        if writes
+            .get(&user_id)
+            .is_some_and(|&at| now.duration_since(at) < LAST_SEEN_INTERVAL)
+        {
+            return false;
+        }
+        writes.insert(user_id, now);
+
+        // Forget users who've gone quiet so the map doesn't grow without bound
+        writes.retain(|_, at| now.duration_since(*at) < LAST_SEEN_INTERVAL);
+        true
+    }
+
+    /// Change a user's email after re-checking their password
+    ///
+    /// Returns the (possibly unchanged) user and, when verification is
+    /// enabled, the address waiting to be confirmed.
+    pub async fn change_email(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        new_email: String,
+        password: String,
+    ) -> Result<(crate::models::User, Option<String>)> {
+        let user = users::find_by_id(pool, user_id)
+            .await?
+            .ok_or_else(|| AppError::Auth("User not found".to_string()))?;
+
+        if !self.verify_password(&password, &user.password_hash)? {
+            return Err(AppError::Auth("Invalid password".to_string()));
+        }
+
+        let email_taken = users::find_by_email(pool, &new_email).await?.is_some();
+
+        match plan_email_change(
+            &user.email,
+            &new_email,
+            email_taken,
+            self.email_verification_enabled,
+        )? {
+            EmailChange::Immediate => {
+                let user = users::update_email(pool, user_id, &new_email).await?;
+                Ok((user, None))
+            }
+            EmailChange::Pending { token } => {
+                users::set_pending_email(pool, user_id, &new_email, &token).await?;
+
+                // No mail transport is configured yet, so the token is logged
+                // for whoever operates the server to pass on.
+                tracing::info!(
+                    "Email verification token for {} ({}): {}",
+                    user.username,
+                    new_email,
+                    token
+                );
+
+                Ok((user, Some(new_email)))
+            }
+        }
+    }
+
+    /// Verify a new account's email address with the token sent to it
+    pub async fn verify_email(&self, pool: &PgPool, token: &str) -> Result<User> {
+        let record = users::find_verification_token(pool, token).await?;
+        let user_id = check_verification_token(record.as_ref(), Utc::now())?;
+
+        let user = users::mark_verified(pool, user_id).await?;
+
+        Ok(user)
+    }
+
+    /// Confirm a pending email change with the token sent to the new address
+    pub async fn confirm_email(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        token: String,
+    ) -> Result<crate::models::User> {
+        let new_email = users::take_pending_email(pool, user_id, &token)
+            .await?
+            .ok_or_else(|| AppError::Validation("Invalid or expired token".to_string()))?;
+
+        // The address may have been registered by someone else in the meantime
+        if users::find_by_email(pool, &new_email).await?.is_some() {
+            return Err(AppError::Validation("Email already registered".to_string()));
+        }
+
+        let user = users::update_email(pool, user_id, &new_email).await?;
+
+        Ok(user)
+    }
+}
+
+/// The user a verification token belongs to, if it is known and unexpired
+fn check_verification_token(
+    record: Option<&EmailVerificationToken>,
+    now: DateTime<Utc>,
+) -> Result<Uuid> {
+    match record {
+        Some(record) if record.expires_at > now => Ok(record.user_id),
+        _ => Err(AppError::Validation("Invalid or expired token".to_string())),
+    }
+}
+
+/// Refuse actions reserved for users who have verified their email
+pub fn ensure_verified(user: &User) -> Result<()> {
+    if !user.verified {
+        return Err(AppError::Forbidden(
+            "Verify your email address first".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether `username` is in the configured admin list, ignoring case
+fn is_admin_username(admin_usernames: &[String], username: &str) -> bool {
+    let key = users::username_key(username);
+    admin_usernames
+        .iter()
+        .any(|admin| users::username_key(admin) == key)
+}
+
+/// Refuse a token that was revoked by logging out
+fn reject_revoked(claims: Claims, revoked: bool) -> Result<Claims> {
+    if revoked {
+        return Err(AppError::Auth("Token has been revoked".to_string()));
+    }
+
+    Ok(claims)
+}
+
+/// A new random refresh token (32 bytes, hex encoded)
+fn generate_refresh_token() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 32]>())
+}
+
+/// Refresh tokens are stored hashed so a database leak doesn't expose them
+fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Decide whether a stored refresh token may be used at `now`
+fn check_refresh_token(record: Option<&RefreshToken>, now: DateTime<Utc>) -> RefreshCheck {
+    match record {
+        None => RefreshCheck::Invalid,
+        Some(record) if record.revoked_at.is_some() => RefreshCheck::Reused {
+            user_id: record.user_id,
+        },
+        Some(record) if record.expires_at <= now => RefreshCheck::Invalid,
+        Some(record) => RefreshCheck::Valid {
+            id: record.id,
+            user_id: record.user_id,
+        },
+    }
+}
+
+/// Email shape check: a local part and a dotted domain around a single '@',
+/// with no whitespace anywhere
+fn is_valid_email(email: &str) -> bool {
+    if email.chars().any(char::is_whitespace) {
+        return false;
+    }
+
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && domain.split('.').all(|label| !label.is_empty())
+        }
+        None => false,
+    }
+}
+
+fn validate_email(email: &str) -> Result<()> {
+    if !is_valid_email(email) {
+        return Err(AppError::Validation(
+            "Invalid email address: expected something like name@example.com".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Decide how to apply an email change, rejecting invalid or taken addresses
+fn plan_email_change(
+    current_email: &str,
+    new_email: &str,
+    email_taken: bool,
+    verification_enabled: bool,
+) -> Result<EmailChange> {
+    validate_email(new_email)?;
+
+    if new_email == current_email {
+        return Err(AppError::Validation(
+            "New email is the same as the current one".to_string(),
+        ));
+    }
+
+    if email_taken {
+        return Err(AppError::Validation("Email already registered".to_string()));
+    }
+
+    if verification_enabled {
+        Ok(EmailChange::Pending {
+            token: Uuid::new_v4().simple().to_string(),
+        })
+    } else {
+        Ok(EmailChange::Immediate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn refresh_record(expires_in_days: i64) -> RefreshToken {
+        let now = Utc::now();
+        RefreshToken {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            token_hash: hash_refresh_token("token"),
+            expires_at: now + Duration::days(expires_in_days),
+            created_at: now,
+            revoked_at: None,
+        }
+    }
+
+    fn auth_service(jwt_policy: JwtPolicy, bcrypt_cost: u32) -> AuthService {
+        AuthService::new(
+            "secret".to_string(),
+            jwt_policy,
+            7,
+            30,
+            Vec::new(),
+            false,
+            bcrypt_cost,
+        )
+    }
+
+    #[test]
+    fn test_token_rejected_after_logout() {
+        let service = auth_service(JwtPolicy::default(), 4);
+        let user_id = Uuid::new_v4();
+        let token = service.generate_token(user_id, "alice").unwrap();
+
+        let claims = service.decode_token(&token).unwrap();
+        assert_eq!(claims.sub, user_id.to_string());
+        assert!(reject_revoked(claims.clone(), false).is_ok());
+
+        // Logging out puts the token's jti on the denylist
+        assert!(matches!(
+            reject_revoked(claims, true),
+            Err(AppError::Auth(_))
+        ));
+    }
+
+    #[test]
+    fn test_hashes_verify_across_cost_changes() {
+        let old = auth_service(JwtPolicy::default(), 4);
+        let new = auth_service(JwtPolicy::default(), 5);
+
+        let old_hash = old.hash_password("hunter22").unwrap();
+        assert!(old_hash.starts_with("$2b$04$"));
+        assert!(old.verify_password("hunter22", &old_hash).unwrap());
+        assert!(!old.verify_password("hunter23", &old_hash).unwrap());
+
+        // Raising the cost doesn't lock out existing accounts
+        assert!(new.verify_password("hunter22", &old_hash).unwrap());
+        assert!(new
+            .hash_password("hunter22")
+            .unwrap()
+            .starts_with("$2b$05$"));
+    }
+
+    #[test]
+    fn test_token_issuer_and_audience_are_checked() {
+        let service = auth_service(JwtPolicy::default(), 4);
+        let token = service.generate_token(Uuid::new_v4(), "alice").unwrap();
+        let claims = service.decode_token(&token).unwrap();
+        assert_eq!(claims.iss, "rusty-chess");
+        assert_eq!(claims.aud, "rusty-chess");
+
+        // Same secret, but meant for another service
+        let other = auth_service(
+            JwtPolicy {
+                audience: "billing".to_string(),
+                ..JwtPolicy::default()
+            },
+            4,
+        );
+        let foreign = other.generate_token(Uuid::new_v4(), "alice").unwrap();
+        assert!(matches!(
+            service.decode_token(&foreign),
+            Err(AppError::Jwt(_))
+        ));
+        assert!(matches!(other.decode_token(&token), Err(AppError::Jwt(_))));
+
+        let impostor = auth_service(
+            JwtPolicy {
+                issuer: "someone-else".to_string(),
+                ..JwtPolicy::default()
+            },
+            4,
+        );
+        let forged = impostor.generate_token(Uuid::new_v4(), "alice").unwrap();
+        assert!(matches!(
+            service.decode_token(&forged),
+            Err(AppError::Jwt(_))
+        ));
+    }
+
+    #[test]
+    fn test_token_expiry_allows_the_leeway_and_no_more() {
+        let service = auth_service(
+            JwtPolicy {
+                leeway_seconds: 30,
+                ..JwtPolicy::default()
+            },
+            4,
+        );
+        let issued_at: DateTime<Utc> = "2026-10-01T12:00:00Z".parse().unwrap();
+        let token = service
+            .generate_token_at(Uuid::new_v4(), "alice", issued_at)
+            .unwrap();
+        let expiry = issued_at + Duration::days(7);
+
+        for (at, valid) in [
+            (expiry - Duration::seconds(1), true),
+            (expiry, true),
+            (expiry + Duration::seconds(29), true),
+            (expiry + Duration::seconds(30), false),
+            (expiry + Duration::seconds(31), false),
+        ] {
+            let result = service.decode_token_at(&token, at);
+            assert_eq!(result.is_ok(), valid, "at {}", at);
+            if !valid {
+                assert!(matches!(result, Err(AppError::Jwt(_))));
+            }
+        }
+
+        // Without leeway a token is dead at exactly its expiry
+        let strict = auth_service(
+            JwtPolicy {
+                leeway_seconds: 0,
+                ..JwtPolicy::default()
+            },
+            4,
+        );
+        assert!(strict
+            .decode_token_at(&token, expiry - Duration::seconds(1))
+            .is_ok());
+        assert!(strict.decode_token_at(&token, expiry).is_err());
+    }
+
+    #[test]
+    fn test_last_seen_written_at_most_once_a_minute() {
+        let service = auth_service(JwtPolicy::default(), 4);
+        let alice = Uuid::new_v4();
+        let start = Instant::now();
+        let later = |secs| start + std::time::Duration::from_secs(secs);
+
+        assert!(service.last_seen_due(alice, start));
+        assert!(!service.last_seen_due(alice, later(1)));
+        assert!(!service.last_seen_due(alice, later(59)));
+        assert!(service.last_seen_due(alice, later(60)));
+        assert!(!service.last_seen_due(alice, later(61)));
+
+        // Each user has their own window
+        assert!(service.last_seen_due(Uuid::new_v4(), later(61)));
+    }
+
+    #[test]
+    fn test_tokens_get_distinct_ids() {
+        let service = auth_service(JwtPolicy::default(), 4);
+        let user_id = Uuid::new_v4();
+        let first = service.generate_token(user_id, "alice").unwrap();
+        let second = service.generate_token(user_id, "alice").unwrap();
+
+        assert_ne!(
+            service.decode_token(&first).unwrap().jti,
+            service.decode_token(&second).unwrap().jti
+        );
+    }
+
+    #[test]
+    fn test_refresh_tokens_are_random_and_hashed() {
+        let token = generate_refresh_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_refresh_token());
+
+        let hashed = hash_refresh_token(&token);
+        assert_eq!(hashed.len(), 64);
+        assert_ne!(hashed, token);
+        assert_eq!(hashed, hash_refresh_token(&token));
+    }
+
+    #[test]
+    fn test_refresh_token_rotation_rejects_reuse() {
+        let now = Utc::now();
+        let mut record = refresh_record(30);
+
+        assert_eq!(
+            check_refresh_token(Some(&record), now),
+            RefreshCheck::Valid {
+                id: record.id,
+                user_id: record.user_id,
+            }
+        );
+
+        // Refreshing revokes the token; presenting it again is reuse
+        record.revoked_at = Some(now);
+        assert_eq!(
+            check_refresh_token(Some(&record), now),
+            RefreshCheck::Reused {
+                user_id: record.user_id,
+            }
+        );
+    }
+
+    #[test]
+    fn test_expired_or_unknown_refresh_token_is_invalid() {
+        let now = Utc::now();
+        assert_eq!(check_refresh_token(None, now), RefreshCheck::Invalid);
+
+        let expired = refresh_record(-1);
+        assert_eq!(
+            check_refresh_token(Some(&expired), now),
+            RefreshCheck::Invalid
+        );
+    }
+
+    fn test_user(verified: bool) -> User {
+        User {
+            id: Uuid::new_v4(),
+            username: "alice".to_string(),
+            password_hash: String::new(),
+            email: "alice@example.com".to_string(),
+            created_at: Utc::now(),
+            last_seen: None,
+            verified,
+        }
+    }
+
+    #[test]
+    fn test_verification_token_validity() {
+        let now = Utc::now();
+        let record = EmailVerificationToken {
+            token: "token".to_string(),
+            user_id: Uuid::new_v4(),
+            expires_at: now + Duration::hours(VERIFICATION_TOKEN_HOURS),
+            created_at: now,
+        };
+        assert_eq!(
+            check_verification_token(Some(&record), now).unwrap(),
+            record.user_id
+        );
+
+        let expired = EmailVerificationToken {
+            expires_at: now - Duration::seconds(1),
+            ..record
+        };
+        assert!(check_verification_token(Some(&expired), now).is_err());
+        assert!(check_verification_token(None, now).is_err());
+    }
+
+    #[test]
+    fn test_unverified_users_are_gated() {
+        assert!(ensure_verified(&test_user(true)).is_ok());
+        assert!(matches!(
+            ensure_verified(&test_user(false)),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_admin_usernames_match_any_case() {
+        let admins = vec!["Alice".to_string()];
+        assert!(is_admin_username(&admins, "alice"));
+        assert!(is_admin_username(&admins, "ALICE"));
+        assert!(!is_admin_username(&admins, "bob"));
+    }
+
+    #[test]
+    fn test_is_valid_email() {
+        let cases = [
+            ("alice@example.com", true),
+            ("alice.smith+chess@mail.example.co.uk", true),
+            ("a@b.io", true),
+            ("", false),
+            ("alice", false),
+            ("@", false),
+            ("a@", false),
+            ("@example.com", false),
+            ("alice@localhost", false),
+            ("alice@example.", false),
+            ("alice@.com", false),
+            ("alice@example..com", false),
+            ("a@b@c.com", false),
+            ("alice smith@example.com", false),
+            ("alice@exa mple.com", false),
+            (" alice@example.com", false),
+        ];
+
+        for (email, valid) in cases {
+            assert_eq!(is_valid_email(email), valid, "{:?}", email);
+        }
+    }
+
+    #[test]
+    fn test_validate_email() {
+        assert!(validate_email("alice@example.com").is_ok());
+        assert!(matches!(validate_email("a@"), Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_email_change_without_verification_is_immediate() {
+        let change = plan_email_change("old@example.com", "new@example.com", false, false).unwrap();
+        assert_eq!(change, EmailChange::Immediate);
+    }
+
+    #[test]
+    fn test_email_change_with_verification_is_pending() {
+        let change = plan_email_change("old@example.com", "new@example.com", false, true).unwrap();
+        match change {
+            EmailChange::Pending { token } => assert_eq!(token.len(), 32),
+            other => panic!("expected pending change, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_email_change_rejects_duplicate_email() {
+        let result = plan_email_change("old@example.com", "taken@example.com", true, false);
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let result = plan_email_change("old@example.com", "taken@example.com", true, true);
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_email_change_rejects_invalid_or_unchanged_email() {
+        assert!(plan_email_change("old@example.com", "not-an-email", false, false).is_err());
+        assert!(plan_email_change("old@example.com", "old@example.com", false, false).is_err());
     }
 }
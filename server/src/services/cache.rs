@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// An in-memory cache that never holds more than `capacity` entries
+///
+/// Entries older than `ttl` are treated as missing and dropped on the next
+/// insert; if the cache is still full, the oldest entry makes way.
+pub struct BoundedCache<K, V> {
+    entries: HashMap<K, (Instant, V)>,
+    capacity: usize,
+    ttl: Option<Duration>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> BoundedCache<K, V> {
+    pub fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// The value cached for `key`, unless it has expired
+    pub fn get(&self, key: &K, now: Instant) -> Option<V> {
+        let (stored_at, value) = self.entries.get(key)?;
+        (!is_older_than(*stored_at, now, self.ttl)).then(|| value.clone())
+    }
+
+    pub fn insert(&mut self, key: K, value: V, now: Instant) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, (stored_at, _)| !is_older_than(*stored_at, now, ttl));
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (stored_at, _))| *stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.entries.insert(key, (now, value));
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+fn is_older_than(stored_at: Instant, now: Instant, ttl: Option<Duration>) -> bool {
+    ttl.is_some_and(|ttl| now.saturating_duration_since(stored_at) >= ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oldest_entry_makes_way_when_full() {
+        let start = Instant::now();
+        let mut cache = BoundedCache::new(2, None);
+        cache.insert("a", 1, start);
+        cache.insert("b", 2, start + Duration::from_secs(1));
+        cache.insert("c", 3, start + Duration::from_secs(2));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"a", start), None);
+        assert_eq!(cache.get(&"b", start), Some(2));
+        assert_eq!(cache.get(&"c", start), Some(3));
+
+        // Replacing a key doesn't evict anything
+        cache.insert("c", 4, start + Duration::from_secs(3));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"b", start), Some(2));
+    }
+
+    #[test]
+    fn test_expired_entries_are_missing_and_dropped() {
+        let start = Instant::now();
+        let ttl = Duration::from_secs(60);
+        let mut cache = BoundedCache::new(3, Some(ttl));
+        cache.insert("a", 1, start);
+        cache.insert("b", 2, start);
+        cache.insert("c", 3, start + ttl);
+
+        let later = start + ttl + Duration::from_secs(1);
+        assert_eq!(cache.get(&"a", later), None);
+        assert_eq!(cache.get(&"c", later), Some(3));
+
+        // The next insert drops both expired entries, not the fresh one
+        cache.insert("d", 4, later);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"c", later), Some(3));
+        assert_eq!(cache.get(&"d", later), Some(4));
+    }
+}
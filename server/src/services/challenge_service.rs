@@ -0,0 +1,411 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{challenges, users};
+use crate::error::{AppError, Result};
+use crate::models::{Challenge, ChallengeWithPlayers, NewChallenge};
+use crate::services::{ensure_not_self, starting_position, GameEvents, GameOptions, GameService};
+use crate::shutdown::Shutdown;
+use shared::protocol::{CreateGameRequest, ServerMessage};
+use shared::types::{Color, TimeControl};
+
+/// Invitations to play; a game only starts once the challenged player accepts
+#[derive(Clone)]
+pub struct ChallengeService {
+    game_service: GameService,
+    events: GameEvents,
+}
+
+impl ChallengeService {
+    pub fn new(game_service: GameService, events: GameEvents) -> Self {
+        Self {
+            game_service,
+            events,
+        }
+    }
+
+    /// Challenge another player and tell them about it over WebSocket
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        challenger_id: Uuid,
+        request: CreateGameRequest,
+    ) -> Result<ChallengeWithPlayers> {
+        let color_preference = parse_color_preference(request.player_color.as_deref())?;
+        if let Some(fen) = request.starting_fen.as_deref() {
+            starting_position(fen)?;
+        }
+
+        let challenged = users::find_by_username(pool, &request.opponent_username)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Opponent not found".to_string()))?;
+
+        ensure_not_self(challenged.id, challenger_id)?;
+
+        let new_challenge = NewChallenge {
+            challenger_id,
+            challenged_id: challenged.id,
+            color_preference: color_preference.map(|color| color.to_string()),
+            takebacks_allowed: request.takebacks_allowed,
+            time_control: request.time_control.map(|tc| tc.to_string()),
+            is_public: request.is_public,
+            starting_fen: request.starting_fen,
+        };
+        let challenge = challenges::create_challenge(pool, &new_challenge).await?;
+        let challenge = challenges::find_with_players(pool, challenge.id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Challenge not found".to_string()))?;
+
+        self.events.publish_to_user(
+            challenge.challenged_id,
+            ServerMessage::ChallengeReceived {
+                challenge_id: challenge.id,
+                opponent: challenge.challenger_username.clone(),
+                your_color: color_preference.map(|color| color.opposite()),
+            },
+        );
+
+        Ok(challenge)
+    }
+
+    /// Pending challenges for a user, split into (incoming, outgoing)
+    pub async fn list(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<(Vec<ChallengeWithPlayers>, Vec<ChallengeWithPlayers>)> {
+        let pending = challenges::list_pending_for_user(pool, user_id).await?;
+
+        Ok(split_by_direction(pending, user_id))
+    }
+
+    /// Accept a challenge, starting the game it describes
+    pub async fn accept(
+        &self,
+        pool: &PgPool,
+        challenge_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<crate::models::Game> {
+        let challenge = find_challenge(pool, challenge_id).await?;
+        check_response(&challenge, user_id)?;
+
+        let preference = parse_color_preference(challenge.color_preference.as_deref())?;
+        let time_control = challenge
+            .time_control
+            .as_deref()
+            .map(str::parse::<TimeControl>)
+            .transpose()
+            .map_err(|e| AppError::Internal(anyhow::anyhow!(e)))?;
+
+        // Claim the challenge first so it can't be accepted twice. The claim,
+        // the game and the link between them are saved together.
+        let mut tx = pool.begin().await?;
+        if !challenges::respond(&mut *tx, challenge_id, "accepted").await? {
+            return Err(already_answered());
+        }
+
+        let (white_id, black_id) = assign_colors(
+            challenge.challenger_id,
+            challenge.challenged_id,
+            preference,
+            rand::thread_rng().gen_bool(0.5),
+        );
+        let options = GameOptions {
+            takebacks_allowed: challenge.takebacks_allowed,
+            time_control,
+            is_public: challenge.is_public,
+            starting_fen: challenge.starting_fen,
+        };
+
+        // The usual game checks (such as the active game cap) still apply;
+        // if they fail the challenge stays open
+        let game = self
+            .game_service
+            .create_game_in(&mut tx, white_id, black_id, options)
+            .await?;
+        challenges::set_game(&mut *tx, challenge_id, game.id).await?;
+        tx.commit().await?;
+
+        Ok(game)
+    }
+
+    /// Decline a challenge; no game is created
+    pub async fn decline(&self, pool: &PgPool, challenge_id: Uuid, user_id: Uuid) -> Result<()> {
+        let challenge = find_challenge(pool, challenge_id).await?;
+        check_response(&challenge, user_id)?;
+
+        if !challenges::respond(pool, challenge_id, "declined").await? {
+            return Err(already_answered());
+        }
+
+        Ok(())
+    }
+
+    /// Periodically expire challenges left unanswered for `expiry_hours`
+    pub async fn run_expiry_sweeper(
+        self,
+        pool: PgPool,
+        every: std::time::Duration,
+        expiry_hours: i64,
+        mut shutdown: Shutdown,
+    ) {
+        let mut ticker = tokio::time::interval(every);
+
+        loop {
+            // Only stop between runs, so a run in progress always completes
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.requested() => break,
+            }
+
+            match self.expire_stale(&pool, expiry_hours, Utc::now()).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Expired {} unanswered challenges", count),
+                Err(e) => tracing::error!("Challenge expiry sweep failed: {:?}", e),
+            }
+        }
+    }
+
+    /// Expire pending challenges older than `expiry_hours`, telling both
+    /// players if they're connected
+    ///
+    /// Returns how many challenges were expired.
+    pub async fn expire_stale(
+        &self,
+        pool: &PgPool,
+        expiry_hours: i64,
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        let candidates = challenges::find_expired(pool, expiry_hours).await?;
+        let mut expired = 0;
+
+        for challenge in candidates {
+            if !is_expired(&challenge, expiry_hours, now) {
+                continue;
+            }
+            if challenges::expire(pool, challenge.id).await? {
+                for user_id in [challenge.challenger_id, challenge.challenged_id] {
+                    self.events.publish_to_user(
+                        user_id,
+                        ServerMessage::ChallengeExpired {
+                            challenge_id: challenge.id,
+                        },
+                    );
+                }
+                expired += 1;
+            }
+        }
+
+        Ok(expired)
+    }
+}
+
+/// Whether a challenge has waited `expiry_hours` for an answer
+fn is_expired(challenge: &Challenge, expiry_hours: i64, now: DateTime<Utc>) -> bool {
+    challenge.status == "pending" && now - challenge.created_at >= Duration::hours(expiry_hours)
+}
+
+async fn find_challenge(pool: &PgPool, challenge_id: Uuid) -> Result<Challenge> {
+    challenges::find_by_id(pool, challenge_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Challenge not found".to_string()))
+}
+
+/// The challenger's requested color; None (or "random") leaves it to chance
+fn parse_color_preference(preference: Option<&str>) -> Result<Option<Color>> {
+    match preference {
+        None | Some("random") => Ok(None),
+        Some("white") => Ok(Some(Color::White)),
+        Some("black") => Ok(Some(Color::Black)),
+        Some(other) => Err(AppError::BadRequest(format!(
+            "Invalid color '{}': use white, black or random",
+            other
+        ))),
+    }
+}
+
+/// Only the challenged player may answer, and only while the challenge is pending
+fn check_response(challenge: &Challenge, user_id: Uuid) -> Result<()> {
+    if challenge.challenged_id != user_id {
+        return Err(AppError::Forbidden(
+            "Only the challenged player can answer this challenge".to_string(),
+        ));
+    }
+
+    if challenge.status != "pending" {
+        return Err(AppError::Conflict(format!(
+            "Challenge was already {}",
+            challenge.status
+        )));
+    }
+
+    Ok(())
+}
+
+/// A challenge answered by someone else between reading and answering it
+fn already_answered() -> AppError {
+    AppError::Conflict("Challenge has already been answered".to_string())
+}
+
+/// (white, black) for a challenge; `challenger_white` settles a random pick
+fn assign_colors(
+    challenger_id: Uuid,
+    challenged_id: Uuid,
+    preference: Option<Color>,
+    challenger_white: bool,
+) -> (Uuid, Uuid) {
+    let challenger_color = preference.unwrap_or(if challenger_white {
+        Color::White
+    } else {
+        Color::Black
+    });
+
+    match challenger_color {
+        Color::White => (challenger_id, challenged_id),
+        Color::Black => (challenged_id, challenger_id),
+    }
+}
+
+/// Split a user's challenges into those sent to them and those they sent
+fn split_by_direction(
+    challenges: Vec<ChallengeWithPlayers>,
+    user_id: Uuid,
+) -> (Vec<ChallengeWithPlayers>, Vec<ChallengeWithPlayers>) {
+    challenges
+        .into_iter()
+        .partition(|challenge| challenge.challenged_id == user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_challenge(status: &str) -> Challenge {
+        Challenge {
+            id: Uuid::new_v4(),
+            challenger_id: Uuid::new_v4(),
+            challenged_id: Uuid::new_v4(),
+            color_preference: None,
+            takebacks_allowed: None,
+            time_control: None,
+            is_public: false,
+            starting_fen: None,
+            status: status.to_string(),
+            game_id: None,
+            created_at: Utc::now(),
+            responded_at: None,
+        }
+    }
+
+    #[test]
+    fn test_only_challenged_player_can_answer() {
+        let challenge = test_challenge("pending");
+        assert!(check_response(&challenge, challenge.challenged_id).is_ok());
+        assert!(matches!(
+            check_response(&challenge, challenge.challenger_id),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            check_response(&challenge, Uuid::new_v4()),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_answered_challenge_cannot_be_answered_again() {
+        for status in ["accepted", "declined"] {
+            let challenge = test_challenge(status);
+            assert!(matches!(
+                check_response(&challenge, challenge.challenged_id),
+                Err(AppError::Conflict(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_only_stale_pending_challenges_expire() {
+        let now = Utc::now();
+        let mut challenge = test_challenge("pending");
+
+        challenge.created_at = now - Duration::hours(71);
+        assert!(!is_expired(&challenge, 72, now));
+
+        challenge.created_at = now - Duration::hours(72);
+        assert!(is_expired(&challenge, 72, now));
+
+        // An answered challenge is left alone however old it is
+        challenge.status = "accepted".to_string();
+        assert!(!is_expired(&challenge, 72, now));
+    }
+
+    #[test]
+    fn test_expired_challenge_cannot_be_accepted() {
+        let challenge = test_challenge("expired");
+        assert!(matches!(
+            check_response(&challenge, challenge.challenged_id),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_assign_colors() {
+        let (challenger, challenged) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let black = Some(Color::Black);
+        assert_eq!(
+            assign_colors(challenger, challenged, black, true),
+            (challenged, challenger)
+        );
+        let white = Some(Color::White);
+        assert_eq!(
+            assign_colors(challenger, challenged, white, false),
+            (challenger, challenged)
+        );
+        assert_eq!(
+            assign_colors(challenger, challenged, None, false),
+            (challenged, challenger)
+        );
+    }
+
+    #[test]
+    fn test_parse_color_preference() {
+        assert_eq!(parse_color_preference(None).unwrap(), None);
+        assert_eq!(parse_color_preference(Some("random")).unwrap(), None);
+        assert_eq!(
+            parse_color_preference(Some("black")).unwrap(),
+            Some(Color::Black)
+        );
+        assert!(parse_color_preference(Some("purple")).is_err());
+    }
+
+    #[test]
+    fn test_split_by_direction() {
+        let me = Uuid::new_v4();
+        let challenge = |challenger_id, challenged_id| ChallengeWithPlayers {
+            id: Uuid::new_v4(),
+            challenger_id,
+            challenged_id,
+            challenger_username: String::new(),
+            challenged_username: String::new(),
+            color_preference: None,
+            time_control: None,
+            status: "pending".to_string(),
+            game_id: None,
+            created_at: Utc::now(),
+        };
+
+        let (incoming, outgoing) = split_by_direction(
+            vec![
+                challenge(Uuid::new_v4(), me),
+                challenge(me, Uuid::new_v4()),
+                challenge(Uuid::new_v4(), me),
+            ],
+            me,
+        );
+        assert_eq!(incoming.len(), 2);
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].challenger_id, me);
+    }
+}
@@ -0,0 +1,162 @@
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::chess::GameState;
+use crate::db::moves;
+use crate::error::{AppError, Result};
+use crate::models::PositionContinuation;
+use crate::services::BoundedCache;
+use shared::protocol::{ExplorerMove, ExplorerResponse};
+
+/// How long explorer results are served from cache before being recomputed
+const CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// Most positions kept in the cache; any user can ask about any position
+const CACHE_CAPACITY: usize = 1000;
+
+#[derive(Clone)]
+pub struct ExplorerService {
+    cache: Arc<RwLock<BoundedCache<String, ExplorerResponse>>>,
+}
+
+impl Default for ExplorerService {
+    fn default() -> Self {
+        Self {
+            cache: Arc::new(RwLock::new(BoundedCache::new(
+                CACHE_CAPACITY,
+                Some(CACHE_TTL),
+            ))),
+        }
+    }
+}
+
+impl ExplorerService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the moves played from a position across finished games
+    pub async fn explore(&self, pool: &PgPool, fen: &str) -> Result<ExplorerResponse> {
+        let game_state = GameState::from_fen(fen)
+            .map_err(|_| AppError::Validation("Invalid FEN".to_string()))?;
+        let key = game_state.position_key();
+
+        if let Ok(cache) = self.cache.read() {
+            if let Some(response) = cache.get(&key, Instant::now()) {
+                return Ok(response);
+            }
+        }
+
+        let continuations = moves::list_continuations(pool, &key).await?;
+        let moves = summarize_continuations(&continuations);
+
+        let response = ExplorerResponse {
+            fen: game_state.fen().to_string(),
+            total_games: moves.iter().map(|m| m.games).sum(),
+            moves,
+        };
+
+        if let Ok(mut cache) = self.cache.write() {
+            cache.insert(key, response.clone(), Instant::now());
+        }
+
+        Ok(response)
+    }
+}
+
+/// Group continuations by move and compute result percentages, most played first
+///
+/// Only public games played on the server count; the query already leaves
+/// out the rest, and they're skipped here too so private games can't leak.
+fn summarize_continuations(continuations: &[PositionContinuation]) -> Vec<ExplorerMove> {
+    let mut grouped: HashMap<&str, (&str, u32, u32, u32, u32)> = HashMap::new();
+
+    for c in continuations.iter().filter(|c| c.is_public && !c.imported) {
+        let entry = grouped
+            .entry(c.move_uci.as_str())
+            .or_insert((c.move_san.as_str(), 0, 0, 0, 0));
+        entry.1 += 1;
+        match c.game_status.as_str() {
+            "white_won" => entry.2 += 1,
+            "draw" => entry.3 += 1,
+            "black_won" => entry.4 += 1,
+            _ => {}
+        }
+    }
+
+    let mut moves: Vec<ExplorerMove> = grouped
+        .into_iter()
+        .map(|(uci, (san, games, white_wins, draws, black_wins))| {
+            let pct = |n: u32| n as f64 * 100.0 / games as f64;
+            ExplorerMove {
+                move_uci: uci.to_string(),
+                move_san: san.to_string(),
+                games,
+                white_win_pct: pct(white_wins),
+                draw_pct: pct(draws),
+                black_win_pct: pct(black_wins),
+            }
+        })
+        .collect();
+
+    moves.sort_by(|a, b| b.games.cmp(&a.games).then_with(|| a.move_uci.cmp(&b.move_uci)));
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn continuation(uci: &str, san: &str, status: &str) -> PositionContinuation {
+        PositionContinuation {
+            move_uci: uci.to_string(),
+            move_san: san.to_string(),
+            game_status: status.to_string(),
+            is_public: true,
+            imported: false,
+        }
+    }
+
+    #[test]
+    fn test_summarize_start_position() {
+        let continuations = vec![
+            continuation("e2e4", "e4", "white_won"),
+            continuation("d2d4", "d4", "black_won"),
+            continuation("e2e4", "e4", "draw"),
+            continuation("e2e4", "e4", "white_won"),
+        ];
+
+        let moves = summarize_continuations(&continuations);
+        assert_eq!(moves.len(), 2);
+
+        assert_eq!(moves[0].move_san, "e4");
+        assert_eq!(moves[0].games, 3);
+        assert!((moves[0].white_win_pct - 200.0 / 3.0).abs() < 1e-9);
+        assert!((moves[0].draw_pct - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(moves[0].black_win_pct, 0.0);
+
+        assert_eq!(moves[1].move_san, "d4");
+        assert_eq!(moves[1].games, 1);
+        assert_eq!(moves[1].black_win_pct, 100.0);
+    }
+
+    #[test]
+    fn test_private_and_imported_games_are_not_counted() {
+        let private = PositionContinuation {
+            is_public: false,
+            ..continuation("d2d4", "d4", "white_won")
+        };
+        let imported = PositionContinuation {
+            imported: true,
+            ..continuation("c2c4", "c4", "draw")
+        };
+        let continuations = vec![continuation("e2e4", "e4", "black_won"), private, imported];
+
+        let moves = summarize_continuations(&continuations);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].move_san, "e4");
+        assert_eq!(moves[0].games, 1);
+    }
+}
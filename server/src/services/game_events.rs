@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use shared::protocol::ServerMessage;
+
+/// Messages buffered per channel before slow subscribers start missing events
+const CHANNEL_CAPACITY: usize = 32;
+
+type Channels = Arc<RwLock<HashMap<Uuid, broadcast::Sender<ServerMessage>>>>;
+
+type ConnectionCounts = Arc<Mutex<HashMap<Uuid, usize>>>;
+
+/// Broadcast channels for pushing events to WebSocket subscribers
+///
+/// Game channels carry events for everyone following a game; user channels
+/// carry events meant for one player only, such as deadline warnings.
+/// Open WebSocket connections are counted per user as well.
+#[derive(Clone, Default)]
+pub struct GameEvents {
+    games: Channels,
+    users: Channels,
+    connections: ConnectionCounts,
+}
+
+impl GameEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start receiving events for a game
+    pub fn subscribe(&self, game_id: Uuid) -> broadcast::Receiver<ServerMessage> {
+        subscribe(&self.games, game_id)
+    }
+
+    /// Send an event to everyone subscribed to a game
+    pub fn publish(&self, game_id: Uuid, message: ServerMessage) {
+        publish(&self.games, game_id, message);
+    }
+
+    /// Start receiving events addressed to a user
+    pub fn subscribe_user(&self, user_id: Uuid) -> broadcast::Receiver<ServerMessage> {
+        subscribe(&self.users, user_id)
+    }
+
+    /// Send an event to every connection of one user
+    pub fn publish_to_user(&self, user_id: Uuid, message: ServerMessage) {
+        publish(&self.users, user_id, message);
+    }
+
+    /// Count a new connection for a user, unless they already have `cap`
+    /// open; the connection is counted until the guard is dropped
+    pub fn connect(&self, user_id: Uuid, cap: usize) -> Option<ConnectionGuard> {
+        let mut connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        let open = connections.entry(user_id).or_insert(0);
+        if *open >= cap {
+            if *open == 0 {
+                connections.remove(&user_id);
+            }
+            return None;
+        }
+
+        *open += 1;
+        Some(ConnectionGuard {
+            connections: self.connections.clone(),
+            user_id,
+        })
+    }
+}
+
+/// One open WebSocket connection, released when dropped
+pub struct ConnectionGuard {
+    connections: ConnectionCounts,
+    user_id: Uuid,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(open) = connections.get_mut(&self.user_id) {
+            *open -= 1;
+            if *open == 0 {
+                connections.remove(&self.user_id);
+            }
+        }
+    }
+}
+
+fn subscribe(channels: &Channels, key: Uuid) -> broadcast::Receiver<ServerMessage> {
+    let mut channels = channels.write().unwrap_or_else(|e| e.into_inner());
+    channels
+        .entry(key)
+        .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+        .subscribe()
+}
+
+/// Channels nobody is listening to any more are dropped here, so a channel
+/// lives only as long as it has subscribers.
+fn publish(channels: &Channels, key: Uuid, message: ServerMessage) {
+    let mut channels = channels.write().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(sender) = channels.get(&key) {
+        if sender.send(message).is_err() {
+            channels.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::time::Duration;
+
+    fn move_made(game_id: Uuid) -> ServerMessage {
+        ServerMessage::MoveMade {
+            game_id,
+            move_san: "e4".to_string(),
+            move_uci: "e2e4".to_string(),
+            position_fen: "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".to_string(),
+            deadline: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_receives_published_move() {
+        let events = GameEvents::new();
+        let game_id = Uuid::new_v4();
+        let mut rx = events.subscribe(game_id);
+
+        events.publish(game_id, move_made(game_id));
+
+        match rx.recv().await.unwrap() {
+            ServerMessage::MoveMade {
+                game_id: id,
+                move_uci,
+                ..
+            } => {
+                assert_eq!(id, game_id);
+                assert_eq!(move_uci, "e2e4");
+            }
+            other => panic!("expected MoveMade, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_are_scoped_to_their_game() {
+        let events = GameEvents::new();
+        let watched = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let mut rx = events.subscribe(watched);
+
+        events.publish(other, move_made(other));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_user_events_only_reach_that_user() {
+        let events = GameEvents::new();
+        let game_id = Uuid::new_v4();
+        let player = Uuid::new_v4();
+        let mut player_rx = events.subscribe_user(player);
+        let mut other_rx = events.subscribe_user(Uuid::new_v4());
+        let mut game_rx = events.subscribe(game_id);
+
+        events.publish_to_user(
+            player,
+            ServerMessage::DeadlineWarning {
+                game_id,
+                time_remaining: Duration::from_secs(3600),
+            },
+        );
+
+        assert!(matches!(
+            player_rx.try_recv().unwrap(),
+            ServerMessage::DeadlineWarning { .. }
+        ));
+        assert!(other_rx.try_recv().is_err());
+        assert!(game_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_channel_dropped_when_last_subscriber_leaves() {
+        let events = GameEvents::new();
+        let game_id = Uuid::new_v4();
+        drop(events.subscribe(game_id));
+
+        events.publish(game_id, move_made(game_id));
+        assert!(events.games.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_connections_beyond_cap_are_refused() {
+        let events = GameEvents::new();
+        let user = Uuid::new_v4();
+
+        let mut open: Vec<_> = (0..3).map(|_| events.connect(user, 3).unwrap()).collect();
+        assert!(events.connect(user, 3).is_none());
+
+        // Other users have their own allowance
+        assert!(events.connect(Uuid::new_v4(), 3).is_some());
+
+        // Closing a connection frees its slot
+        open.pop();
+        assert!(events.connect(user, 3).is_some());
+
+        drop(open);
+        assert!(events.connections.lock().unwrap().is_empty());
+    }
+}
@@ -1,24 +1,179 @@
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde_json::json;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use uuid::Uuid;
 
-use crate::chess::{check_game_result, validate_move, GameResult, GameState};
-use crate::db::{games, moves, users};
+use crate::chess::{
+    check_game_result_with_history, claimable_draw, describe_mismatch, find_position_mismatches,
+    identify_opening, material_eval, normalize_fen, parse_pgn, unreadable_start, validate_move,
+    GameResult, GameState,
+};
+use crate::db::{conditional_moves, games, idempotency, moves, users};
 use crate::error::{AppError, Result};
-use crate::models::{NewGame, NewMove};
-use shared::types::{Color, GameStatus};
+use crate::models::{ConditionalMove, NewGame, NewMove};
+use crate::services::{BoundedCache, GameEvents};
+use crate::shutdown::Shutdown;
+use shared::protocol::{
+    GameAuditResponse, GameVerificationResponse, MoveResponse, PositionMismatch, ReviewResponse,
+    ServerMessage, StandingsResponse,
+};
+use shared::types::{Color, GameStatus, Move, TimeControl};
+
+/// Most games whose material eval is kept in memory
+const EVAL_CACHE_CAPACITY: usize = 10_000;
+
+/// Restrictions on which users may start new games
+///
+/// A user qualifies by meeting either threshold; with neither set, anyone can.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CreationPolicy {
+    pub min_account_age_hours: Option<i64>,
+    pub min_completed_games: Option<i64>,
+}
+
+impl CreationPolicy {
+    fn is_satisfied(&self, account_age: Duration, completed_games: i64) -> bool {
+        let old_enough = self
+            .min_account_age_hours
+            .map(|hours| account_age >= Duration::hours(hours));
+        let experienced_enough = self
+            .min_completed_games
+            .map(|games| completed_games >= games);
+
+        match (old_enough, experienced_enough) {
+            (None, None) => true,
+            (Some(ok), None) | (None, Some(ok)) => ok,
+            (Some(a), Some(b)) => a || b,
+        }
+    }
+
+    fn requirement_message(&self) -> String {
+        let mut requirements = Vec::new();
+        if let Some(hours) = self.min_account_age_hours {
+            requirements.push(format!("be at least {} hours old", hours));
+        }
+        if let Some(games) = self.min_completed_games {
+            requirements.push(format!("have completed {} games", games));
+        }
+        format!(
+            "Your account must {} before you can create games",
+            requirements.join(" or ")
+        )
+    }
+}
+
+/// Per-game settings chosen by the player creating the game
+#[derive(Debug, Clone, Default)]
+pub struct GameOptions {
+    pub takebacks_allowed: Option<bool>,
+    pub time_control: Option<TimeControl>,
+    /// Let anyone watch through the public endpoint
+    pub is_public: bool,
+    /// FEN to start from instead of the standard position
+    pub starting_fen: Option<String>,
+}
 
 #[derive(Clone)]
 pub struct GameService {
     move_deadline_hours: i64,
+    creation_policy: CreationPolicy,
+    takebacks_allowed_default: bool,
+    max_active_games_per_user: Option<i64>,
+    allow_duplicate_active_games: bool,
+    events: GameEvents,
+    /// Material eval per game, with the position it was computed for
+    eval_cache: Arc<RwLock<BoundedCache<Uuid, (String, i32)>>>,
 }
 
 impl GameService {
-    pub fn new(move_deadline_hours: i64) -> Self {
+    pub fn new(
+        move_deadline_hours: i64,
+        creation_policy: CreationPolicy,
+        takebacks_allowed_default: bool,
+        max_active_games_per_user: Option<i64>,
+        allow_duplicate_active_games: bool,
+        events: GameEvents,
+    ) -> Self {
         Self {
             move_deadline_hours,
+            creation_policy,
+            takebacks_allowed_default,
+            max_active_games_per_user,
+            allow_duplicate_active_games,
+            events,
+            eval_cache: Arc::new(RwLock::new(BoundedCache::new(EVAL_CACHE_CAPACITY, None))),
+        }
+    }
+
+    /// The most active games a user may have at once, if there's a limit
+    pub fn max_active_games_per_user(&self) -> Option<i64> {
+        self.max_active_games_per_user
+    }
+
+    /// Import a finished game from PGN as an analysis record owned by `user_id`
+    ///
+    /// The importer is recorded as both players; the PGN's own player names
+    /// stay in the PGN. Games without a decisive or drawn result are stored
+    /// as abandoned so they can never be played on.
+    pub async fn import_pgn(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        pgn: &str,
+    ) -> Result<crate::models::Game> {
+        let parsed = parse_pgn(pgn).map_err(|e| AppError::Validation(e.to_string()))?;
+
+        let final_fen = parsed
+            .moves
+            .last()
+            .map(|m| m.fen_after.clone())
+            .unwrap_or_else(|| parsed.start.fen().to_string());
+        let final_state = GameState::from_fen(&final_fen)?;
+
+        let new_game = NewGame {
+            white_player_id: user_id,
+            black_player_id: user_id,
+            current_position: final_fen.clone(),
+            game_state: json!({ "fen": final_fen }),
+            status: status_from_pgn_result(&parsed.result).to_string(),
+            current_turn: final_state.current_turn()?.to_string(),
+            takebacks_allowed: false,
+            move_deadline_hours: self.move_deadline_hours,
+            is_public: false,
+        };
+        let ended_on_board = final_state.is_game_over()?;
+
+        // The game and its moves are saved together or not at all
+        let mut tx = pool.begin().await?;
+        let game = games::create_imported_game(&mut *tx, &new_game).await?;
+        for new_move in imported_moves(game.id, &parsed, ended_on_board)? {
+            moves::create_move(&mut *tx, &new_move).await?;
+        }
+        tx.commit().await?;
+
+        Ok(game)
+    }
+
+    /// Check that a user is allowed to start new games under the creation policy
+    pub async fn ensure_can_create_games(&self, pool: &PgPool, user_id: Uuid) -> Result<()> {
+        let policy = self.creation_policy;
+        if policy.min_account_age_hours.is_none() && policy.min_completed_games.is_none() {
+            return Ok(());
+        }
+
+        let user = users::find_by_id(pool, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        let completed_games = games::count_completed_by_user(pool, user_id).await?;
+
+        if !policy.is_satisfied(Utc::now() - user.created_at, completed_games) {
+            return Err(AppError::Forbidden(policy.requirement_message()));
         }
+
+        Ok(())
     }
 
     /// Create a new game between two players
@@ -27,13 +182,35 @@ impl GameService {
         pool: &PgPool,
         white_player_id: Uuid,
         black_player_id: Uuid,
+        options: GameOptions,
+    ) -> Result<crate::models::Game> {
+        let mut tx = pool.begin().await?;
+        let game = self
+            .create_game_in(&mut tx, white_player_id, black_player_id, options)
+            .await?;
+        tx.commit().await?;
+
+        Ok(game)
+    }
+
+    /// Create a new game as part of the caller's transaction
+    ///
+    /// Both players' rows stay locked until the transaction ends, so the
+    /// active game cap and the duplicate game rule hold against concurrent
+    /// creations too.
+    pub async fn create_game_in(
+        &self,
+        conn: &mut PgConnection,
+        white_player_id: Uuid,
+        black_player_id: Uuid,
+        options: GameOptions,
     ) -> Result<crate::models::Game> {
         // Verify both players exist
-        users::find_by_id(pool, white_player_id)
+        users::find_by_id(&mut *conn, white_player_id)
             .await?
             .ok_or_else(|| AppError::NotFound("White player not found".to_string()))?;
 
-        users::find_by_id(pool, black_player_id)
+        users::find_by_id(&mut *conn, black_player_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Black player not found".to_string()))?;
 
@@ -43,8 +220,34 @@ impl GameService {
             ));
         }
 
+        users::lock_for_update(&mut *conn, &[white_player_id, black_player_id]).await?;
+
+        // Enforce the active game cap for both players
+        if let Some(cap) = self.max_active_games_per_user {
+            for player_id in [white_player_id, black_player_id] {
+                let active = games::count_active_by_user(&mut *conn, player_id).await?;
+                if !has_room_for_game(active, cap) {
+                    return Err(AppError::BadRequest(format!(
+                        "A player has reached the limit of {} active games",
+                        cap
+                    )));
+                }
+            }
+        }
+
+        // Some clubs allow only one active game between the same two players
+        if !self.allow_duplicate_active_games {
+            let exists =
+                games::active_game_exists_between(&mut *conn, white_player_id, black_player_id)
+                    .await?;
+            ensure_no_duplicate_game(self.allow_duplicate_active_games, exists)?;
+        }
+
         // Create initial game state
-        let game_state = GameState::new();
+        let game_state = match options.starting_fen.as_deref() {
+            Some(fen) => starting_position(fen)?,
+            None => GameState::new(),
+        };
         let fen = game_state.fen().to_string();
 
         let new_game = NewGame {
@@ -53,31 +256,38 @@ impl GameService {
             current_position: fen,
             game_state: json!({ "fen": game_state.fen() }),
             status: "active".to_string(),
-            current_turn: "white".to_string(),
+            current_turn: game_state.current_turn()?.to_string(),
+            takebacks_allowed: options
+                .takebacks_allowed
+                .unwrap_or(self.takebacks_allowed_default),
+            move_deadline_hours: options
+                .time_control
+                .map_or(self.move_deadline_hours, |tc| tc.hours()),
+            is_public: options.is_public,
         };
 
-        let game = games::create_game(pool, &new_game).await?;
+        let game = games::create_game(&mut *conn, &new_game).await?;
 
         Ok(game)
     }
 
-    /// Submit a move for a game
-    pub async fn submit_move(
+    /// Validate a move against the current game and work out its consequences
+    async fn plan_move(
         &self,
         pool: &PgPool,
         game_id: Uuid,
         user_id: Uuid,
         move_uci: String,
-    ) -> Result<(crate::models::MoveRecord, crate::models::Game)> {
+        comment: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<(crate::models::Game, PlannedMove)> {
         // Get the game
         let game = games::find_by_id(pool, game_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
 
-        // Check game is active
-        if game.status != "active" {
-            return Err(AppError::BadRequest("Game is not active".to_string()));
-        }
+        // Check game is active before touching its position
+        ensure_active(&game)?;
 
         // Determine which player is making the move
         let player_color = if user_id == game.white_player_id {
@@ -85,7 +295,7 @@ impl GameService {
         } else if user_id == game.black_player_id {
             Color::Black
         } else {
-            return Err(AppError::BadRequest(
+            return Err(AppError::Forbidden(
                 "You are not a player in this game".to_string(),
             ));
         };
@@ -98,142 +308,2974 @@ impl GameService {
         };
 
         if player_color != current_turn {
-            return Err(AppError::BadRequest("It's not your turn".to_string()));
+            return Err(AppError::NotYourTurn {
+                current_turn,
+                deadline: game.move_deadline,
+            });
         }
 
-        // Load game state and validate move
-        let game_state = GameState::from_fen(&game.current_position)?;
-        validate_move(&game_state, &move_uci)?;
+        let comment = normalize_comment(comment)?;
 
-        // Make the move
-        let (new_state, san) = game_state.make_move(&move_uci)?;
+        // Existing moves give the move number and the positions seen so far
+        let previous_moves = moves::list_by_game(pool, game_id).await?;
+        let planned = plan_after(&game, previous_moves, player_color, move_uci, comment, now)?;
 
-        // Count existing moves to determine move number
-        let move_count = moves::count_by_game(pool, game_id).await?;
-        let move_number = (move_count / 2) + 1;
+        Ok((game, planned))
+    }
 
-        // Create move record
-        let new_move = NewMove {
-            game_id,
-            move_number: move_number as i32,
-            player_color: player_color.to_string(),
-            move_uci,
-            move_san: san,
-            position_before: game.current_position.clone(),
-            position_after: new_state.fen().to_string(),
-        };
-
-        let move_record = moves::create_move(pool, &new_move).await?;
-
-        // Check for game over
-        let game_result = check_game_result(&new_state)?;
-        let new_status = if let Some(result) = game_result {
-            match result {
-                GameResult::Checkmate => {
-                    // Current player (who just moved) wins
-                    match player_color {
-                        Color::White => "white_won",
-                        Color::Black => "black_won",
-                    }
-                }
-                GameResult::Stalemate => "draw",
-            }
-        } else {
-            "active"
-        };
+    /// Translate a SAN move into UCI against the game's current position
+    pub async fn resolve_san(&self, pool: &PgPool, game_id: Uuid, san: &str) -> Result<String> {
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
 
-        // Update game state
-        let next_turn = player_color.opposite().to_string();
-        let deadline = Utc::now() + Duration::hours(self.move_deadline_hours);
+        let game_state = current_position(&game)?;
+        game_state
+            .san_to_uci(san)
+            .map_err(|e| AppError::IllegalMove(e.to_string()))
+    }
 
-        games::update_after_move(
-            pool,
-            game_id,
-            new_state.fen(),
-            &json!({ "fen": new_state.fen() }),
-            &next_turn,
-            deadline,
-        )
-        .await?;
+    /// Run a move through the full validation pipeline without persisting it
+    ///
+    /// Returns the move and game as they would look after the move. The
+    /// move has a nil id and the game's deadline is left untouched.
+    pub async fn preview_move(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+        move_uci: String,
+        comment: Option<String>,
+    ) -> Result<(crate::models::MoveRecord, crate::models::Game)> {
+        let now = Utc::now();
+        let (game, planned) = self
+            .plan_move(pool, game_id, user_id, move_uci, comment, now)
+            .await?;
+
+        Ok(preview_result(game, &planned, now))
+    }
+
+    /// Submit a move for a game
+    ///
+    /// Replies the players entered in advance for the positions that follow
+    /// are played straight after it; the returned game includes them. With
+    /// an idempotency key, the response is saved along with the move.
+    pub async fn submit_move(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+        move_uci: String,
+        comment: Option<String>,
+        idempotency_key: Option<&str>,
+    ) -> Result<(crate::models::MoveRecord, crate::models::Game)> {
+        let (move_record, game) = self
+            .apply_move(pool, game_id, user_id, move_uci, comment, idempotency_key)
+            .await?;
+        let game = self.play_conditional_moves(pool, game).await;
+
+        Ok((move_record, game))
+    }
+
+    /// Validate, save and announce a single move
+    async fn apply_move(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+        move_uci: String,
+        comment: Option<String>,
+        idempotency_key: Option<&str>,
+    ) -> Result<(crate::models::MoveRecord, crate::models::Game)> {
+        let now = Utc::now();
+        let (game, planned) = self
+            .plan_move(pool, game_id, user_id, move_uci, comment, now)
+            .await?;
+
+        let mut tx = pool.begin().await?;
+        let saved = save_move(&mut tx, &game, planned, now).await?;
 
-        // If game is over, update status
-        if new_status != "active" {
-            games::update_status(pool, game_id, new_status).await?;
+        // A retry with the same key finds this response once the move is in
+        if let Some(key) = idempotency_key {
+            let game = games::find_by_id(&mut *tx, game_id)
+                .await?
+                .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Game disappeared")))?;
+            let response = move_response(saved.move_record.clone(), game, user_id)?;
+            idempotency::save_move_response(
+                &mut tx,
+                key,
+                game_id,
+                user_id,
+                saved.move_record.id,
+                &serde_json::to_value(&response).map_err(anyhow::Error::from)?,
+            )
+            .await?;
         }
 
+        tx.commit().await?;
+        self.announce_move(game_id, &saved);
+
         // Fetch updated game
         let updated_game = games::find_by_id(pool, game_id)
             .await?
             .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Game disappeared")))?;
 
-        Ok((move_record, updated_game))
+        Ok((saved.move_record, updated_game))
     }
 
-    /// Generate PGN for a game
-    pub async fn generate_pgn(
+    /// Tell subscribers about a saved move, and the result if it ended the game
+    fn announce_move(&self, game_id: Uuid, saved: &SavedMove) {
+        self.events.publish(
+            game_id,
+            ServerMessage::MoveMade {
+                game_id,
+                move_san: saved.move_record.move_san.clone(),
+                move_uci: saved.move_record.move_uci.clone(),
+                position_fen: saved.move_record.position_after.clone(),
+                deadline: saved.deadline,
+            },
+        );
+
+        if let Some(result) = saved.result {
+            self.notify_status_change(game_id, saved.new_status, result.reason());
+        }
+    }
+
+    /// Play the conditional moves that answer the game's position, in turn
+    ///
+    /// The move that led here is already saved, so a planned move that can't
+    /// be played is logged and dropped rather than reported to its sender.
+    async fn play_conditional_moves(
+        &self,
+        pool: &PgPool,
+        mut game: crate::models::Game,
+    ) -> crate::models::Game {
+        let planned = match conditional_moves::list_by_game(pool, game.id).await {
+            Ok(planned) => planned,
+            Err(e) => {
+                tracing::error!("Loading conditional moves failed: {:?}", e);
+                return game;
+            }
+        };
+        let Ok(state) = current_position(&game) else {
+            return game;
+        };
+
+        for reply in conditional_chain(&state, &planned) {
+            if let Err(e) = conditional_moves::delete(pool, reply.id).await {
+                tracing::error!("Removing conditional move {} failed: {:?}", reply.id, e);
+                break;
+            }
+
+            let played = self
+                .apply_move(
+                    pool,
+                    game.id,
+                    reply.user_id,
+                    reply.move_uci.clone(),
+                    None,
+                    None,
+                )
+                .await;
+            match played {
+                Ok((_, next)) => game = next,
+                Err(e) => {
+                    tracing::warn!(
+                        "Conditional move {} in game {} not played: {}",
+                        reply.move_uci,
+                        game.id,
+                        e
+                    );
+                    break;
+                }
+            }
+
+            // A repetition can end the game before the chain runs out
+            if game.status != "active" {
+                break;
+            }
+        }
+
+        game
+    }
+
+    /// Plan a reply to a position the game may reach, to be played automatically
+    ///
+    /// The player must be to move in the position and the move legal there.
+    /// A new plan for a position replaces the old one. Returns the player's
+    /// plans for the game.
+    pub async fn set_conditional_move(
         &self,
         pool: &PgPool,
         game_id: Uuid,
-    ) -> Result<String> {
-        // Get game
+        user_id: Uuid,
+        position_fen: &str,
+        move_uci: &str,
+    ) -> Result<Vec<ConditionalMove>> {
         let game = games::find_by_id(pool, game_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
 
-        // Get players
-        let white_player = users::find_by_id(pool, game.white_player_id)
+        let color = player_color(&game, user_id)?;
+        ensure_active(&game)?;
+
+        let state = GameState::from_fen(position_fen)
+            .map_err(|e| AppError::Validation(format!("Invalid position: {}", e)))?;
+        if state.current_turn()? != color {
+            return Err(AppError::Validation(
+                "Conditional moves are for positions where it's your move".to_string(),
+            ));
+        }
+        validate_move(&state, move_uci).map_err(|e| AppError::IllegalMove(e.to_string()))?;
+
+        let position_fen = normalize_fen(state.fen());
+        conditional_moves::upsert(pool, game_id, user_id, &position_fen, move_uci).await?;
+
+        Ok(conditional_moves::list_for_player(pool, game_id, user_id).await?)
+    }
+
+    /// A player's conditional moves in a game
+    pub async fn conditional_moves(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<ConditionalMove>> {
+        let game = games::find_by_id(pool, game_id)
             .await?
-            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("White player not found")))?;
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+        player_color(&game, user_id)?;
 
-        let black_player = users::find_by_id(pool, game.black_player_id)
+        Ok(conditional_moves::list_for_player(pool, game_id, user_id).await?)
+    }
+
+    /// Drop all of a player's conditional moves in a game
+    pub async fn clear_conditional_moves(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<()> {
+        let game = games::find_by_id(pool, game_id)
             .await?
-            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Black player not found")))?;
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+        player_color(&game, user_id)?;
 
-        // Get all moves
-        let all_moves = moves::list_by_game(pool, game_id).await?;
+        conditional_moves::clear_for_player(pool, game_id, user_id).await?;
+        Ok(())
+    }
+
+    /// Tell a game's subscribers that it has left the `active` state
+    ///
+    /// `status` is the stored status string; `reason` is shown to players
+    /// (e.g. "checkmate", "resignation", "timeout").
+    pub fn notify_status_change(&self, game_id: Uuid, status: &str, reason: &str) {
+        let status: GameStatus = match status.parse() {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::error!("Not broadcasting status for game {}: {}", game_id, e);
+                return;
+            }
+        };
+
+        self.events.publish(
+            game_id,
+            ServerMessage::GameStatusChanged {
+                game_id,
+                status,
+                winner: status.winner(),
+                reason: reason.to_string(),
+            },
+        );
+    }
+
+    /// Periodically adjudicate games whose move deadline has passed
+    pub async fn run_deadline_sweeper(
+        self,
+        pool: PgPool,
+        every: std::time::Duration,
+        abandon_after_hours: i64,
+        mut shutdown: Shutdown,
+    ) {
+        let mut ticker = tokio::time::interval(every);
+
+        loop {
+            // Only stop between runs, so a run in progress always completes
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.requested() => break,
+            }
+
+            match self.sweep_expired_deadlines(&pool).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Adjudicated {} games on time", count),
+                Err(e) => tracing::error!("Deadline sweep failed: {:?}", e),
+            }
+
+            match self
+                .abandon_stale_games(&pool, abandon_after_hours, Utc::now())
+                .await
+            {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Abandoned {} games with no moves", count),
+                Err(e) => tracing::error!("Abandonment sweep failed: {:?}", e),
+            }
+        }
+    }
+
+    /// Award every game past its deadline to the player not on move
+    ///
+    /// Returns how many games were ended by this sweep.
+    pub async fn sweep_expired_deadlines(&self, pool: &PgPool) -> Result<usize> {
+        let expired = games::find_expired_deadlines(pool).await?;
+        let now = Utc::now();
+        let mut adjudicated = 0;
+
+        for game in expired {
+            let Some(new_status) = timed_out_status(&game, now) else {
+                continue;
+            };
+            if games::complete_expired(pool, game.id, new_status).await? {
+                self.notify_status_change(game.id, new_status, "timeout");
+                adjudicated += 1;
+            }
+        }
+
+        Ok(adjudicated)
+    }
+
+    /// Mark games nobody has moved in for `abandon_after_hours` as abandoned
+    ///
+    /// Games with a move have a deadline and go through the timeout path
+    /// instead. Abandoned games have no winner and don't count as completed.
+    /// Returns how many games were abandoned.
+    pub async fn abandon_stale_games(
+        &self,
+        pool: &PgPool,
+        abandon_after_hours: i64,
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        let candidates = games::find_abandonable(pool, abandon_after_hours).await?;
+        let mut abandoned = 0;
+
+        for game in candidates {
+            if !is_abandoned(&game, abandon_after_hours, now) {
+                continue;
+            }
+            if games::abandon_unstarted(pool, game.id).await? {
+                self.notify_status_change(game.id, "abandoned", "no moves");
+                abandoned += 1;
+            }
+        }
+
+        Ok(abandoned)
+    }
+
+    /// Periodically warn the player on move when their deadline is near
+    ///
+    /// Each deadline is warned about once; a move sets a new deadline, which
+    /// can be warned about again.
+    pub async fn run_deadline_warnings(
+        self,
+        pool: PgPool,
+        every: std::time::Duration,
+        warning_hours: i32,
+        mut shutdown: Shutdown,
+    ) {
+        let mut ticker = tokio::time::interval(every);
+        let mut warned = WarnedDeadlines::default();
+
+        loop {
+            // Only stop between runs, so a run in progress always completes
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.requested() => break,
+            }
+
+            let result = self
+                .send_deadline_warnings(&pool, warning_hours, &mut warned)
+                .await;
+            if let Err(e) = result {
+                tracing::error!("Deadline warning pass failed: {:?}", e);
+            }
+        }
+    }
+
+    async fn send_deadline_warnings(
+        &self,
+        pool: &PgPool,
+        warning_hours: i32,
+        warned: &mut WarnedDeadlines,
+    ) -> Result<()> {
+        let now = Utc::now();
+        warned.forget_passed(now);
 
-        // Build PGN
-        let mut pgn = String::new();
-
-        // PGN headers
-        pgn.push_str(&format!("[Event \"Rusty Chess Correspondence Game\"]\n"));
-        pgn.push_str(&format!("[Site \"Rusty Chess\"]\n"));
-        pgn.push_str(&format!(
-            "[Date \"{}\"]\n",
-            game.created_at.format("%Y.%m.%d")
-        ));
-        pgn.push_str(&format!("[White \"{}\"]\n", white_player.username));
-        pgn.push_str(&format!("[Black \"{}\"]\n", black_player.username));
-        pgn.push_str(&format!("[Result \"{}\"]\n", game_status_to_pgn(&game.status)));
-        pgn.push_str("\n");
-
-        // Moves
-        let mut move_text = String::new();
-        for (i, mv) in all_moves.iter().enumerate() {
-            if i % 2 == 0 {
-                // White's move
-                let move_num = (i / 2) + 1;
-                move_text.push_str(&format!("{}. {} ", move_num, mv.move_san));
+        for game in games::find_approaching_deadlines(pool, warning_hours).await? {
+            let Some(deadline) = game.move_deadline else {
+                continue;
+            };
+            if !warned.first_warning(game.id, deadline) {
+                continue;
+            }
+
+            let player_on_move = if game.current_turn == "white" {
+                game.white_player_id
             } else {
-                // Black's move
-                move_text.push_str(&format!("{} ", mv.move_san));
+                game.black_player_id
+            };
+
+            self.events.publish_to_user(
+                player_on_move,
+                ServerMessage::DeadlineWarning {
+                    game_id: game.id,
+                    time_remaining: time_remaining(deadline, now),
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Resign an active game on behalf of one of its players
+    pub async fn resign(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<crate::models::Game> {
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+        let player_color = if user_id == game.white_player_id {
+            Color::White
+        } else if user_id == game.black_player_id {
+            Color::Black
+        } else {
+            return Err(AppError::Forbidden(
+                "You are not a player in this game".to_string(),
+            ));
+        };
+
+        ensure_active(&game)?;
+
+        let new_status = status_after_resignation(player_color);
+        // A mate or the deadline sweep may have ended the game since it was read
+        if !games::update_status(pool, game_id, new_status, None, Some("resignation")).await? {
+            return Err(already_finished(pool, game_id).await);
+        }
+        self.notify_status_change(game_id, new_status, "resignation");
+
+        let updated_game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Game disappeared")))?;
+
+        Ok(updated_game)
+    }
+
+    /// Claim a draw by threefold repetition or the fifty-move rule
+    ///
+    /// Only the player to move may claim, either in the current position or
+    /// in the one their intended `move_uci` would reach; in that case the move
+    /// is played and the draw recorded together. A claim that doesn't hold is
+    /// rejected and nothing is played.
+    pub async fn claim_draw(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+        move_uci: Option<String>,
+    ) -> Result<crate::models::Game> {
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+        ensure_active(&game)?;
+        let player_color = player_color(&game, user_id)?;
+        let current_turn = current_position(&game)?.current_turn()?;
+        if player_color != current_turn {
+            return Err(AppError::NotYourTurn {
+                current_turn,
+                deadline: game.move_deadline,
+            });
+        }
+
+        let previous_moves = moves::list_by_game(pool, game_id).await?;
+        let claim = check_draw_claim(&game, &previous_moves, move_uci.as_deref())?;
+
+        match move_uci {
+            Some(move_uci) => {
+                let now = Utc::now();
+                let (game, planned) = self
+                    .plan_move(pool, game_id, user_id, move_uci, None, now)
+                    .await?;
+
+                let mut tx = pool.begin().await?;
+                let saved = save_move(&mut tx, &game, planned, now).await?;
+                // The move may have ended the game by itself, e.g. with mate
+                let drawn = saved.new_status == "active";
+                if drawn {
+                    let updated =
+                        games::update_status(&mut *tx, game_id, "draw", claim.draw_reason(), None)
+                            .await?;
+                    ensure_not_stale(updated)?;
+                }
+                tx.commit().await?;
+
+                self.announce_move(game_id, &saved);
+                if drawn {
+                    self.notify_status_change(game_id, "draw", claim.reason());
+                }
+            }
+            None => {
+                if !games::update_status(pool, game_id, "draw", claim.draw_reason(), None).await? {
+                    return Err(already_finished(pool, game_id).await);
+                }
+                self.notify_status_change(game_id, "draw", claim.reason());
             }
         }
 
-        // Add result
-        move_text.push_str(&game_status_to_pgn(&game.status));
-        pgn.push_str(&move_text);
-        pgn.push('\n');
+        let updated_game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Game disappeared")))?;
 
-        Ok(pgn)
+        Ok(updated_game)
     }
-}
 
-fn game_status_to_pgn(status: &str) -> &str {
-    match status {
-        "white_won" => "1-0",
+    /// Call off a game before its first move, e.g. one started against the
+    /// wrong opponent
+    ///
+    /// The game is marked abandoned, so nobody wins and it doesn't count as
+    /// completed.
+    pub async fn abort_game(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<crate::models::Game> {
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+        let moves_played = moves::count_by_game(pool, game_id).await?;
+        ensure_abortable(&game, user_id, moves_played)?;
+
+        // The first move may land between the check and the update
+        if !games::abandon_unstarted(pool, game_id).await? {
+            return Err(AppError::BadRequest(
+                "Games can only be aborted before the first move".to_string(),
+            ));
+        }
+        self.notify_status_change(game_id, "abandoned", "aborted");
+
+        let updated_game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Game disappeared")))?;
+
+        Ok(updated_game)
+    }
+
+    /// Ask the opponent to take back the caller's last move
+    pub async fn request_takeback(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<()> {
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+        let requester = player_color(&game, user_id)?;
+        ensure_active(&game)?;
+        ensure_takebacks_allowed(&game)?;
+
+        let moves_played = moves::count_by_game(pool, game_id).await?;
+        takeback_plies(requester, &game.current_turn, moves_played)?;
+
+        if !games::request_takeback(pool, game_id, user_id).await? {
+            return Err(AppError::BadRequest(
+                "A takeback has already been requested in this game".to_string(),
+            ));
+        }
+
+        self.events.publish(
+            game_id,
+            ServerMessage::TakebackRequested {
+                game_id,
+                requested_by: requester,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Accept or decline the opponent's takeback request
+    ///
+    /// Accepting removes the requester's last move, and the opponent's reply
+    /// if there was one, so the requester is on move again.
+    pub async fn respond_to_takeback(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+        accept: bool,
+    ) -> Result<crate::models::Game> {
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+        let responder = player_color(&game, user_id)?;
+        ensure_active(&game)?;
+
+        let requested_by = games::find_takeback_request(pool, game_id).await?;
+        let requester = check_takeback_response(requested_by, user_id, responder)?;
+
+        // Claim the request so it can only be answered once. The claim, the
+        // removed moves and the restored position are saved together, so a
+        // takeback that fails leaves the request open to answer again.
+        let requested_by = requested_by.unwrap_or_default();
+        let mut tx = pool.begin().await?;
+        if !games::clear_takeback_request(&mut *tx, game_id, requested_by).await? {
+            return Err(AppError::BadRequest(
+                "No takeback has been requested".to_string(),
+            ));
+        }
+
+        let mut position = game.current_position.clone();
+        if accept {
+            let moves_played = moves::count_by_game(&mut *tx, game_id).await?;
+            let plies = takeback_plies(requester, &game.current_turn, moves_played)?;
+
+            let removed = moves::delete_last(&mut *tx, game_id, plies).await?;
+            let (fen, turn) = position_before(&removed)?;
+
+            // The clock restarts for the player back on move; before the first
+            // move there is no deadline
+            let deadline = (moves_played > plies).then(|| next_deadline(&game, Utc::now()));
+            let restored = games::restore_position(
+                &mut *tx,
+                game_id,
+                game.version,
+                &fen,
+                &json!({ "fen": fen }),
+                &turn,
+                deadline,
+            )
+            .await?;
+            ensure_not_stale(restored)?;
+            position = fen;
+        }
+        tx.commit().await?;
+
+        self.events.publish(
+            game_id,
+            ServerMessage::TakebackAnswered {
+                game_id,
+                accepted: accept,
+                position_fen: position,
+            },
+        );
+
+        let updated_game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Game disappeared")))?;
+
+        Ok(updated_game)
+    }
+
+    /// Replay a game's stored moves and report stored positions that don't match
+    ///
+    /// Replay starts from the position before the first move, so games set up
+    /// from a FEN are checked too, and ends by comparing against the game's
+    /// current position. Every mismatch is logged.
+    ///
+    /// Games don't store their starting position separately, so the first
+    /// move's `position_before` is trusted as the start: corruption there
+    /// only shows up if it makes that move illegal or the FEN unreadable.
+    pub async fn verify_game(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+    ) -> Result<GameVerificationResponse> {
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+        let all_moves = moves::list_by_game(pool, game_id).await?;
+        let history: Vec<(&str, &str)> = all_moves
+            .iter()
+            .map(|m| (m.move_uci.as_str(), m.position_after.as_str()))
+            .collect();
+
+        let start_fen = match all_moves.first() {
+            Some(first) => &first.position_before,
+            None => &game.current_position,
+        };
+
+        verification_report(game_id, start_fen, &history, &game.current_position)
+    }
+
+    /// Whether a game's stored positions all agree with a replay of its moves
+    ///
+    /// For audits over many games, where only the verdict matters; the
+    /// mismatches themselves go to the log.
+    pub async fn verify_game_integrity(&self, pool: &PgPool, game_id: Uuid) -> Result<bool> {
+        let report = self.verify_game(pool, game_id).await?;
+        Ok(report.mismatches.is_empty())
+    }
+
+    /// Verify every game, listing those whose stored positions are corrupt
+    pub async fn audit_games(&self, pool: &PgPool) -> Result<GameAuditResponse> {
+        let game_ids = games::list_ids(pool).await?;
+        let mut corrupted = Vec::new();
+
+        for &game_id in &game_ids {
+            if !self.verify_game_integrity(pool, game_id).await? {
+                corrupted.push(game_id);
+            }
+        }
+
+        Ok(GameAuditResponse {
+            games_checked: game_ids.len(),
+            corrupted,
+        })
+    }
+
+    /// Get the position at a ply with every legal move for the side to move
+    ///
+    /// Finished games can be explored at any ply by either player. While a
+    /// game is active only the player on move may use this, and only for the
+    /// current position, so it can't be used to scout the opponent's options.
+    pub async fn review_position(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+        user_id: Uuid,
+        ply: usize,
+    ) -> Result<ReviewResponse> {
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+        let player_color = if user_id == game.white_player_id {
+            Color::White
+        } else if user_id == game.black_player_id {
+            Color::Black
+        } else {
+            return Err(AppError::Forbidden(
+                "You are not a player in this game".to_string(),
+            ));
+        };
+
+        let all_moves = moves::list_by_game(pool, game_id).await?;
+        check_review_access(
+            &game.status,
+            &game.current_turn,
+            player_color,
+            ply,
+            all_moves.len(),
+        )?;
+
+        let fen = if ply == 0 {
+            all_moves
+                .first()
+                .map(|m| m.position_before.clone())
+                .unwrap_or_else(|| game.current_position.clone())
+        } else {
+            all_moves
+                .get(ply - 1)
+                .map(|m| m.position_after.clone())
+                .ok_or_else(|| AppError::NotFound("Ply out of range".to_string()))?
+        };
+
+        let state = GameState::from_fen(&fen)?;
+        let legal_moves_san = state
+            .legal_moves_san()?
+            .into_iter()
+            .map(|(_, san)| san)
+            .collect();
+
+        Ok(ReviewResponse {
+            ply,
+            side_to_move: state.current_turn()?,
+            fen,
+            legal_moves_san,
+        })
+    }
+
+    /// Count the user's active games that are winning, equal, or losing on material
+    pub async fn standings(&self, pool: &PgPool, user_id: Uuid) -> Result<StandingsResponse> {
+        let active_games = games::list_active_by_user(pool, user_id).await?;
+
+        Ok(self.tally_standings(&active_games, user_id))
+    }
+
+    /// Standings over `active_games`, leaving out (and logging) any game
+    /// whose stored position is corrupt so the rest still count
+    fn tally_standings(
+        &self,
+        active_games: &[crate::models::GameWithPlayers],
+        user_id: Uuid,
+    ) -> StandingsResponse {
+        let mut standings = StandingsResponse {
+            winning: 0,
+            equal: 0,
+            losing: 0,
+        };
+
+        for game in active_games {
+            let color = if game.white_player_id == user_id {
+                Color::White
+            } else {
+                Color::Black
+            };
+
+            let eval = match self.cached_eval(game.id, &game.current_position) {
+                Ok(eval) => eval,
+                Err(e) => {
+                    tracing::error!("Leaving game {} out of standings: {:?}", game.id, e);
+                    continue;
+                }
+            };
+            match classify_standing(eval, color) {
+                Standing::Winning => standings.winning += 1,
+                Standing::Equal => standings.equal += 1,
+                Standing::Losing => standings.losing += 1,
+            }
+        }
+
+        standings
+    }
+
+    /// Material eval for a game's position, reusing the cached value until the position changes
+    fn cached_eval(&self, game_id: Uuid, fen: &str) -> Result<i32> {
+        if let Ok(cache) = self.eval_cache.read() {
+            if let Some((cached_fen, eval)) = cache.get(&game_id, Instant::now()) {
+                if cached_fen == fen {
+                    return Ok(eval);
+                }
+            }
+        }
+
+        let eval = material_eval(&GameState::from_fen(fen)?.board()?);
+
+        if let Ok(mut cache) = self.eval_cache.write() {
+            cache.insert(game_id, (fen.to_string(), eval), Instant::now());
+        }
+
+        Ok(eval)
+    }
+
+    /// Generate PGN for a game
+    pub async fn generate_pgn(
+        &self,
+        pool: &PgPool,
+        game_id: Uuid,
+    ) -> Result<String> {
+        // Get game
+        let game = games::find_by_id(pool, game_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Game not found".to_string()))?;
+
+        // Get players
+        let white_player = users::find_by_id(pool, game.white_player_id)
+            .await?
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("White player not found")))?;
+
+        let black_player = users::find_by_id(pool, game.black_player_id)
+            .await?
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Black player not found")))?;
+
+        // Get all moves
+        let all_moves = moves::list_by_game(pool, game_id).await?;
+
+        Ok(format_pgn(
+            &game,
+            &white_player.username,
+            &black_player.username,
+            &all_moves,
+        ))
+    }
+
+    /// PGN of each of a user's finished games, oldest first, optionally only
+    /// those they played as `color`
+    ///
+    /// Reads the games and all their moves in two queries however many games
+    /// there are.
+    pub async fn export_games_pgn(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        color: Option<Color>,
+    ) -> Result<Vec<String>> {
+        let color = color.map(|c| c.to_string());
+        let archived = games::list_completed_for_export(pool, user_id, color.as_deref()).await?;
+
+        let game_ids: Vec<Uuid> = archived.iter().map(|a| a.game.id).collect();
+        let all_moves = moves::list_by_games(pool, &game_ids).await?;
+
+        Ok(format_pgn_archive(&archived, all_moves))
+    }
+}
+
+/// Check whether a player may explore the position at `ply` of a game
+fn check_review_access(
+    status: &str,
+    current_turn: &str,
+    player_color: Color,
+    ply: usize,
+    total_plies: usize,
+) -> Result<()> {
+    if status != "active" {
+        return Ok(());
+    }
+
+    if current_turn != player_color.to_string() || ply != total_plies {
+        return Err(AppError::Forbidden(
+            "Only the current position can be explored by the player on move until the game ends"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Replay a game's stored history and report (and log) where it diverges
+fn verification_report(
+    game_id: Uuid,
+    start_fen: &str,
+    history: &[(&str, &str)],
+    current_position: &str,
+) -> Result<GameVerificationResponse> {
+    let mismatches = verify_replay(start_fen, history, current_position)?;
+
+    for mismatch in &mismatches {
+        tracing::warn!(
+            "Game {} diverges from replay at {}",
+            game_id,
+            describe_mismatch(mismatch)
+        );
+    }
+
+    Ok(GameVerificationResponse {
+        game_id,
+        plies_checked: history.len(),
+        first_divergence: mismatches.first().map(|m| m.ply),
+        mismatches,
+    })
+}
+
+/// Replay a game from `start_fen`, reporting a start position that won't
+/// parse as a mismatch at ply 0 rather than as an error
+fn verify_replay(
+    start_fen: &str,
+    history: &[(&str, &str)],
+    current_position: &str,
+) -> Result<Vec<PositionMismatch>> {
+    match GameState::from_fen(start_fen) {
+        Ok(start) => Ok(find_position_mismatches(&start, history, current_position)?),
+        Err(_) => Ok(vec![unreadable_start(start_fen)]),
+    }
+}
+
+/// Reject a game between players who already have one going, unless the
+/// server allows duplicate games
+fn ensure_no_duplicate_game(allow_duplicates: bool, active_game_exists: bool) -> Result<()> {
+    if active_game_exists && !allow_duplicates {
+        return Err(AppError::BadRequest(
+            "These players already have an active game against each other".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether a player with `active` games can start another under `cap`
+fn has_room_for_game(active: i64, cap: i64) -> bool {
+    active < cap
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Standing {
+    Winning,
+    Equal,
+    Losing,
+}
+
+/// Classify a White-relative eval from the point of view of `color`
+fn classify_standing(eval: i32, color: Color) -> Standing {
+    let relative = match color {
+        Color::White => eval,
+        Color::Black => -eval,
+    };
+
+    match relative.signum() {
+        1 => Standing::Winning,
+        -1 => Standing::Losing,
+        _ => Standing::Equal,
+    }
+}
+
+/// Check that `user_id` may abort a game with `moves_played` moves
+fn ensure_abortable(game: &crate::models::Game, user_id: Uuid, moves_played: i64) -> Result<()> {
+    if user_id != game.white_player_id && user_id != game.black_player_id {
+        return Err(AppError::Forbidden(
+            "You are not a player in this game".to_string(),
+        ));
+    }
+
+    ensure_active(game)?;
+
+    if moves_played > 0 {
+        return Err(AppError::BadRequest(
+            "Games can only be aborted before the first move".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The planned moves that play themselves from `state`, in order
+///
+/// Plans are keyed by normalized FEN, and only the player to move can plan
+/// for a position, so each position reached picks out at most one. The
+/// chain stops at the first position nobody planned for, at an illegal
+/// plan, or when the game is over; each plan is used at most once.
+fn conditional_chain<'a>(
+    state: &GameState,
+    planned: &'a [ConditionalMove],
+) -> Vec<&'a ConditionalMove> {
+    let mut chain: Vec<&ConditionalMove> = Vec::new();
+    let mut state = state.clone();
+
+    while !state.is_game_over().unwrap_or(true) {
+        let position = normalize_fen(state.fen());
+        let Some(reply) = planned
+            .iter()
+            .find(|p| p.position_fen == position && !chain.iter().any(|c| c.id == p.id))
+        else {
+            break;
+        };
+        let Ok((next, _)) = state.make_move(&reply.move_uci) else {
+            break;
+        };
+
+        chain.push(reply);
+        state = next;
+    }
+
+    chain
+}
+
+/// The color `user_id` plays in a game
+fn player_color(game: &crate::models::Game, user_id: Uuid) -> Result<Color> {
+    if user_id == game.white_player_id {
+        Ok(Color::White)
+    } else if user_id == game.black_player_id {
+        Ok(Color::Black)
+    } else {
+        Err(AppError::Forbidden(
+            "You are not a player in this game".to_string(),
+        ))
+    }
+}
+
+/// How many plies a takeback by `requester` removes: their last move, plus
+/// the opponent's reply if it's already their turn again
+fn takeback_plies(requester: Color, current_turn: &str, moves_played: i64) -> Result<i64> {
+    let plies = if current_turn == requester.to_string() {
+        2
+    } else {
+        1
+    };
+    if moves_played < plies {
+        return Err(AppError::BadRequest(
+            "You have no move to take back".to_string(),
+        ));
+    }
+
+    Ok(plies)
+}
+
+/// Check that there is a takeback request for the player answering to answer,
+/// returning the requester's color
+fn check_takeback_response(
+    requested_by: Option<Uuid>,
+    user_id: Uuid,
+    responder: Color,
+) -> Result<Color> {
+    match requested_by {
+        None => Err(AppError::BadRequest(
+            "No takeback has been requested".to_string(),
+        )),
+        Some(requester) if requester == user_id => Err(AppError::BadRequest(
+            "You can't answer your own takeback request".to_string(),
+        )),
+        Some(_) => Ok(responder.opposite()),
+    }
+}
+
+/// Position and side to move before the first of the `removed` moves
+fn position_before(removed: &[crate::models::MoveRecord]) -> Result<(String, String)> {
+    let first = removed
+        .first()
+        .ok_or_else(|| AppError::BadRequest("You have no move to take back".to_string()))?;
+
+    Ok((first.position_before.clone(), first.player_color.clone()))
+}
+
+/// Check that a game's takeback policy permits takebacks
+pub fn ensure_takebacks_allowed(game: &crate::models::Game) -> Result<()> {
+    if !game.takebacks_allowed {
+        return Err(AppError::BadRequest(
+            "Takebacks are not allowed in this game".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reject a move or takeback whose guarded update matched no row: it was
+/// validated against a position that has since changed
+fn ensure_not_stale(updated: bool) -> Result<()> {
+    if !updated {
+        return Err(AppError::Conflict(
+            "The game changed while your request was being handled; reload and try again"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Load a game's stored position
+///
+/// A FEN that won't parse means the row was damaged (a bad migration or a
+/// manual edit), so it's logged with the game id and reported as an internal
+/// error rather than blamed on the request.
+pub fn current_position(game: &crate::models::Game) -> Result<GameState> {
+    GameState::from_fen(&game.current_position).map_err(|e| {
+        tracing::error!(
+            "Game {} has a corrupt position {:?}: {}",
+            game.id,
+            game.current_position,
+            e
+        );
+        AppError::Internal(anyhow::anyhow!(
+            "Corrupt position stored for game {}",
+            game.id
+        ))
+    })
+}
+
+/// Check a FEN a new game should start from, e.g. a puzzle position
+///
+/// It has to be a legal position with moves left to play.
+pub fn starting_position(fen: &str) -> Result<GameState> {
+    let game_state = GameState::from_fen(fen)
+        .map_err(|e| AppError::Validation(format!("Invalid starting position: {}", e)))?;
+
+    if game_state.is_game_over()? {
+        return Err(AppError::Validation(
+            "Starting position is already checkmate or stalemate".to_string(),
+        ));
+    }
+
+    Ok(game_state)
+}
+
+/// Refuse a game or challenge against the caller's own account, before
+/// anything is created
+pub fn ensure_not_self(opponent_id: Uuid, user_id: Uuid) -> Result<()> {
+    if opponent_id == user_id {
+        return Err(AppError::Validation(
+            "You cannot challenge yourself".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// A validated move and its consequences, computed before anything is persisted
+struct PlannedMove {
+    new_move: NewMove,
+    new_state: GameState,
+    player_color: Color,
+    result: Option<GameResult>,
+    new_status: &'static str,
+}
+
+/// A move written to the database, with what to announce about it
+struct SavedMove {
+    move_record: crate::models::MoveRecord,
+    deadline: DateTime<Utc>,
+    result: Option<GameResult>,
+    new_status: &'static str,
+}
+
+/// Write a planned move as part of the caller's transaction: the new
+/// position, the move record and any result it produced
+///
+/// Fails with a conflict if a concurrent move, takeback or game end changed
+/// the game since it was read.
+async fn save_move(
+    conn: &mut PgConnection,
+    game: &crate::models::Game,
+    planned: PlannedMove,
+    now: DateTime<Utc>,
+) -> Result<SavedMove> {
+    let PlannedMove {
+        new_move,
+        new_state,
+        player_color,
+        result,
+        new_status,
+    } = planned;
+
+    let next_turn = player_color.opposite().to_string();
+    let deadline = next_deadline(game, now);
+    let updated = games::update_after_move(
+        &mut *conn,
+        game.id,
+        game.version,
+        new_state.fen(),
+        &json!({ "fen": new_state.fen() }),
+        &next_turn,
+        deadline,
+    )
+    .await?;
+    ensure_not_stale(updated)?;
+
+    let move_record = moves::create_move(&mut *conn, &new_move).await?;
+
+    // If game is over, update status
+    if new_status != "active" {
+        let draw_reason = result.and_then(|r| r.draw_reason());
+        let win_reason = result.and_then(|r| r.win_reason());
+        let ended =
+            games::update_status(&mut *conn, game.id, new_status, draw_reason, win_reason).await?;
+        ensure_not_stale(ended)?;
+    }
+
+    Ok(SavedMove {
+        move_record,
+        deadline,
+        result,
+        new_status,
+    })
+}
+
+/// The draw a player may claim in the game, after their intended move if
+/// they give one
+///
+/// `previous_moves` are the moves played so far, whose starting positions
+/// make up the game's history.
+fn check_draw_claim(
+    game: &crate::models::Game,
+    previous_moves: &[crate::models::MoveRecord],
+    move_uci: Option<&str>,
+) -> Result<GameResult> {
+    let mut history: Vec<String> = previous_moves
+        .iter()
+        .map(|m| m.position_before.clone())
+        .collect();
+    let mut state = current_position(game)?;
+
+    if let Some(move_uci) = move_uci {
+        validate_move(&state, move_uci).map_err(|e| AppError::IllegalMove(e.to_string()))?;
+        history.push(game.current_position.clone());
+        state = state.make_move(move_uci)?.0;
+    }
+
+    claimable_draw(&state, &history)?.ok_or_else(|| {
+        AppError::BadRequest(
+            "No draw to claim: the position hasn't occurred three times and the fifty-move rule doesn't apply"
+                .to_string(),
+        )
+    })
+}
+
+/// Map the result of a move to the game status that should be stored
+fn status_after_move(result: Option<GameResult>, mover: Color) -> &'static str {
+    match result {
+        // The side to move is mated, so the player who just moved wins
+        Some(GameResult::Checkmate) => match mover {
+            Color::White => "white_won",
+            Color::Black => "black_won",
+        },
+        Some(
+            GameResult::Stalemate
+            | GameResult::ThreefoldRepetition
+            | GameResult::FiftyMoveRule
+            | GameResult::FivefoldRepetition
+            | GameResult::SeventyFiveMoveRule
+            | GameResult::InsufficientMaterial,
+        ) => "draw",
+        None => "active",
+    }
+}
+
+/// The status stored when `resigner` resigns: their opponent wins
+fn status_after_resignation(resigner: Color) -> &'static str {
+    match resigner {
+        Color::White => "black_won",
+        Color::Black => "white_won",
+    }
+}
+
+/// Deadlines that have already been warned about, per game
+#[derive(Debug, Default)]
+struct WarnedDeadlines {
+    deadlines: HashMap<Uuid, DateTime<Utc>>,
+}
+
+impl WarnedDeadlines {
+    /// Record a warning for this deadline, returning false if it was already sent
+    fn first_warning(&mut self, game_id: Uuid, deadline: DateTime<Utc>) -> bool {
+        self.deadlines.insert(game_id, deadline) != Some(deadline)
+    }
+
+    /// Drop deadlines that have passed, since they can't come up again
+    fn forget_passed(&mut self, now: DateTime<Utc>) {
+        self.deadlines.retain(|_, deadline| *deadline > now);
+    }
+}
+
+/// Time left until `deadline`, or zero if it has passed
+fn time_remaining(deadline: DateTime<Utc>, now: DateTime<Utc>) -> std::time::Duration {
+    (deadline - now).to_std().unwrap_or_default()
+}
+
+/// Map a PGN result token to a stored status (unfinished games count as abandoned)
+fn status_from_pgn_result(result: &str) -> &'static str {
+    match result {
+        "1-0" => "white_won",
+        "0-1" => "black_won",
+        "1/2-1/2" => "draw",
+        _ => "abandoned",
+    }
+}
+
+/// The status stored when the player on move runs out of time: their opponent wins
+fn status_after_timeout(current_turn: &str) -> &'static str {
+    if current_turn == "white" {
+        "black_won"
+    } else {
+        "white_won"
+    }
+}
+
+/// The status the deadline sweep gives `game` at `now`, or None if it hasn't
+/// run out of time
+///
+/// Only the first move sets a deadline, so a game nobody has moved in never
+/// times out; `abandon_stale_games` deals with those.
+fn timed_out_status(game: &crate::models::Game, now: DateTime<Utc>) -> Option<&'static str> {
+    let deadline = game.move_deadline?;
+    (game.status == "active" && deadline < now).then(|| status_after_timeout(&game.current_turn))
+}
+
+/// Whether an active game counts as abandoned at `now`
+///
+/// The first move sets a deadline, so a game without one has never been
+/// played in.
+fn is_abandoned(game: &crate::models::Game, abandon_after_hours: i64, now: DateTime<Utc>) -> bool {
+    game.status == "active"
+        && game.move_deadline.is_none()
+        && now - game.created_at >= Duration::hours(abandon_after_hours)
+}
+
+/// Deadline for the reply to a move made at `now`, at the game's own pace
+fn next_deadline(game: &crate::models::Game, now: DateTime<Utc>) -> DateTime<Utc> {
+    now + Duration::hours(game.move_deadline_hours)
+}
+
+/// Seconds a player spent on a move made at `now`: time since the previous
+/// move, or since the game was created for the first move
+///
+/// Clamped at zero so clock skew between the app and the database can't
+/// produce a negative duration.
+fn time_taken_seconds(
+    game: &crate::models::Game,
+    previous_move: Option<&crate::models::MoveRecord>,
+    now: DateTime<Utc>,
+) -> i64 {
+    let since = previous_move.map_or(game.created_at, |m| m.timestamp);
+    (now - since).num_seconds().max(0)
+}
+
+/// Work out a move's record and consequences, given the moves played so far
+fn plan_after(
+    game: &crate::models::Game,
+    previous_moves: Vec<crate::models::MoveRecord>,
+    player_color: Color,
+    move_uci: String,
+    comment: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<PlannedMove> {
+    // Load game state and validate move
+    let game_state = current_position(game)?;
+    validate_move(&game_state, &move_uci).map_err(|e| AppError::IllegalMove(e.to_string()))?;
+
+    // Make the move
+    let (new_state, san) = game_state.make_move(&move_uci)?;
+
+    let move_number = next_move_number(&game_state, previous_moves.last().map(numbered));
+    let time_taken = time_taken_seconds(game, previous_moves.last(), now);
+    let history: Vec<String> = previous_moves
+        .into_iter()
+        .map(|m| m.position_before)
+        .chain(std::iter::once(game.current_position.clone()))
+        .collect();
+
+    // Check for game over
+    let result = check_game_result_with_history(&new_state, &history)?;
+    let new_status = status_after_move(result, player_color);
+
+    // Create move record
+    let new_move = NewMove {
+        game_id: game.id,
+        move_number,
+        player_color: player_color.to_string(),
+        move_uci,
+        move_san: san,
+        position_before: game.current_position.clone(),
+        position_after: new_state.fen().to_string(),
+        time_taken_seconds: Some(time_taken),
+        comment,
+        is_terminal: result.is_some(),
+    };
+
+    Ok(PlannedMove {
+        new_move,
+        new_state,
+        player_color,
+        result,
+        new_status,
+    })
+}
+
+/// Build the move record and game as they would look if a planned move were committed
+fn preview_result(
+    mut game: crate::models::Game,
+    planned: &PlannedMove,
+    now: DateTime<Utc>,
+) -> (crate::models::MoveRecord, crate::models::Game) {
+    let new_move = &planned.new_move;
+
+    let move_record = crate::models::MoveRecord {
+        id: Uuid::nil(),
+        game_id: new_move.game_id,
+        move_number: new_move.move_number,
+        player_color: new_move.player_color.clone(),
+        move_uci: new_move.move_uci.clone(),
+        move_san: new_move.move_san.clone(),
+        position_before: new_move.position_before.clone(),
+        position_after: new_move.position_after.clone(),
+        timestamp: now,
+        time_taken_seconds: new_move.time_taken_seconds,
+        comment: new_move.comment.clone(),
+        is_terminal: new_move.is_terminal,
+    };
+
+    game.current_position = planned.new_state.fen().to_string();
+    game.game_state = json!({ "fen": planned.new_state.fen() });
+    game.current_turn = planned.player_color.opposite().to_string();
+    if planned.new_status != "active" {
+        game.status = planned.new_status.to_string();
+        game.draw_reason = planned
+            .result
+            .and_then(|r| r.draw_reason())
+            .map(String::from);
+        game.win_reason = planned
+            .result
+            .and_then(|r| r.win_reason())
+            .map(String::from);
+        game.completed_at = Some(now);
+    }
+
+    (move_record, game)
+}
+
+/// Render a game as PGN with the Seven Tag Roster plus timing and termination headers
+fn format_pgn(
+    game: &crate::models::Game,
+    white_username: &str,
+    black_username: &str,
+    all_moves: &[crate::models::MoveRecord],
+) -> String {
+    let result = game_status_to_pgn(&game.status);
+    let mut pgn = String::new();
+
+    // PGN headers
+    pgn.push_str("[Event \"Rusty Chess Correspondence Game\"]\n");
+    pgn.push_str("[Site \"Rusty Chess\"]\n");
+    pgn.push_str(&format!(
+        "[Date \"{}\"]\n",
+        game.created_at.format("%Y.%m.%d")
+    ));
+    pgn.push_str("[Round \"-\"]\n");
+    pgn.push_str(&format!("[White \"{}\"]\n", white_username));
+    pgn.push_str(&format!("[Black \"{}\"]\n", black_username));
+    pgn.push_str(&format!("[Result \"{}\"]\n", result));
+    pgn.push_str(&format!(
+        "[UTCDate \"{}\"]\n",
+        game.created_at.format("%Y.%m.%d")
+    ));
+    pgn.push_str(&format!(
+        "[UTCTime \"{}\"]\n",
+        game.created_at.format("%H:%M:%S")
+    ));
+    // One move per deadline period
+    pgn.push_str(&format!(
+        "[TimeControl \"1/{}\"]\n",
+        game.move_deadline_hours * 3600
+    ));
+    pgn.push_str(&format!("[Termination \"{}\"]\n", termination(game)));
+
+    // A game without moves is still at its starting position
+    let start = match all_moves.first() {
+        Some(first) => first.position_before.as_str(),
+        None => game.current_position.as_str(),
+    };
+    if normalize_fen(start) != GameState::new().position_key() {
+        pgn.push_str("[SetUp \"1\"]\n");
+        pgn.push_str(&format!("[FEN \"{}\"]\n", start));
+    } else {
+        // Openings are only known from the standard start
+        let sans: Vec<String> = all_moves.iter().map(|m| m.move_san.clone()).collect();
+        if let Some((eco, name)) = identify_opening(&sans) {
+            pgn.push_str(&format!("[ECO \"{}\"]\n", eco));
+            pgn.push_str(&format!("[Opening \"{}\"]\n", name));
+        }
+    }
+    pgn.push('\n');
+
+    // Moves, numbered as stored, which already carry on from the starting
+    // position's fullmove number. A Black move that doesn't continue White's
+    // move of the same number (a game starting with Black to move, say) gets
+    // "N..." so the move text stays valid.
+    let mut move_text = String::new();
+    let mut previous: Option<&crate::models::MoveRecord> = None;
+    for mv in all_moves {
+        let continues_white =
+            previous.is_some_and(|p| p.player_color == "white" && p.move_number == mv.move_number);
+        if mv.player_color == "white" {
+            move_text.push_str(&format!("{}. {} ", mv.move_number, mv.move_san));
+        } else if continues_white {
+            move_text.push_str(&format!("{} ", mv.move_san));
+        } else {
+            move_text.push_str(&format!("{}... {} ", mv.move_number, mv.move_san));
+        }
+        if let Some(comment) = &mv.comment {
+            // A PGN comment runs to the first closing brace
+            move_text.push_str(&format!("{{{}}} ", comment.replace('}', ")")));
+        }
+        // A comment after White's move means Black's reply needs "N..."
+        previous = Some(mv).filter(|m| m.comment.is_none());
+    }
+
+    // Add result
+    move_text.push_str(result);
+    pgn.push_str(&move_text);
+    pgn.push('\n');
+
+    pgn
+}
+
+/// Longest comment a player can attach to a move
+const MAX_COMMENT_CHARS: usize = 1000;
+
+/// Trim a move comment, dropping it if blank and refusing one that's too long
+fn normalize_comment(comment: Option<String>) -> Result<Option<String>> {
+    let Some(comment) = comment else {
+        return Ok(None);
+    };
+
+    let comment = comment.trim();
+    if comment.chars().count() > MAX_COMMENT_CHARS {
+        return Err(AppError::Validation(format!(
+            "Comments can be at most {} characters",
+            MAX_COMMENT_CHARS
+        )));
+    }
+
+    Ok(Some(comment.to_string()).filter(|c| !c.is_empty()))
+}
+
+/// The error for a game that finished before it could be ended, reporting
+/// the result that stands
+async fn already_finished(pool: &PgPool, game_id: Uuid) -> AppError {
+    match games::find_by_id(pool, game_id).await {
+        Ok(Some(game)) => finished_error(&game),
+        Ok(None) => AppError::NotFound("Game not found".to_string()),
+        Err(e) => e.into(),
+    }
+}
+
+fn finished_error(game: &crate::models::Game) -> AppError {
+    match ensure_active(game) {
+        Err(e) => e,
+        // Still active, so the guarded update lost to some other change
+        Ok(()) => ensure_not_stale(false).unwrap_err(),
+    }
+}
+
+/// Refuse to play on a game that has ended, saying how it ended
+fn ensure_active(game: &crate::models::Game) -> Result<()> {
+    match game.status.parse::<GameStatus>() {
+        Ok(GameStatus::Active) => Ok(()),
+        Ok(status) => Err(AppError::GameNotActive {
+            status,
+            winner: status.winner(),
+        }),
+        Err(e) => Err(AppError::Internal(anyhow::anyhow!(e))),
+    }
+}
+
+/// The API response to a move, from the saved move and the game after it
+pub fn move_response(
+    move_record: crate::models::MoveRecord,
+    game: crate::models::Game,
+    user_id: Uuid,
+) -> Result<MoveResponse> {
+    // Saves clients fetching the game again to show check or count replies
+    let position_after = GameState::from_fen(&move_record.position_after)?;
+    let gives_check = position_after.in_check()?;
+    let opponent_legal_move_count = position_after.legal_moves()?.len();
+
+    let your_color = your_color(&game, user_id);
+
+    let response = MoveResponse {
+        r#move: Move {
+            id: move_record.id,
+            game_id: move_record.game_id,
+            move_number: move_record.move_number,
+            player_color: if move_record.player_color == "white" {
+                Color::White
+            } else {
+                Color::Black
+            },
+            move_uci: move_record.move_uci,
+            move_san: move_record.move_san,
+            position_before: move_record.position_before,
+            position_after: move_record.position_after,
+            timestamp: move_record.timestamp,
+            time_taken_seconds: move_record.time_taken_seconds,
+            comment: move_record.comment,
+            is_terminal: move_record.is_terminal,
+        },
+        game: shared::types::Game {
+            id: game.id,
+            white_player_id: game.white_player_id,
+            black_player_id: game.black_player_id,
+            current_position: game.current_position,
+            status: game.status.parse().unwrap_or(shared::types::GameStatus::Active),
+            current_turn: if game.current_turn == "white" {
+                Color::White
+            } else {
+                Color::Black
+            },
+            move_deadline: game.move_deadline,
+            created_at: game.created_at,
+            completed_at: game.completed_at,
+            takebacks_allowed: game.takebacks_allowed,
+            move_deadline_hours: game.move_deadline_hours,
+            is_public: game.is_public,
+            draw_reason: game.draw_reason,
+        },
+        gives_check,
+        opponent_legal_move_count,
+        your_color,
+    };
+
+    Ok(response)
+}
+
+/// The color `user_id` plays in `game`, or None if they aren't playing
+pub fn your_color(game: &crate::models::Game, user_id: Uuid) -> Option<Color> {
+    if user_id == game.white_player_id {
+        Some(Color::White)
+    } else if user_id == game.black_player_id {
+        Some(Color::Black)
+    } else {
+        None
+    }
+}
+
+/// Number of the move played from `position`, given the number and color
+/// of the move before it
+///
+/// Read from the position's fullmove counter, so a game set up from a FEN
+/// numbers on from it. Positions saved before the counters were kept all
+/// say move 1, so the number following the last record is the floor.
+fn next_move_number(position: &GameState, previous: Option<(i32, &str)>) -> i32 {
+    let after_previous = match previous {
+        None => 1,
+        Some((number, "black")) => number + 1,
+        Some((number, _)) => number,
+    };
+    after_previous.max(position.fullmove_number() as i32)
+}
+
+/// Number and color of a stored move, for numbering the one after it
+fn numbered(record: &crate::models::MoveRecord) -> (i32, &str) {
+    (record.move_number, record.player_color.as_str())
+}
+
+/// Move records for an imported game, numbered as if it had been played here
+fn imported_moves(
+    game_id: Uuid,
+    parsed: &crate::chess::ParsedGame,
+    ended_on_board: bool,
+) -> Result<Vec<NewMove>> {
+    let mut records: Vec<NewMove> = Vec::with_capacity(parsed.moves.len());
+
+    for (i, parsed_move) in parsed.moves.iter().enumerate() {
+        let before = GameState::from_fen(&parsed_move.fen_before)?;
+        let previous = records
+            .last()
+            .map(|m| (m.move_number, m.player_color.as_str()));
+
+        records.push(NewMove {
+            game_id,
+            move_number: next_move_number(&before, previous),
+            player_color: before.current_turn()?.to_string(),
+            move_uci: parsed_move.uci.clone(),
+            move_san: parsed_move.san.clone(),
+            position_before: parsed_move.fen_before.clone(),
+            position_after: parsed_move.fen_after.clone(),
+            time_taken_seconds: None,
+            comment: None,
+            is_terminal: ended_on_board && i + 1 == parsed.moves.len(),
+        });
+    }
+
+    Ok(records)
+}
+
+/// PGN Termination header for a game
+///
+/// Games the deadline sweeper ended were lost on time. Draws name their
+/// reason instead of "Normal" when it's known.
+fn termination(game: &crate::models::Game) -> &'static str {
+    match game.status.as_str() {
+        "active" => "Unterminated",
+        "abandoned" => "Abandoned",
+        "draw" => match game.draw_reason.as_deref() {
+            Some("stalemate") => "Stalemate",
+            Some("threefold") => "Threefold repetition",
+            Some("fifty_move") => "Fifty-move rule",
+            Some("fivefold") => "Fivefold repetition",
+            Some("seventy_five_move") => "Seventy-five-move rule",
+            Some("insufficient_material") => "Insufficient material",
+            Some("agreement") => "Draw by agreement",
+            _ => "Normal",
+        },
+        _ => match game.win_reason.as_deref() {
+            Some("timeout") => "Time forfeit",
+            _ => "Normal",
+        },
+    }
+}
+
+/// One PGN per game, each followed by the blank line that separates games
+/// in a multi-game file
+fn format_pgn_archive(
+    archived: &[crate::models::ArchivedGame],
+    all_moves: Vec<crate::models::MoveRecord>,
+) -> Vec<String> {
+    let mut moves_by_game: HashMap<Uuid, Vec<crate::models::MoveRecord>> = HashMap::new();
+    for mv in all_moves {
+        moves_by_game.entry(mv.game_id).or_default().push(mv);
+    }
+
+    archived
+        .iter()
+        .map(|a| {
+            let game_moves = moves_by_game.remove(&a.game.id).unwrap_or_default();
+            let mut pgn = format_pgn(
+                &a.game,
+                &a.white_player_username,
+                &a.black_player_username,
+                &game_moves,
+            );
+            pgn.push('\n');
+            pgn
+        })
+        .collect()
+}
+
+fn game_status_to_pgn(status: &str) -> &str {
+    match status {
+        "white_won" => "1-0",
         "black_won" => "0-1",
         "draw" => "1/2-1/2",
         _ => "*",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::check_game_result;
+
+    fn test_game(fen: &str, turn: &str) -> crate::models::Game {
+        crate::models::Game {
+            id: Uuid::new_v4(),
+            white_player_id: Uuid::new_v4(),
+            black_player_id: Uuid::new_v4(),
+            current_position: fen.to_string(),
+            game_state: json!({ "fen": fen }),
+            status: "active".to_string(),
+            current_turn: turn.to_string(),
+            move_deadline: Some(Utc::now() + Duration::hours(24)),
+            created_at: Utc::now(),
+            completed_at: None,
+            takebacks_allowed: false,
+            move_deadline_hours: 72,
+            is_public: false,
+            draw_reason: None,
+            win_reason: None,
+            version: 0,
+        }
+    }
+
+    #[test]
+    fn test_ending_a_finished_game_reports_its_result() {
+        let mut game = test_game(GameState::new().fen(), "black");
+        game.status = "white_won".to_string();
+        assert!(matches!(
+            finished_error(&game),
+            AppError::GameNotActive {
+                status: GameStatus::WhiteWon,
+                winner: Some(Color::White),
+            }
+        ));
+
+        game.status = "active".to_string();
+        assert!(matches!(finished_error(&game), AppError::Conflict(_)));
+    }
+
+    #[test]
+    fn test_abort_before_first_move() {
+        let game = test_game(GameState::new().fen(), "white");
+        assert!(ensure_abortable(&game, game.white_player_id, 0).is_ok());
+        assert!(ensure_abortable(&game, game.black_player_id, 0).is_ok());
+        assert!(matches!(
+            ensure_abortable(&game, Uuid::new_v4(), 0),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_abort_rejected_after_a_move() {
+        let game = test_game(GameState::new().fen(), "black");
+        assert!(matches!(
+            ensure_abortable(&game, game.black_player_id, 1),
+            Err(AppError::BadRequest(msg)) if msg.contains("before the first move")
+        ));
+
+        let mut finished = test_game(GameState::new().fen(), "white");
+        finished.status = "abandoned".to_string();
+        assert!(matches!(
+            ensure_abortable(&finished, finished.white_player_id, 0),
+            Err(AppError::GameNotActive { .. })
+        ));
+    }
+
+    fn plan(game: &crate::models::Game, move_uci: &str, player_color: Color) -> PlannedMove {
+        let state = GameState::from_fen(&game.current_position).unwrap();
+        let (new_state, san) = state.make_move(move_uci).unwrap();
+        PlannedMove {
+            new_move: NewMove {
+                game_id: game.id,
+                move_number: 1,
+                player_color: player_color.to_string(),
+                move_uci: move_uci.to_string(),
+                move_san: san,
+                position_before: game.current_position.clone(),
+                position_after: new_state.fen().to_string(),
+                time_taken_seconds: Some(0),
+                comment: None,
+                is_terminal: check_game_result(&new_state).unwrap().is_some(),
+            },
+            result: check_game_result(&new_state).unwrap(),
+            new_status: status_after_move(check_game_result(&new_state).unwrap(), player_color),
+            new_state,
+            player_color,
+        }
+    }
+
+    #[test]
+    fn test_preview_result_reflects_move_without_touching_deadline() {
+        let game = test_game(GameState::new().fen(), "white");
+        let deadline = game.move_deadline;
+        let planned = plan(&game, "e2e4", Color::White);
+
+        let (preview_move, preview_game) = preview_result(game, &planned, Utc::now());
+        assert!(preview_move.id.is_nil());
+        assert_eq!(preview_move.move_san, "e4");
+        assert_eq!(preview_game.current_position, planned.new_state.fen());
+        assert_eq!(preview_game.current_turn, "black");
+        assert_eq!(preview_game.status, "active");
+        assert_eq!(preview_game.move_deadline, deadline);
+    }
+
+    #[test]
+    fn test_each_draw_records_its_reason() {
+        let cases = [
+            (
+                "k7/8/8/2Q5/8/8/8/7K w - - 0 1",
+                "c5b6",
+                "stalemate",
+                "Stalemate",
+            ),
+            (
+                "7k/8/8/8/8/8/1r6/K7 w - - 0 1",
+                "a1b2",
+                "insufficient_material",
+                "Insufficient material",
+            ),
+            (
+                "4k3/8/8/8/8/8/8/R3K3 w - - 149 80",
+                "a1a2",
+                "seventy_five_move",
+                "Seventy-five-move rule",
+            ),
+        ];
+
+        for (fen, uci, reason, pgn_termination) in cases {
+            let game = test_game(fen, "white");
+            let planned = plan(&game, uci, Color::White);
+            assert_eq!(planned.new_status, "draw", "{}", fen);
+
+            let (_, preview_game) = preview_result(game, &planned, Utc::now());
+            assert_eq!(preview_game.draw_reason.as_deref(), Some(reason));
+            assert_eq!(termination(&preview_game), pgn_termination);
+        }
+
+        assert_eq!(
+            GameResult::ThreefoldRepetition.draw_reason(),
+            Some("threefold")
+        );
+        assert_eq!(GameResult::Checkmate.draw_reason(), None);
+        assert_eq!(GameResult::Checkmate.win_reason(), Some("checkmate"));
+        assert_eq!(GameResult::Stalemate.win_reason(), None);
+    }
+
+    #[test]
+    fn test_draw_termination_without_reason_is_normal() {
+        let mut game = test_game(GameState::new().fen(), "white");
+        game.status = "draw".to_string();
+        game.completed_at = Some(Utc::now());
+        assert_eq!(termination(&game), "Normal");
+
+        game.draw_reason = Some("agreement".to_string());
+        assert_eq!(termination(&game), "Draw by agreement");
+    }
+
+    #[test]
+    fn test_game_from_mid_game_position() {
+        // Black to move after 1. e4 e5 2. Nf3
+        let fen = "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2";
+        let state = starting_position(fen).unwrap();
+        assert_eq!(state.fen(), fen);
+        assert_eq!(state.current_turn().unwrap(), Color::Black);
+
+        assert!(validate_move(&state, "b8c6").is_ok());
+        assert!(validate_move(&state, "e2e4").is_err());
+        assert!(validate_move(&state, "e5e4").is_err());
+    }
+
+    #[test]
+    fn test_corrupt_stored_position_is_an_internal_error() {
+        let game = test_game("not a fen", "white");
+
+        let err = current_position(&game).unwrap_err();
+        assert!(matches!(err, AppError::Internal(_)), "{:?}", err);
+        assert_eq!(err.code(), "internal_error");
+        assert!(err.to_string().contains(&game.id.to_string()));
+
+        let game = test_game(GameState::new().fen(), "white");
+        assert!(current_position(&game).is_ok());
+    }
+
+    #[test]
+    fn test_challenging_yourself_is_rejected() {
+        let user_id = Uuid::new_v4();
+
+        let err = ensure_not_self(user_id, user_id).unwrap_err();
+        assert_eq!(err.status(), axum::http::StatusCode::BAD_REQUEST);
+        assert_eq!(
+            err.to_string(),
+            "Validation error: You cannot challenge yourself"
+        );
+        assert!(ensure_not_self(Uuid::new_v4(), user_id).is_ok());
+    }
+
+    #[test]
+    fn test_starting_position_must_be_playable() {
+        for fen in [
+            "not a fen",
+            "4k3/4R3/8/8/8/8/8/4K3 w - - 0 1",
+            // Fool's mate, White to move
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+        ] {
+            assert!(
+                matches!(starting_position(fen), Err(AppError::Validation(_))),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_pgn_of_unplayed_game_from_custom_position() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1";
+        let game = test_game(fen, "white");
+
+        let pgn = format_pgn(&game, "alice", "bob", &[]);
+        assert!(pgn.contains("[SetUp \"1\"]\n"));
+        assert!(pgn.contains(&format!("[FEN \"{}\"]\n", fen)));
+
+        let standard = test_game(GameState::new().fen(), "white");
+        assert!(!format_pgn(&standard, "alice", "bob", &[]).contains("[SetUp"));
+    }
+
+    /// Play `moves` from `fen` the way `plan_move` does, returning the status
+    /// `submit_move` would store after the last one
+    fn final_status(fen: &str, moves: &[&str]) -> &'static str {
+        let mut state = GameState::from_fen(fen).unwrap();
+        let mut history = Vec::new();
+        let mut status = "active";
+        for uci in moves {
+            assert_eq!(status, "active", "game ended before {}", uci);
+            let mover = state.current_turn().unwrap();
+            let (next, _) = state.make_move(uci).unwrap();
+            history.push(state.fen().to_string());
+            let result = check_game_result_with_history(&next, &history).unwrap();
+            status = status_after_move(result, mover);
+            state = next;
+        }
+        status
+    }
+
+    #[test]
+    fn test_scholars_mate_is_won_by_white() {
+        let moves = ["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"];
+        assert_eq!(final_status(GameState::new().fen(), &moves), "white_won");
+    }
+
+    #[test]
+    fn test_only_the_mating_move_is_terminal() {
+        let mut game = test_game(GameState::new().fen(), "white");
+        let mut played: Vec<crate::models::MoveRecord> = Vec::new();
+        for uci in ["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"] {
+            let mover = GameState::from_fen(&game.current_position)
+                .unwrap()
+                .current_turn()
+                .unwrap();
+            let planned = plan_after(
+                &game,
+                played.clone(),
+                mover,
+                uci.to_string(),
+                None,
+                Utc::now(),
+            )
+            .unwrap();
+            let (move_record, next) = preview_result(game, &planned, Utc::now());
+            played.push(move_record);
+            game = next;
+        }
+
+        assert_eq!(game.status, "white_won");
+        let terminal: Vec<bool> = played.iter().map(|m| m.is_terminal).collect();
+        assert_eq!(terminal, [false, false, false, false, false, false, true]);
+    }
+
+    #[test]
+    fn test_back_rank_mate_is_won_by_black() {
+        let fen = "3r2k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1";
+        assert_eq!(final_status(fen, &["d8d1"]), "black_won");
+
+        // The preview of the same move agrees
+        let game = test_game(fen, "black");
+        let planned = plan(&game, "d8d1", Color::Black);
+        let (_, preview_game) = preview_result(game, &planned, Utc::now());
+        assert_eq!(preview_game.status, "black_won");
+        assert_eq!(preview_game.draw_reason, None);
+    }
+
+    #[test]
+    fn test_stale_move_is_rejected_with_conflict() {
+        assert!(ensure_not_stale(true).is_ok());
+
+        // Two requests validated against version 3; only the first update matches
+        let err = ensure_not_stale(false).unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        assert_eq!(err.status(), axum::http::StatusCode::CONFLICT);
+        assert_eq!(err.code(), "conflict");
+    }
+
+    #[test]
+    fn test_only_empty_stale_games_are_abandoned() {
+        let now = Utc::now();
+
+        let mut stale = test_game(GameState::new().fen(), "white");
+        stale.move_deadline = None;
+        stale.created_at = now - Duration::hours(73);
+
+        let mut fresh = stale.clone();
+        fresh.created_at = now - Duration::hours(71);
+
+        // A move was played, so this one times out instead
+        let mut started = stale.clone();
+        started.move_deadline = Some(now - Duration::hours(1));
+
+        let mut finished = stale.clone();
+        finished.status = "draw".to_string();
+
+        assert!(is_abandoned(&stale, 72, now));
+        assert!(!is_abandoned(&fresh, 72, now));
+        assert!(!is_abandoned(&started, 72, now));
+        assert!(!is_abandoned(&finished, 72, now));
+
+        // Passing time eventually abandons the fresh game too
+        assert!(is_abandoned(&fresh, 72, now + Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_deadline_follows_game_time_control() {
+        let mut daily = test_game(GameState::new().fen(), "white");
+        daily.move_deadline_hours = TimeControl::Daily1.hours();
+        let mut weekly = test_game(GameState::new().fen(), "white");
+        weekly.move_deadline_hours = TimeControl::Daily7.hours();
+
+        let now = Utc::now();
+        assert_eq!(next_deadline(&daily, now), now + Duration::hours(24));
+        assert_eq!(next_deadline(&weekly, now), now + Duration::hours(168));
+    }
+
+    #[test]
+    fn test_time_taken_between_moves() {
+        let game = test_game(GameState::new().fen(), "white");
+        let created = game.created_at;
+
+        // First move is timed from game creation
+        let first_at = created + Duration::seconds(30);
+        assert_eq!(time_taken_seconds(&game, None, first_at), 30);
+
+        // Second move is timed from the first
+        let mut first = record(1, "white", "e4", GameState::new().fen());
+        first.timestamp = first_at;
+        let second_at = first_at + Duration::minutes(90);
+        assert_eq!(time_taken_seconds(&game, Some(&first), second_at), 90 * 60);
+
+        // A clock behind the stored timestamp never gives a negative duration
+        let skewed = first_at - Duration::seconds(2);
+        assert_eq!(time_taken_seconds(&game, Some(&first), skewed), 0);
+    }
+
+    #[test]
+    fn test_preview_result_reports_game_ending_move() {
+        // Scholar's mate, white to play Qxf7#
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+        let game = test_game(fen, "white");
+        let planned = plan(&game, "h5f7", Color::White);
+
+        let (preview_move, preview_game) = preview_result(game, &planned, Utc::now());
+        assert_eq!(preview_move.move_san, "Qxf7#");
+        assert_eq!(preview_game.status, "white_won");
+        assert!(preview_game.completed_at.is_some());
+    }
+
+    #[test]
+    fn test_resignation_awards_game_to_opponent() {
+        assert_eq!(status_after_resignation(Color::White), "black_won");
+        assert_eq!(status_after_resignation(Color::Black), "white_won");
+    }
+
+    #[test]
+    fn test_deadline_warning_sent_once_per_deadline() {
+        let mut warned = WarnedDeadlines::default();
+        let game_id = Uuid::new_v4();
+        let deadline = Utc::now() + Duration::hours(2);
+
+        assert!(warned.first_warning(game_id, deadline));
+        assert!(!warned.first_warning(game_id, deadline));
+
+        // A move sets a new deadline, which gets its own warning
+        let next_deadline = deadline + Duration::hours(72);
+        assert!(warned.first_warning(game_id, next_deadline));
+        assert!(!warned.first_warning(game_id, next_deadline));
+
+        // Other games are tracked separately
+        assert!(warned.first_warning(Uuid::new_v4(), deadline));
+    }
+
+    #[test]
+    fn test_passed_deadlines_are_forgotten() {
+        let mut warned = WarnedDeadlines::default();
+        let now = Utc::now();
+        warned.first_warning(Uuid::new_v4(), now - Duration::minutes(1));
+        warned.first_warning(Uuid::new_v4(), now + Duration::minutes(1));
+
+        warned.forget_passed(now);
+        assert_eq!(warned.deadlines.len(), 1);
+    }
+
+    #[test]
+    fn test_time_remaining_clamped_to_zero() {
+        let now = Utc::now();
+        assert_eq!(
+            time_remaining(now + Duration::seconds(90), now),
+            std::time::Duration::from_secs(90)
+        );
+        assert_eq!(
+            time_remaining(now - Duration::seconds(90), now),
+            std::time::Duration::ZERO
+        );
+    }
+
+    fn record(move_number: i32, color: &str, san: &str, before: &str) -> crate::models::MoveRecord {
+        crate::models::MoveRecord {
+            id: Uuid::new_v4(),
+            game_id: Uuid::nil(),
+            move_number,
+            player_color: color.to_string(),
+            move_uci: String::new(),
+            move_san: san.to_string(),
+            position_before: before.to_string(),
+            position_after: String::new(),
+            timestamp: Utc::now(),
+            time_taken_seconds: None,
+            comment: None,
+            is_terminal: false,
+        }
+    }
+
+    #[test]
+    fn test_pgn_archive_has_one_game_per_finished_game() {
+        let archived: Vec<crate::models::ArchivedGame> = ["white_won", "draw", "black_won"]
+            .iter()
+            .map(|status| {
+                let mut game = test_game(GameState::new().fen(), "white");
+                game.status = status.to_string();
+                crate::models::ArchivedGame {
+                    game,
+                    white_player_username: "alice".to_string(),
+                    black_player_username: "bob".to_string(),
+                }
+            })
+            .collect();
+
+        // Moves arrive in one batch and are matched to their games by id
+        let start = GameState::new();
+        let mut e4 = record(1, "white", "e4", start.fen());
+        e4.game_id = archived[1].game.id;
+        let mut d4 = record(1, "white", "d4", start.fen());
+        d4.game_id = archived[2].game.id;
+
+        let chunks = format_pgn_archive(&archived, vec![e4, d4]);
+        assert_eq!(chunks.len(), 3);
+
+        let file = chunks.concat();
+        assert_eq!(file.matches("[Event ").count(), 3);
+        assert_eq!(file.matches("\n\n[Event ").count(), 2);
+        assert!(chunks[0].ends_with("1-0\n\n"));
+        assert!(chunks[1].contains("1. e4 1/2-1/2"));
+        assert!(chunks[2].contains("1. d4 0-1"));
+    }
+
+    /// A game after `moves` from the start, with the records `claim_draw` reads
+    fn shuffled(moves: &[&str]) -> (crate::models::Game, Vec<crate::models::MoveRecord>) {
+        let mut state = GameState::new();
+        let mut records = Vec::new();
+        for (i, uci) in moves.iter().enumerate() {
+            let color = if i % 2 == 0 { "white" } else { "black" };
+            records.push(record(i as i32 / 2 + 1, color, uci, state.fen()));
+            state = state.make_move(uci).unwrap().0;
+        }
+        let turn = state.current_turn().unwrap().to_string();
+        (test_game(state.fen(), &turn), records)
+    }
+
+    const SHUFFLE: [&str; 4] = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+    #[test]
+    fn test_threefold_claim() {
+        // Back at the start for the third time
+        let moves: Vec<&str> = SHUFFLE.iter().cycle().take(8).copied().collect();
+        let (game, records) = shuffled(&moves);
+        assert_eq!(
+            check_draw_claim(&game, &records, None).unwrap(),
+            GameResult::ThreefoldRepetition
+        );
+
+        // One move short, the claim holds only with the move that repeats
+        let (game, records) = shuffled(&moves[..7]);
+        assert_eq!(
+            check_draw_claim(&game, &records, Some("f6g8")).unwrap(),
+            GameResult::ThreefoldRepetition
+        );
+    }
+
+    #[test]
+    fn test_premature_draw_claim_is_rejected() {
+        let (game, records) = shuffled(&SHUFFLE);
+        assert!(matches!(
+            check_draw_claim(&game, &records, None),
+            Err(AppError::BadRequest(msg)) if msg.contains("No draw to claim")
+        ));
+
+        let moves: Vec<&str> = SHUFFLE.iter().cycle().take(7).copied().collect();
+        let (game, records) = shuffled(&moves);
+        assert!(matches!(
+            check_draw_claim(&game, &records, Some("e7e5")),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            check_draw_claim(&game, &records, Some("e7e4")),
+            Err(AppError::IllegalMove(_))
+        ));
+    }
+
+    #[test]
+    fn test_fifty_move_claim() {
+        let game = test_game("4k3/8/8/8/8/8/8/R3K3 w - - 99 80", "white");
+        assert!(check_draw_claim(&game, &[], None).is_err());
+        assert_eq!(
+            check_draw_claim(&game, &[], Some("a1a2")).unwrap(),
+            GameResult::FiftyMoveRule
+        );
+
+        // A pawn move resets the count, so it can't be claimed with one
+        let game = test_game("4k3/8/8/8/8/8/P7/R3K3 w - - 100 80", "white");
+        assert_eq!(
+            check_draw_claim(&game, &[], None).unwrap(),
+            GameResult::FiftyMoveRule
+        );
+        assert!(check_draw_claim(&game, &[], Some("a2a3")).is_err());
+    }
+
+    #[test]
+    fn test_pgn_headers() {
+        let mut game = test_game(GameState::new().fen(), "white");
+        game.created_at = "2026-03-04T05:06:07Z".parse().unwrap();
+        let pgn = format_pgn(&game, "alice", "bob", &[]);
+
+        assert!(pgn.contains("[Round \"-\"]\n"));
+        assert!(pgn.contains("[White \"alice\"]\n"));
+        assert!(pgn.contains("[Black \"bob\"]\n"));
+        assert!(pgn.contains("[UTCDate \"2026.03.04\"]\n"));
+        assert!(pgn.contains("[UTCTime \"05:06:07\"]\n"));
+        assert!(pgn.contains("[TimeControl \"1/259200\"]\n"));
+        assert!(pgn.contains("[Termination \"Unterminated\"]\n"));
+        assert!(!pgn.contains("[FEN "));
+    }
+
+    #[test]
+    fn test_move_comment_kept_and_written_to_pgn() {
+        let game = test_game(GameState::new().fen(), "white");
+        let mut planned = plan(&game, "e2e4", Color::White);
+        planned.new_move.comment = normalize_comment(Some("  Best by test ".into())).unwrap();
+
+        let (first, _) = preview_result(game.clone(), &planned, Utc::now());
+        assert_eq!(first.comment.as_deref(), Some("Best by test"));
+
+        let mut reply = record(1, "black", "e5", &first.position_after);
+        reply.comment = Some("Symmetry {again}".to_string());
+        let pgn = format_pgn(&game, "alice", "bob", &[first, reply]);
+        assert!(
+            pgn.contains("1. e4 {Best by test} 1... e5 {Symmetry {again)} *"),
+            "{}",
+            pgn
+        );
+    }
+
+    #[test]
+    fn test_normalize_comment() {
+        assert_eq!(normalize_comment(None).unwrap(), None);
+        assert_eq!(normalize_comment(Some("   ".into())).unwrap(), None);
+        assert!(matches!(
+            normalize_comment(Some("x".repeat(MAX_COMMENT_CHARS + 1))),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_pgn_names_the_opening() {
+        let game = test_game(GameState::new().fen(), "white");
+        let start = GameState::new();
+        let (after_e4, _) = start.make_move("e2e4").unwrap();
+        let moves = vec![
+            record(1, "white", "e4", start.fen()),
+            record(1, "black", "c5", after_e4.fen()),
+        ];
+
+        let pgn = format_pgn(&game, "alice", "bob", &moves);
+        assert!(
+            pgn.contains("[ECO \"B20\"]\n[Opening \"Sicilian Defense\"]\n"),
+            "{}",
+            pgn
+        );
+    }
+
+    #[test]
+    fn test_pgn_termination() {
+        let mut game = test_game(GameState::new().fen(), "white");
+        let deadline = game.move_deadline.unwrap();
+
+        game.status = "black_won".to_string();
+        game.completed_at = Some(deadline + Duration::minutes(1));
+        game.win_reason = Some("timeout".to_string());
+        assert_eq!(termination(&game), "Time forfeit");
+
+        // Resigning after the deadline, before the sweep got to the game
+        game.win_reason = Some("resignation".to_string());
+        assert_eq!(termination(&game), "Normal");
+
+        game.win_reason = Some("checkmate".to_string());
+        assert_eq!(termination(&game), "Normal");
+
+        game.status = "abandoned".to_string();
+        assert_eq!(termination(&game), "Abandoned");
+    }
+
+    #[test]
+    fn test_pgn_black_resigns_before_moving() {
+        let start = GameState::new();
+        let mut game = test_game(start.fen(), "black");
+        game.status = "white_won".to_string();
+        game.completed_at = Some(Utc::now());
+
+        let moves = vec![record(1, "white", "e4", start.fen())];
+        let pgn = format_pgn(&game, "alice", "bob", &moves);
+
+        assert!(pgn.ends_with("\n\n1. e4 1-0\n"));
+        assert!(pgn.contains("[Termination \"Normal\"]\n"));
+    }
+
+    #[test]
+    fn test_pgn_white_resigns_after_one_move() {
+        let start = GameState::new();
+        let mut game = test_game(start.fen(), "black");
+        game.status = "black_won".to_string();
+        game.completed_at = Some(Utc::now());
+
+        let moves = vec![record(1, "white", "e4", start.fen())];
+        let pgn = format_pgn(&game, "alice", "bob", &moves);
+
+        assert!(pgn.contains("[Result \"0-1\"]\n"));
+        assert!(pgn.ends_with("\n\n1. e4 0-1\n"));
+    }
+
+    #[test]
+    fn test_pgn_game_ending_on_black_move() {
+        let mut state = GameState::new();
+        let mut moves: Vec<crate::models::MoveRecord> = Vec::new();
+        for uci in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            let before = state.fen().to_string();
+            let color = state.current_turn().unwrap().to_string();
+            let (next, san) = state.make_move(uci).unwrap();
+            let number = next_move_number(&state, moves.last().map(numbered));
+            moves.push(record(number, &color, &san, &before));
+            state = next;
+        }
+        let mut game = test_game(state.fen(), "white");
+        game.status = "black_won".to_string();
+
+        let pgn = format_pgn(&game, "alice", "bob", &moves);
+        assert!(pgn.ends_with("\n\n1. f3 e5 2. g4 Qh4# 0-1\n"), "{}", pgn);
+    }
+
+    #[test]
+    fn test_move_to_finished_game_reports_result() {
+        let mut game = test_game(GameState::new().fen(), "black");
+        assert!(ensure_active(&game).is_ok());
+
+        game.status = "white_won".to_string();
+        match ensure_active(&game) {
+            Err(AppError::GameNotActive { status, winner }) => {
+                assert_eq!(status, GameStatus::WhiteWon);
+                assert_eq!(winner, Some(Color::White));
+            }
+            other => panic!("expected GameNotActive, got {:?}", other),
+        }
+
+        game.status = "draw".to_string();
+        assert!(matches!(
+            ensure_active(&game),
+            Err(AppError::GameNotActive { winner: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_next_move_number() {
+        let start = GameState::new();
+        let (after_e4, _) = start.make_move("e2e4").unwrap();
+        let (after_e5, _) = after_e4.make_move("e7e5").unwrap();
+        assert_eq!(next_move_number(&start, None), 1);
+        assert_eq!(next_move_number(&after_e4, Some((1, "white"))), 1);
+        assert_eq!(next_move_number(&after_e5, Some((1, "black"))), 2);
+
+        // A set-up position numbers on from its own counter
+        let setup = GameState::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 3 30").unwrap();
+        assert_eq!(next_move_number(&setup, None), 30);
+
+        // A position saved without counters falls back on the records
+        assert_eq!(next_move_number(&start, Some((5, "black"))), 6);
+    }
+
+    #[test]
+    fn test_imported_moves_number_on_from_the_fen() {
+        let pgn = "[FEN \"r1bqkbnr/pppp1ppp/2n5/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R b KQkq - 3 10\"]\n\
+                   [SetUp \"1\"]\n\n10... Nf6 11. Bb5 Bc5 *\n";
+        let parsed = parse_pgn(pgn).unwrap();
+
+        let records = imported_moves(Uuid::nil(), &parsed, false).unwrap();
+        let numbered: Vec<(i32, &str)> = records
+            .iter()
+            .map(|m| (m.move_number, m.player_color.as_str()))
+            .collect();
+        assert_eq!(numbered, [(10, "black"), (11, "white"), (11, "black")]);
+        assert!(records.iter().all(|m| !m.is_terminal));
+    }
+
+    #[test]
+    fn test_pgn_from_custom_position() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1";
+        let game = test_game(fen, "black");
+        let (moves, _) = play(fen, &["Kd7", "e4"]);
+        let pgn = format_pgn(&game, "alice", "bob", &moves);
+
+        assert!(pgn.contains("[SetUp \"1\"]\n"));
+        assert!(pgn.contains(&format!("[FEN \"{}\"]\n", fen)));
+        assert!(pgn.ends_with("\n\n1... Kd7 2. e4 *\n"), "{}", pgn);
+    }
+
+    #[test]
+    fn test_pgn_numbers_moves_from_the_starting_fullmove() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R b KQkq - 3 10";
+        let game = test_game(fen, "black");
+        let (moves, _) = play(fen, &["Nf6", "Bb5", "Bc5"]);
+        let pgn = format_pgn(&game, "alice", "bob", &moves);
+
+        assert!(pgn.contains(&format!("[FEN \"{}\"]\n", fen)));
+        assert!(pgn.ends_with("10... Nf6 11. Bb5 Bc5 *\n"), "{}", pgn);
+    }
+
+    /// Play SAN moves from `fen`, recording them as `submit_move` would
+    fn play(fen: &str, sans: &[&str]) -> (Vec<crate::models::MoveRecord>, GameState) {
+        let mut state = GameState::from_fen(fen).unwrap();
+        let mut records: Vec<crate::models::MoveRecord> = Vec::new();
+        for san in sans {
+            let number = next_move_number(&state, records.last().map(numbered));
+            let uci = state.san_to_uci(san).unwrap();
+            let (next, stored_san) = state.make_move(&uci).unwrap();
+            let color = if state.current_turn().unwrap() == Color::White {
+                "white"
+            } else {
+                "black"
+            };
+            let mut move_record = record(number, color, &stored_san, state.fen());
+            move_record.move_uci = uci;
+            move_record.position_after = next.fen().to_string();
+            records.push(move_record);
+            state = next;
+        }
+        (records, state)
+    }
+
+    #[test]
+    fn test_stored_san_replays_through_pgn_import() {
+        // Morphy's Opera Game, with its checks, a disambiguated knight and a mate
+        let opera = [
+            "e4", "e5", "Nf3", "d6", "d4", "Bg4", "dxe5", "Bxf3", "Qxf3", "dxe5", "Bc4", "Nf6",
+            "Qb3", "Qe7", "Nc3", "c6", "Bg5", "b5", "Nxb5", "cxb5", "Bxb5+", "Nbd7", "O-O-O",
+            "Rd8", "Rxd7", "Rxd7", "Rd1", "Qe6", "Bxd7+", "Nxd7", "Qb8+", "Nxb8", "Rd8#",
+        ];
+        // Castling with check, en passant and a promotion
+        let fen = "5k2/1P6/8/8/3p4/8/4P3/4K2R w K - 0 1";
+        let endgame = ["O-O+", "Kg7", "e4", "dxe3", "b8=Q", "e2", "Qe5+"];
+
+        for (fen, sans) in [
+            (GameState::new().fen().to_string(), &opera[..]),
+            (fen.to_string(), &endgame[..]),
+        ] {
+            let (records, end) = play(&fen, sans);
+            let stored: Vec<&str> = records.iter().map(|m| m.move_san.as_str()).collect();
+            assert_eq!(stored, sans);
+
+            let game = test_game(&fen, "white");
+            let parsed =
+                crate::chess::parse_pgn(&format_pgn(&game, "alice", "bob", &records)).unwrap();
+            let replayed: Vec<&str> = parsed.moves.iter().map(|m| m.san.as_str()).collect();
+            assert_eq!(replayed, sans);
+            assert_eq!(parsed.moves.last().unwrap().fen_after, end.fen());
+        }
+    }
+
+    #[test]
+    fn test_pgn_result_to_status() {
+        assert_eq!(status_from_pgn_result("1-0"), "white_won");
+        assert_eq!(status_from_pgn_result("0-1"), "black_won");
+        assert_eq!(status_from_pgn_result("1/2-1/2"), "draw");
+        assert_eq!(status_from_pgn_result("*"), "abandoned");
+    }
+
+    #[test]
+    fn test_timeout_awards_game_to_player_not_on_move() {
+        assert_eq!(status_after_timeout("white"), "black_won");
+        assert_eq!(status_after_timeout("black"), "white_won");
+    }
+
+    #[test]
+    fn test_either_color_can_lose_on_time() {
+        let (after_e4, _) = GameState::new().make_move("e2e4").unwrap();
+        let (after_e5, _) = after_e4.make_move("e7e5").unwrap();
+        let now = Utc::now();
+
+        for (state, loser, status) in [
+            (&after_e4, "black", "white_won"),
+            (&after_e5, "white", "black_won"),
+        ] {
+            let mut game = test_game(state.fen(), loser);
+            assert_eq!(timed_out_status(&game, now), None, "deadline still ahead");
+
+            game.move_deadline = Some(now - Duration::hours(1));
+            assert_eq!(
+                timed_out_status(&game, now),
+                Some(status),
+                "{} to move",
+                loser
+            );
+
+            game.status = "draw".to_string();
+            assert_eq!(timed_out_status(&game, now), None, "already finished");
+        }
+    }
+
+    #[test]
+    fn test_game_without_moves_is_abandoned_not_timed_out() {
+        let now = Utc::now();
+        let mut game = test_game(GameState::new().fen(), "white");
+        game.move_deadline = None;
+        game.created_at = now - Duration::hours(100);
+
+        assert_eq!(timed_out_status(&game, now), None);
+        assert!(is_abandoned(&game, 48, now));
+    }
+
+    #[tokio::test]
+    async fn test_resignation_broadcasts_winner() {
+        let events = GameEvents::new();
+        let service = GameService::new(
+            72,
+            CreationPolicy::default(),
+            false,
+            None,
+            true,
+            events.clone(),
+        );
+        let game_id = Uuid::new_v4();
+        let mut rx = events.subscribe(game_id);
+
+        service.notify_status_change(
+            game_id,
+            status_after_resignation(Color::White),
+            "resignation",
+        );
+
+        match rx.recv().await.unwrap() {
+            ServerMessage::GameStatusChanged {
+                game_id: id,
+                status,
+                winner,
+                reason,
+            } => {
+                assert_eq!(id, game_id);
+                assert_eq!(status, GameStatus::BlackWon);
+                assert_eq!(winner, Some(Color::Black));
+                assert_eq!(reason, "resignation");
+            }
+            other => panic!("expected GameStatusChanged, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_corrupt_game_is_left_out_of_standings() {
+        let service = GameService::new(
+            72,
+            CreationPolicy::default(),
+            false,
+            None,
+            true,
+            GameEvents::new(),
+        );
+        let user_id = Uuid::new_v4();
+        let active_game = |fen: &str| crate::models::GameWithPlayers {
+            id: Uuid::new_v4(),
+            white_player_id: user_id,
+            black_player_id: Uuid::new_v4(),
+            white_player_username: "alice".to_string(),
+            black_player_username: "bob".to_string(),
+            current_position: fen.to_string(),
+            status: "active".to_string(),
+            current_turn: "white".to_string(),
+            move_deadline: None,
+            created_at: Utc::now(),
+        };
+        // White is a queen up in the first game; the second can't be parsed
+        let games = [
+            active_game("3qk3/8/8/8/8/8/8/3QK2Q w - - 0 1"),
+            active_game("not a position"),
+        ];
+
+        let standings = service.tally_standings(&games, user_id);
+        assert_eq!(
+            (standings.winning, standings.equal, standings.losing),
+            (1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_draw_broadcast_has_no_winner() {
+        let events = GameEvents::new();
+        let service = GameService::new(
+            72,
+            CreationPolicy::default(),
+            false,
+            None,
+            true,
+            events.clone(),
+        );
+        let game_id = Uuid::new_v4();
+        let mut rx = events.subscribe(game_id);
+
+        service.notify_status_change(game_id, "draw", GameResult::Stalemate.reason());
+
+        match rx.try_recv().unwrap() {
+            ServerMessage::GameStatusChanged { winner, reason, .. } => {
+                assert_eq!(winner, None);
+                assert_eq!(reason, "stalemate");
+            }
+            other => panic!("expected GameStatusChanged, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_review_allowed_for_both_sides_after_game_ends() {
+        assert!(check_review_access("white_won", "black", Color::White, 3, 9).is_ok());
+        assert!(check_review_access("white_won", "black", Color::Black, 4, 9).is_ok());
+        assert!(check_review_access("draw", "white", Color::Black, 0, 9).is_ok());
+    }
+
+    #[test]
+    fn test_review_rejected_during_active_game() {
+        // Not on move
+        assert!(check_review_access("active", "white", Color::Black, 4, 4).is_err());
+        // On move, but asking about an earlier position
+        assert!(check_review_access("active", "white", Color::White, 2, 4).is_err());
+        // On move, current position
+        assert!(check_review_access("active", "white", Color::White, 4, 4).is_ok());
+    }
+
+    #[test]
+    fn test_active_game_cap() {
+        assert!(has_room_for_game(2, 3));
+        // Creating past the cap is refused
+        assert!(!has_room_for_game(3, 3));
+        // Finishing a game frees a slot
+        assert!(has_room_for_game(3 - 1, 3));
+    }
+
+    #[test]
+    fn test_corrupted_move_row_fails_verification() {
+        let start = GameState::new();
+        let (after_e4, _) = start.make_move("e2e4").unwrap();
+        let (after_e5, _) = after_e4.make_move("e7e5").unwrap();
+        let game_id = Uuid::new_v4();
+
+        let intact = [("e2e4", after_e4.fen()), ("e7e5", after_e5.fen())];
+        let report = verification_report(game_id, start.fen(), &intact, after_e5.fen()).unwrap();
+        assert!(report.mismatches.is_empty());
+
+        // The second row's stored position was edited by hand
+        let tampered = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 5";
+        let corrupted = [("e2e4", after_e4.fen()), ("e7e5", tampered)];
+        let report = verification_report(game_id, start.fen(), &corrupted, tampered).unwrap();
+        assert!(!report.mismatches.is_empty());
+        assert_eq!(report.first_divergence, Some(2));
+        assert_eq!(report.plies_checked, 2);
+    }
+
+    #[test]
+    fn test_corrupt_start_position_is_a_mismatch() {
+        let start = GameState::new();
+        let (after_e4, _) = start.make_move("e2e4").unwrap();
+        let history = [("e2e4", after_e4.fen())];
+
+        let clean = verify_replay(start.fen(), &history, after_e4.fen()).unwrap();
+        assert!(clean.is_empty());
+
+        let mismatches = verify_replay("not a position", &history, after_e4.fen()).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].ply, 0);
+        assert_eq!(mismatches[0].stored_fen, "not a position");
+        assert!(mismatches[0].computed_fen.is_none());
+    }
+
+    #[test]
+    fn test_duplicate_active_game_rejected() {
+        // Duplicates allowed (the default)
+        assert!(ensure_no_duplicate_game(true, true).is_ok());
+        assert!(ensure_no_duplicate_game(true, false).is_ok());
+
+        // Duplicates forbidden
+        assert!(ensure_no_duplicate_game(false, false).is_ok());
+        assert!(matches!(
+            ensure_no_duplicate_game(false, true),
+            Err(AppError::BadRequest(msg)) if msg.contains("already have an active game")
+        ));
+    }
+
+    #[test]
+    fn test_up_a_queen_is_winning_for_that_side() {
+        let state =
+            GameState::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                .unwrap();
+        let eval = material_eval(&state.board().unwrap());
+        assert_eq!(classify_standing(eval, Color::White), Standing::Winning);
+        assert_eq!(classify_standing(eval, Color::Black), Standing::Losing);
+        assert_eq!(classify_standing(0, Color::Black), Standing::Equal);
+    }
+
+    #[test]
+    fn test_takebacks_refused_when_disabled() {
+        let game = test_game(GameState::new().fen(), "white");
+        assert!(ensure_takebacks_allowed(&game).is_err());
+    }
+
+    #[test]
+    fn test_takebacks_allowed_when_enabled() {
+        let mut game = test_game(GameState::new().fen(), "white");
+        game.takebacks_allowed = true;
+        assert!(ensure_takebacks_allowed(&game).is_ok());
+    }
+
+    #[test]
+    fn test_takeback_restores_prior_position_and_turn() {
+        // 1. e4 e5 2. Nf3, so Black is on move
+        let mut state = GameState::new();
+        let mut history = Vec::new();
+        for (i, uci) in ["e2e4", "e7e5", "g1f3"].iter().enumerate() {
+            let color = if i % 2 == 0 { "white" } else { "black" };
+            let mut mv = record(i as i32 / 2 + 1, color, "", state.fen());
+            state = state.make_move(uci).unwrap().0;
+            mv.position_after = state.fen().to_string();
+            history.push(mv);
+        }
+        let moves_played = history.len() as i64;
+
+        // White takes back Nf3 alone and is on move again
+        let plies = takeback_plies(Color::White, "black", moves_played).unwrap();
+        let removed = &history[history.len() - plies as usize..];
+        let (fen, turn) = position_before(removed).unwrap();
+        assert_eq!(fen, history[1].position_after);
+        assert_eq!(turn, "white");
+
+        // Black's e5 has been answered, so Nf3 goes too
+        let plies = takeback_plies(Color::Black, "black", moves_played).unwrap();
+        let removed = &history[history.len() - plies as usize..];
+        let (fen, turn) = position_before(removed).unwrap();
+        assert_eq!(fen, history[0].position_after);
+        assert_eq!(turn, "black");
+        assert_eq!(
+            GameState::from_fen(&fen).unwrap().current_turn().unwrap(),
+            Color::Black
+        );
+    }
+
+    #[test]
+    fn test_takeback_needs_a_move_and_the_opponent() {
+        // Black hasn't moved yet
+        assert!(takeback_plies(Color::Black, "black", 1).is_err());
+        assert!(takeback_plies(Color::White, "white", 0).is_err());
+
+        let requester = Uuid::new_v4();
+        assert!(matches!(
+            check_takeback_response(Some(requester), requester, Color::White),
+            Err(AppError::BadRequest(msg)) if msg.contains("your own")
+        ));
+        assert!(check_takeback_response(None, requester, Color::White).is_err());
+        assert_eq!(
+            check_takeback_response(Some(requester), Uuid::new_v4(), Color::White).unwrap(),
+            Color::Black
+        );
+    }
+
+    #[test]
+    fn test_creation_policy_off_allows_new_accounts() {
+        let policy = CreationPolicy::default();
+        assert!(policy.is_satisfied(Duration::zero(), 0));
+    }
+
+    #[test]
+    fn test_creation_policy_blocks_new_accounts() {
+        let policy = CreationPolicy {
+            min_account_age_hours: Some(24),
+            min_completed_games: None,
+        };
+        assert!(!policy.is_satisfied(Duration::hours(1), 0));
+        assert!(policy.is_satisfied(Duration::hours(24), 0));
+    }
+
+    #[test]
+    fn test_creation_policy_accepts_either_threshold() {
+        let policy = CreationPolicy {
+            min_account_age_hours: Some(24),
+            min_completed_games: Some(3),
+        };
+        assert!(!policy.is_satisfied(Duration::hours(1), 2));
+        assert!(policy.is_satisfied(Duration::hours(1), 3));
+        assert!(policy.is_satisfied(Duration::hours(48), 0));
+    }
+
+    fn planned(state: &GameState, move_uci: &str) -> ConditionalMove {
+        ConditionalMove {
+            id: Uuid::new_v4(),
+            game_id: Uuid::nil(),
+            user_id: Uuid::new_v4(),
+            position_fen: normalize_fen(state.fen()),
+            move_uci: move_uci.to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_two_conditional_moves_fire_after_one_opponent_move() {
+        let start = GameState::new();
+        let (after_e4, _) = start.make_move("e2e4").unwrap();
+        let (after_e5, _) = after_e4.make_move("e7e5").unwrap();
+        let plans = vec![planned(&after_e4, "e7e5"), planned(&after_e5, "g1f3")];
+
+        let chain: Vec<&str> = conditional_chain(&after_e4, &plans)
+            .iter()
+            .map(|c| c.move_uci.as_str())
+            .collect();
+        assert_eq!(chain, ["e7e5", "g1f3"]);
+    }
+
+    #[test]
+    fn test_unmatched_position_leaves_conditional_moves_alone() {
+        let start = GameState::new();
+        let (after_e4, _) = start.make_move("e2e4").unwrap();
+        let (after_d4, _) = start.make_move("d2d4").unwrap();
+        let plans = vec![planned(&after_e4, "e7e5")];
+
+        assert!(conditional_chain(&after_d4, &plans).is_empty());
+    }
+}
@@ -1,5 +1,15 @@
 pub mod auth_service;
+pub mod cache;
+pub mod challenge_service;
+pub mod explorer_service;
+pub mod game_events;
 pub mod game_service;
+pub mod tournament_service;
 
 pub use auth_service::*;
+pub use cache::*;
+pub use challenge_service::*;
+pub use explorer_service::*;
+pub use game_events::*;
 pub use game_service::*;
+pub use tournament_service::*;
@@ -0,0 +1,337 @@
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::db::{games, tournaments, users};
+use crate::error::{AppError, Result};
+use crate::models::{GameWithPlayers, Tournament, TournamentPlayer};
+use crate::services::{GameOptions, GameService};
+use shared::types::{Color, GameStatus, Outcome, TournamentFormat, TournamentStanding};
+
+/// Longest tournament name we store
+const MAX_NAME_LEN: usize = 100;
+
+/// Tournaments between club members; round-robin only for now
+#[derive(Clone)]
+pub struct TournamentService {
+    game_service: GameService,
+}
+
+impl TournamentService {
+    pub fn new(game_service: GameService) -> Self {
+        Self { game_service }
+    }
+
+    /// Create a tournament and start a game for every pairing
+    ///
+    /// All or nothing: if any pairing can't be started (a player at their
+    /// active game limit, say) no tournament or game is created.
+    pub async fn create_tournament(
+        &self,
+        pool: &PgPool,
+        created_by: Uuid,
+        name: &str,
+        participant_ids: &[Uuid],
+        format: TournamentFormat,
+    ) -> Result<Tournament> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > MAX_NAME_LEN {
+            return Err(AppError::Validation(format!(
+                "Tournament name must be 1 to {} characters",
+                MAX_NAME_LEN
+            )));
+        }
+        check_participants(participant_ids)?;
+        for &user_id in participant_ids {
+            users::find_by_id(pool, user_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Player {} not found", user_id)))?;
+        }
+
+        let pairings = match format {
+            TournamentFormat::RoundRobin => round_robin_pairings(participant_ids),
+        };
+
+        // Catch a player without room for their games before writing anything
+        if let Some(cap) = self.game_service.max_active_games_per_user() {
+            let mut active = HashMap::new();
+            for &user_id in participant_ids {
+                active.insert(user_id, games::count_active_by_user(pool, user_id).await?);
+            }
+            check_room_for_pairings(&pairings, &active, cap)?;
+        }
+
+        // The checks above can still lose a race, so any failure from here
+        // rolls the whole tournament back
+        let mut tx = pool.begin().await?;
+        let tournament =
+            tournaments::create_tournament(&mut *tx, name, &format.to_string(), created_by).await?;
+        for &user_id in participant_ids {
+            tournaments::add_participant(&mut *tx, tournament.id, user_id).await?;
+        }
+
+        for (white_id, black_id) in pairings {
+            let game = self
+                .game_service
+                .create_game_in(&mut tx, white_id, black_id, GameOptions::default())
+                .await?;
+            tournaments::add_game(&mut *tx, tournament.id, game.id).await?;
+        }
+        tx.commit().await?;
+
+        Ok(tournament)
+    }
+
+    /// A tournament and its games
+    pub async fn games(
+        &self,
+        pool: &PgPool,
+        tournament_id: Uuid,
+    ) -> Result<(Tournament, Vec<GameWithPlayers>)> {
+        let tournament = find_tournament(pool, tournament_id).await?;
+        let games = tournaments::list_games(pool, tournament_id).await?;
+
+        Ok((tournament, games))
+    }
+
+    /// A tournament and its standings, from the games finished so far
+    pub async fn standings(
+        &self,
+        pool: &PgPool,
+        tournament_id: Uuid,
+    ) -> Result<(Tournament, Vec<TournamentStanding>)> {
+        let tournament = find_tournament(pool, tournament_id).await?;
+        let players = tournaments::list_participants(pool, tournament_id).await?;
+        let games = tournaments::list_games(pool, tournament_id).await?;
+
+        Ok((tournament, tally_standings(&players, &games)))
+    }
+}
+
+async fn find_tournament(pool: &PgPool, tournament_id: Uuid) -> Result<Tournament> {
+    tournaments::find_by_id(pool, tournament_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Tournament not found".to_string()))
+}
+
+/// Check there are at least two participants and nobody is listed twice
+fn check_participants(participant_ids: &[Uuid]) -> Result<()> {
+    if participant_ids.len() < 2 {
+        return Err(AppError::Validation(
+            "A tournament needs at least two players".to_string(),
+        ));
+    }
+
+    let mut seen = participant_ids.to_vec();
+    seen.sort();
+    seen.dedup();
+    if seen.len() != participant_ids.len() {
+        return Err(AppError::Validation(
+            "Each player can only be entered once".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check each player's active games plus their tournament games stay
+/// within `cap`
+fn check_room_for_pairings(
+    pairings: &[(Uuid, Uuid)],
+    active: &HashMap<Uuid, i64>,
+    cap: i64,
+) -> Result<()> {
+    let mut games = active.clone();
+    for &(white_id, black_id) in pairings {
+        for player_id in [white_id, black_id] {
+            let count = games.entry(player_id).or_default();
+            *count += 1;
+            if *count > cap {
+                return Err(AppError::BadRequest(format!(
+                    "Player {} would go over the limit of {} active games",
+                    player_id, cap
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Every pairing of the participants once, as (white, black)
+///
+/// Colors alternate through the table so each player gets white in about
+/// half their games.
+fn round_robin_pairings(participant_ids: &[Uuid]) -> Vec<(Uuid, Uuid)> {
+    let mut pairings = Vec::new();
+    for (i, &first) in participant_ids.iter().enumerate() {
+        for (j, &second) in participant_ids.iter().enumerate().skip(i + 1) {
+            if (i + j) % 2 == 0 {
+                pairings.push((second, first));
+            } else {
+                pairings.push((first, second));
+            }
+        }
+    }
+    pairings
+}
+
+/// Standings from the finished games: 1 point a win, half a draw
+///
+/// Leaders first; ties keep the participants' username order.
+fn tally_standings(
+    players: &[TournamentPlayer],
+    games: &[GameWithPlayers],
+) -> Vec<TournamentStanding> {
+    let mut standings: Vec<TournamentStanding> = players
+        .iter()
+        .map(|player| TournamentStanding {
+            user_id: player.user_id,
+            username: player.username.clone(),
+            points: 0.0,
+            played: 0,
+            won: 0,
+            drawn: 0,
+            lost: 0,
+        })
+        .collect();
+    let index: HashMap<Uuid, usize> = standings
+        .iter()
+        .enumerate()
+        .map(|(i, standing)| (standing.user_id, i))
+        .collect();
+
+    for game in games {
+        let Ok(status) = game.status.parse::<GameStatus>() else {
+            continue;
+        };
+        for (user_id, color) in [
+            (game.white_player_id, Color::White),
+            (game.black_player_id, Color::Black),
+        ] {
+            let Some(outcome) = status.outcome_for(color) else {
+                continue;
+            };
+            let Some(&i) = index.get(&user_id) else {
+                continue;
+            };
+            let standing = &mut standings[i];
+            standing.played += 1;
+            match outcome {
+                Outcome::Won => {
+                    standing.won += 1;
+                    standing.points += 1.0;
+                }
+                Outcome::Draw => {
+                    standing.drawn += 1;
+                    standing.points += 0.5;
+                }
+                Outcome::Lost => standing.lost += 1,
+            }
+        }
+    }
+
+    standings.sort_by(|a, b| b.points.total_cmp(&a.points));
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn players(names: &[&str]) -> Vec<TournamentPlayer> {
+        names
+            .iter()
+            .map(|name| TournamentPlayer {
+                user_id: Uuid::new_v4(),
+                username: name.to_string(),
+            })
+            .collect()
+    }
+
+    fn game(white: &TournamentPlayer, black: &TournamentPlayer, status: &str) -> GameWithPlayers {
+        GameWithPlayers {
+            id: Uuid::new_v4(),
+            white_player_id: white.user_id,
+            black_player_id: black.user_id,
+            white_player_username: white.username.clone(),
+            black_player_username: black.username.clone(),
+            current_position: String::new(),
+            status: status.to_string(),
+            current_turn: "white".to_string(),
+            move_deadline: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_tournament_over_active_game_cap_is_refused() {
+        let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        let pairings = round_robin_pairings(&ids);
+        let mut active: HashMap<Uuid, i64> = ids.iter().map(|&id| (id, 0)).collect();
+
+        // Four players means three games each
+        assert!(check_room_for_pairings(&pairings, &active, 3).is_ok());
+
+        active.insert(ids[2], 1);
+        assert!(matches!(
+            check_room_for_pairings(&pairings, &active, 3),
+            Err(AppError::BadRequest(msg)) if msg.contains(&ids[2].to_string())
+        ));
+        assert!(check_room_for_pairings(&pairings, &active, 4).is_ok());
+    }
+
+    #[test]
+    fn test_round_robin_pairs_everyone_once() {
+        for n in 2..=7 {
+            let ids: Vec<Uuid> = (0..n).map(|_| Uuid::new_v4()).collect();
+            let pairings = round_robin_pairings(&ids);
+            assert_eq!(pairings.len(), n * (n - 1) / 2);
+
+            let mut pairs: Vec<(Uuid, Uuid)> = pairings
+                .iter()
+                .map(|&(white, black)| (white.min(black), white.max(black)))
+                .collect();
+            pairs.sort();
+            pairs.dedup();
+            assert_eq!(pairs.len(), pairings.len(), "{} players", n);
+            assert!(pairings.iter().all(|(white, black)| white != black));
+        }
+    }
+
+    #[test]
+    fn test_participants_must_be_two_or_more_and_distinct() {
+        let alice = Uuid::new_v4();
+        assert!(check_participants(&[alice]).is_err());
+        assert!(check_participants(&[alice, alice]).is_err());
+        assert!(check_participants(&[alice, Uuid::new_v4()]).is_ok());
+    }
+
+    #[test]
+    fn test_standings_update_as_games_finish() {
+        let field = players(&["alice", "bob", "carol"]);
+        let (alice, bob, carol) = (&field[0], &field[1], &field[2]);
+        let mut games = vec![
+            game(alice, bob, "active"),
+            game(bob, carol, "active"),
+            game(carol, alice, "active"),
+        ];
+
+        let standings = tally_standings(&field, &games);
+        assert!(standings.iter().all(|s| s.points == 0.0 && s.played == 0));
+
+        games[0].status = "white_won".to_string();
+        games[1].status = "draw".to_string();
+        let standings = tally_standings(&field, &games);
+        let summary: Vec<(&str, f64, u32)> = standings
+            .iter()
+            .map(|s| (s.username.as_str(), s.points, s.played))
+            .collect();
+        assert_eq!(
+            summary,
+            [("alice", 1.0, 1), ("bob", 0.5, 2), ("carol", 0.5, 1)]
+        );
+        assert_eq!((standings[1].drawn, standings[1].lost), (1, 1));
+    }
+}
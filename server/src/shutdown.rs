@@ -0,0 +1,100 @@
+use tokio::sync::watch;
+
+/// Tells background tasks to stop
+pub struct ShutdownTrigger(watch::Sender<bool>);
+
+/// Lets a background task find out the server is stopping
+#[derive(Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+/// A trigger and the receiver to hand (cloned) to each background task
+pub fn channel() -> (ShutdownTrigger, Shutdown) {
+    let (tx, rx) = watch::channel(false);
+    (ShutdownTrigger(tx), Shutdown(rx))
+}
+
+impl ShutdownTrigger {
+    pub fn trigger(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+impl Shutdown {
+    /// Resolves once shutdown is triggered (or the trigger is gone)
+    pub async fn requested(&mut self) {
+        let _ = self.0.wait_for(|&stopping| stopping).await;
+    }
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM on Unix
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {:?}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_requested_resolves_when_triggered() {
+        let (trigger, shutdown) = channel();
+
+        let mut waiting = shutdown.clone();
+        let task = tokio::spawn(async move { waiting.requested().await });
+
+        // Nothing happens until the trigger fires
+        let mut early = shutdown.clone();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), early.requested())
+                .await
+                .is_err()
+        );
+
+        trigger.trigger();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("task should stop after shutdown is triggered")
+            .unwrap();
+
+        // Receivers that start waiting afterwards return straight away
+        let mut late = shutdown;
+        late.requested().await;
+    }
+
+    #[tokio::test]
+    async fn test_requested_resolves_when_trigger_dropped() {
+        let (trigger, mut shutdown) = channel();
+        drop(trigger);
+
+        tokio::time::timeout(Duration::from_secs(1), shutdown.requested())
+            .await
+            .unwrap();
+    }
+}
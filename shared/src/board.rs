@@ -0,0 +1,216 @@
+use chess::{Board, ChessMove, Color, File, Piece, Rank, Square};
+use std::str::FromStr;
+
+/// Characters drawn either side of the squares the last move came from and
+/// went to. Plain characters rather than colors, so any terminal shows them.
+pub const LAST_MOVE_MARKERS: (char, char) = ('[', ']');
+
+/// Characters drawn either side of a king in check
+pub const CHECK_MARKERS: (char, char) = ('!', '!');
+
+/// Drawn between two neighbouring marked squares, which share a gap
+const SHARED_MARKER: char = '|';
+
+/// Render a FEN position as a Unicode board, White at the bottom
+pub fn render_ascii(fen: &str) -> Result<String, chess::Error> {
+    Ok(render_board(&Board::from_str(fen)?))
+}
+
+/// Render a board as a Unicode grid with coordinate labels
+pub fn render_board(board: &Board) -> String {
+    render_board_oriented(board, false)
+}
+
+/// Render a board from White's side, or from Black's when `flip` is set
+///
+/// Empty light squares are shown as `·` so the checkered pattern stays
+/// readable in a terminal.
+pub fn render_board_oriented(board: &Board, flip: bool) -> String {
+    render_with_markers(board, flip, &[])
+}
+
+/// Render a board like `render_board_oriented`, marking the squares of
+/// `last_move` and the king of the side to move if it's in check
+pub fn render_board_highlighted(board: &Board, flip: bool, last_move: Option<ChessMove>) -> String {
+    let mut marked = Vec::new();
+    if let Some(last_move) = last_move {
+        marked.push((last_move.get_source(), LAST_MOVE_MARKERS));
+        marked.push((last_move.get_dest(), LAST_MOVE_MARKERS));
+    }
+    if board.checkers().popcnt() > 0 {
+        // Check wins over the last move, which usually gave it
+        let king = board.king_square(board.side_to_move());
+        marked.retain(|(square, _)| *square != king);
+        marked.push((king, CHECK_MARKERS));
+    }
+
+    render_with_markers(board, flip, &marked)
+}
+
+/// Render a board, drawing each square's markers in the gaps either side of it
+fn render_with_markers(board: &Board, flip: bool, marked: &[(Square, (char, char))]) -> String {
+    let markers = |square: Square| {
+        marked
+            .iter()
+            .find(|(marked_square, _)| *marked_square == square)
+            .map(|(_, markers)| *markers)
+    };
+
+    let (ranks, files): (Vec<usize>, Vec<usize>) = if flip {
+        ((0..8).collect(), (0..8).rev().collect())
+    } else {
+        ((0..8).rev().collect(), (0..8).collect())
+    };
+    let labels = if flip {
+        "  h g f e d c b a\n"
+    } else {
+        "  a b c d e f g h\n"
+    };
+
+    let mut out = String::from(labels);
+
+    for &rank in &ranks {
+        out.push_str(&format!("{}", rank + 1));
+
+        let mut previous_right = None;
+        for &file in &files {
+            let square = Square::make_square(Rank::from_index(rank), File::from_index(file));
+            let (left, right) = markers(square).unzip();
+
+            // The gap before this square
+            out.push(match (previous_right, left) {
+                (Some(_), Some(_)) => SHARED_MARKER,
+                (Some(marker), None) | (None, Some(marker)) => marker,
+                (None, None) => ' ',
+            });
+            previous_right = right;
+
+            let piece_str = if let Some(piece) = board.piece_on(square) {
+                let color = board.color_on(square).unwrap();
+                piece_to_unicode(piece, color)
+            } else {
+                // Checkered pattern
+                if (rank + file) % 2 == 0 {
+                    "·"
+                } else {
+                    " "
+                }
+            };
+
+            out.push_str(piece_str);
+        }
+
+        out.push(previous_right.unwrap_or(' '));
+        out.push_str(&format!("{}\n", rank + 1));
+    }
+
+    out.push_str(labels);
+    out
+}
+
+/// Convert a piece to Unicode character
+fn piece_to_unicode(piece: Piece, color: Color) -> &'static str {
+    match (piece, color) {
+        (Piece::King, Color::White) => "♔",
+        (Piece::Queen, Color::White) => "♕",
+        (Piece::Rook, Color::White) => "♖",
+        (Piece::Bishop, Color::White) => "♗",
+        (Piece::Knight, Color::White) => "♘",
+        (Piece::Pawn, Color::White) => "♙",
+        (Piece::King, Color::Black) => "♚",
+        (Piece::Queen, Color::Black) => "♛",
+        (Piece::Rook, Color::Black) => "♜",
+        (Piece::Bishop, Color::Black) => "♝",
+        (Piece::Knight, Color::Black) => "♞",
+        (Piece::Pawn, Color::Black) => "♟",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_start_position() {
+        let rendered =
+            render_ascii("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        let expected = "  a b c d e f g h
+8 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜ 8
+7 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟ 7
+6   ·   ·   ·   · 6
+5 ·   ·   ·   ·   5
+4   ·   ·   ·   · 4
+3 ·   ·   ·   ·   3
+2 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙ 2
+1 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖ 1
+  a b c d e f g h
+";
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_render_flipped_start_position() {
+        let board =
+            Board::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        // Squares keep their colors: h1 is still light, a1 still dark
+        let expected = "  h g f e d c b a
+1 ♖ ♘ ♗ ♔ ♕ ♗ ♘ ♖ 1
+2 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙ 2
+3   ·   ·   ·   · 3
+4 ·   ·   ·   ·   4
+5   ·   ·   ·   · 5
+6 ·   ·   ·   ·   6
+7 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟ 7
+8 ♜ ♞ ♝ ♚ ♛ ♝ ♞ ♜ 8
+  h g f e d c b a
+";
+        assert_eq!(render_board_oriented(&board, true), expected);
+    }
+
+    #[test]
+    fn test_render_highlights_last_move() {
+        let board =
+            Board::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        let e4 = ChessMove::from_str("e2e4").unwrap();
+
+        let rendered = render_board_highlighted(&board, false, Some(e4));
+        assert!(rendered.contains("\n4   ·   ·[♙]·   · 4\n"), "{}", rendered);
+        assert!(rendered.contains("\n2 ♙ ♙ ♙ ♙[ ]♙ ♙ ♙ 2\n"), "{}", rendered);
+
+        // Without a last move it matches the plain rendering
+        assert_eq!(
+            render_board_highlighted(&board, false, None),
+            render_board_oriented(&board, false)
+        );
+    }
+
+    #[test]
+    fn test_render_highlights_king_in_check() {
+        // 1. f3 e5 2. g4 Qh4#, the queen's move next to the king
+        let board =
+            Board::from_str("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        let qh4 = ChessMove::from_str("d8h4").unwrap();
+
+        let rendered = render_board_highlighted(&board, false, Some(qh4));
+        assert!(rendered.contains("\n1 ♖ ♘ ♗ ♕!♔!♗ ♘ ♖ 1\n"), "{}", rendered);
+        assert!(rendered.contains("\n8 ♜ ♞ ♝[·]♚ ♝ ♞ ♜ 8\n"), "{}", rendered);
+        assert!(rendered.contains("\n4   ·   ·   · ♙[♛]4\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_render_neighbouring_marks_share_a_gap() {
+        let board = Board::from_str("4k3/8/8/8/8/8/8/5K2 b - - 1 1").unwrap();
+        let kf1 = ChessMove::from_str("e1f1").unwrap();
+
+        let rendered = render_board_highlighted(&board, false, Some(kf1));
+        assert!(rendered.contains("\n1 ·   ·  [·|♔]·   1\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_fen() {
+        assert!(render_ascii("not a fen").is_err());
+    }
+}
@@ -1,2 +1,7 @@
 pub mod types;
 pub mod protocol;
+
+#[cfg(feature = "board")]
+pub mod board;
+#[cfg(feature = "board")]
+pub mod notation;
@@ -0,0 +1,371 @@
+use chess::{Board, ChessMove, Piece, File, Rank, MoveGen};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SanError {
+    #[error("Illegal move cannot be converted to SAN")]
+    IllegalMove,
+
+    #[error("Empty SAN move")]
+    Empty,
+
+    #[error("No legal move matches '{0}'")]
+    NoMatch(String),
+
+    /// The move is missing a disambiguating file or rank, e.g. "Nd2" when
+    /// both knights can go there
+    #[error("'{san}' is ambiguous: {}", candidates.join(", "))]
+    Ambiguous { san: String, candidates: Vec<String> },
+}
+
+type Result<T> = std::result::Result<T, SanError>;
+
+/// Convert a ChessMove to Standard Algebraic Notation (SAN)
+pub fn move_to_san(board: &Board, chess_move: ChessMove) -> Result<String> {
+    format_san(board, chess_move, true)
+}
+
+/// SAN of a legal move, optionally without the file/rank that tells apart
+/// two pieces of the same type
+fn format_san(board: &Board, chess_move: ChessMove, disambiguate: bool) -> Result<String> {
+    if !board.legal(chess_move) {
+        return Err(SanError::IllegalMove);
+    }
+
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let promotion = chess_move.get_promotion();
+
+    // Get the piece being moved
+    let piece = board.piece_on(source).ok_or(SanError::IllegalMove)?;
+
+    // Check for castling
+    if piece == Piece::King && source.get_file() == File::E {
+        if dest.get_file() == File::G {
+            return Ok(format!("O-O{}", check_suffix(board, chess_move))); // Kingside castling
+        } else if dest.get_file() == File::C {
+            return Ok(format!("O-O-O{}", check_suffix(board, chess_move))); // Queenside castling
+        }
+    }
+
+    let mut san = String::new();
+
+    // Add piece letter (except for pawns)
+    if piece != Piece::Pawn {
+        san.push(piece_to_char(piece));
+    }
+
+    // Disambiguate if necessary (for pieces other than pawns and kings)
+    if disambiguate && piece != Piece::Pawn && piece != Piece::King {
+        let disambiguation = get_disambiguation(board, chess_move)?;
+        san.push_str(&disambiguation);
+    }
+
+    // Capture notation
+    let is_capture = board.piece_on(dest).is_some();
+    if is_capture {
+        if piece == Piece::Pawn {
+            // Pawn captures include the source file
+            san.push(file_to_char(source.get_file()));
+        }
+        san.push('x');
+    } else if piece == Piece::Pawn && source.get_file() != dest.get_file() {
+        // En passant
+        san.push(file_to_char(source.get_file()));
+        san.push('x');
+    }
+
+    // Destination square
+    san.push(file_to_char(dest.get_file()));
+    san.push(rank_to_char(dest.get_rank()));
+
+    // Promotion
+    if let Some(promo_piece) = promotion {
+        san.push('=');
+        san.push(piece_to_char(promo_piece));
+    }
+
+    san.push_str(check_suffix(board, chess_move));
+
+    Ok(san)
+}
+
+/// "#" if the move mates, "+" if it checks, otherwise nothing
+fn check_suffix(board: &Board, chess_move: ChessMove) -> &'static str {
+    let new_board = board.make_move_new(chess_move);
+    match new_board.status() {
+        chess::BoardStatus::Checkmate => "#",
+        chess::BoardStatus::Ongoing if new_board.checkers().popcnt() > 0 => "+",
+        _ => "",
+    }
+}
+
+/// Parse a SAN move (e.g. "Nf3", "exd6", "O-O", "e8=Q+") in the given position
+///
+/// This is the inverse of `move_to_san`: the input is matched against the SAN
+/// of every legal move. Check/mate markers, annotations, an "e.p." suffix and
+/// the '=' before a promotion piece are optional, and castling may be written
+/// with zeros. A piece move that leaves out a needed file or rank is
+/// rejected as ambiguous, listing the moves it could mean.
+pub fn san_to_move(board: &Board, san: &str) -> Result<ChessMove> {
+    let wanted = normalize_san(san);
+    if wanted.is_empty() {
+        return Err(SanError::Empty);
+    }
+
+    for chess_move in MoveGen::new_legal(board) {
+        if normalize_san(&move_to_san(board, chess_move)?) == wanted {
+            return Ok(chess_move);
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for chess_move in MoveGen::new_legal(board) {
+        if normalize_san(&format_san(board, chess_move, false)?) == wanted {
+            candidates.push(move_to_san(board, chess_move)?);
+        }
+    }
+
+    if candidates.len() > 1 {
+        candidates.sort();
+        return Err(SanError::Ambiguous {
+            san: san.trim().to_string(),
+            candidates,
+        });
+    }
+
+    Err(SanError::NoMatch(san.to_string()))
+}
+
+/// Reduce SAN to the parts that identify a move
+fn normalize_san(san: &str) -> String {
+    let san = san.trim();
+    let san = san.strip_suffix("e.p.").unwrap_or(san).trim_end();
+
+    san.trim_end_matches(['+', '#', '!', '?'])
+        .chars()
+        .filter(|&c| c != '=')
+        .map(|c| if c == '0' { 'O' } else { c })
+        .collect()
+}
+
+/// Get disambiguation string for a move (file, rank, or both)
+fn get_disambiguation(board: &Board, chess_move: ChessMove) -> Result<String> {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let piece = board.piece_on(source).ok_or(SanError::IllegalMove)?;
+
+    // Find all pieces of the same type that can move to the destination
+    let mut same_type_moves = Vec::new();
+    for m in MoveGen::new_legal(board) {
+        if m.get_dest() == dest {
+            if let Some(p) = board.piece_on(m.get_source()) {
+                if p == piece && m.get_source() != source {
+                    same_type_moves.push(m);
+                }
+            }
+        }
+    }
+
+    if same_type_moves.is_empty() {
+        return Ok(String::new());
+    }
+
+    // Check if file disambiguation is enough
+    let same_file = same_type_moves.iter()
+        .any(|m| m.get_source().get_file() == source.get_file());
+
+    // Check if rank disambiguation is enough
+    let same_rank = same_type_moves.iter()
+        .any(|m| m.get_source().get_rank() == source.get_rank());
+
+    if !same_file {
+        // File is unique
+        Ok(file_to_char(source.get_file()).to_string())
+    } else if !same_rank {
+        // Rank is unique
+        Ok(rank_to_char(source.get_rank()).to_string())
+    } else {
+        // Need both file and rank
+        Ok(format!(
+            "{}{}",
+            file_to_char(source.get_file()),
+            rank_to_char(source.get_rank())
+        ))
+    }
+}
+
+fn piece_to_char(piece: Piece) -> char {
+    match piece {
+        Piece::King => 'K',
+        Piece::Queen => 'Q',
+        Piece::Rook => 'R',
+        Piece::Bishop => 'B',
+        Piece::Knight => 'N',
+        Piece::Pawn => ' ', // Not used, but included for completeness
+    }
+}
+
+fn file_to_char(file: File) -> char {
+    match file {
+        File::A => 'a',
+        File::B => 'b',
+        File::C => 'c',
+        File::D => 'd',
+        File::E => 'e',
+        File::F => 'f',
+        File::G => 'g',
+        File::H => 'h',
+    }
+}
+
+fn rank_to_char(rank: Rank) -> char {
+    match rank {
+        Rank::First => '1',
+        Rank::Second => '2',
+        Rank::Third => '3',
+        Rank::Fourth => '4',
+        Rank::Fifth => '5',
+        Rank::Sixth => '6',
+        Rank::Seventh => '7',
+        Rank::Eighth => '8',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_pawn_move_to_san() {
+        let board = Board::default();
+        let chess_move = ChessMove::from_str("e2e4").unwrap();
+        let san = move_to_san(&board, chess_move).unwrap();
+        assert_eq!(san, "e4");
+    }
+
+    #[test]
+    fn test_knight_move_to_san() {
+        let board = Board::default();
+        let chess_move = ChessMove::from_str("g1f3").unwrap();
+        let san = move_to_san(&board, chess_move).unwrap();
+        assert_eq!(san, "Nf3");
+    }
+
+    #[test]
+    fn test_castling_kingside() {
+        // Position after 1.e4 e5 2.Nf3 Nf6 3.Bc4 Bc5
+        let fen = "rnbqk2r/pppp1ppp/5n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        let board = Board::from_str(fen).unwrap();
+        let chess_move = ChessMove::from_str("e1g1").unwrap();
+        let san = move_to_san(&board, chess_move).unwrap();
+        assert_eq!(san, "O-O");
+    }
+
+    #[test]
+    fn test_castling_with_check() {
+        // The rook lands on f1, facing the king on f8
+        let fen = "5k2/8/8/8/8/8/8/4K2R w K - 0 1";
+        assert_eq!(san_of(fen, "e1g1"), "O-O+");
+        // Queenside, the rook checks down the d-file
+        let fen = "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1";
+        assert_eq!(san_of(fen, "e1c1"), "O-O-O+");
+    }
+
+    fn san_of(fen: &str, uci: &str) -> String {
+        let board = Board::from_str(fen).unwrap();
+        move_to_san(&board, ChessMove::from_str(uci).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_under_promotion_to_san() {
+        let fen = "8/4P3/7k/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(san_of(fen, "e7e8q"), "e8=Q");
+        assert_eq!(san_of(fen, "e7e8r"), "e8=R");
+        assert_eq!(san_of(fen, "e7e8b"), "e8=B");
+        assert_eq!(san_of(fen, "e7e8n"), "e8=N");
+    }
+
+    #[test]
+    fn test_capturing_promotion_to_san() {
+        // The new queen on c8 checks the king along the back rank
+        let fen = "2r2k2/3P4/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(san_of(fen, "d7c8q"), "dxc8=Q+");
+        assert_eq!(san_of(fen, "d7c8n"), "dxc8=N");
+        // Pushing instead gives check from d8 too
+        assert_eq!(san_of(fen, "d7d8r"), "d8=R+");
+    }
+
+    /// Every legal move in `fen` should survive a SAN round trip
+    fn assert_round_trip(fen: &str) {
+        let board = Board::from_str(fen).unwrap();
+        for chess_move in MoveGen::new_legal(&board) {
+            let san = move_to_san(&board, chess_move).unwrap();
+            assert_eq!(
+                san_to_move(&board, &san).unwrap(),
+                chess_move,
+                "{} in {}",
+                san,
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_san_round_trip() {
+        // Start position
+        assert_round_trip("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        // Both castling options
+        assert_round_trip("r3k2r/pppq1ppp/2npbn2/4p3/4P3/2NPBN2/PPPQ1PPP/R3K2R w KQkq - 0 1");
+        // En passant available (exd6)
+        assert_round_trip("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+        // Promotions, including with capture
+        assert_round_trip("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        // Rooks and knights needing file/rank disambiguation
+        assert_round_trip("4k3/8/8/R6R/8/8/8/N1N1K2N w - - 0 1");
+    }
+
+    #[test]
+    fn test_san_to_move_lenient_input() {
+        let board = Board::from_str("4k3/P7/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(
+            san_to_move(&board, "0-0").unwrap(),
+            ChessMove::from_str("e1g1").unwrap()
+        );
+        assert_eq!(
+            san_to_move(&board, "a8Q+").unwrap(),
+            ChessMove::from_str("a7a8q").unwrap()
+        );
+        assert_eq!(
+            san_to_move(&board, "a8=N").unwrap(),
+            ChessMove::from_str("a7a8n").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_san_to_move_ambiguous() {
+        // Knights on b1 and f3 can both reach d2
+        let board = Board::from_str("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1").unwrap();
+
+        assert_eq!(
+            san_to_move(&board, "Nd2+"),
+            Err(SanError::Ambiguous {
+                san: "Nd2+".to_string(),
+                candidates: vec!["Nbd2".to_string(), "Nfd2".to_string()],
+            })
+        );
+        assert_eq!(
+            san_to_move(&board, "Nbd2").unwrap(),
+            ChessMove::from_str("b1d2").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_san_to_move_rejects_illegal() {
+        let board = Board::default();
+        assert!(san_to_move(&board, "e5").is_err());
+        assert!(san_to_move(&board, "Nf6").is_err());
+        assert!(san_to_move(&board, "").is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::types::{TimeControl, TournamentFormat};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegisterRequest {
     pub username: String,
@@ -14,15 +16,89 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogoutRequest {
+    #[serde(default)]
+    pub refresh_token: Option<String>, // Revoked if given
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEmailRequest {
+    pub new_email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmEmailRequest {
+    pub token: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateGameRequest {
     pub opponent_username: String,
     pub player_color: Option<String>, // "white", "black", or None for random
+    #[serde(default)]
+    pub takebacks_allowed: Option<bool>, // None uses the server default
+    #[serde(default)]
+    pub time_control: Option<TimeControl>, // None uses the server default
+    #[serde(default)]
+    pub is_public: bool, // let spectators watch
+    #[serde(default)]
+    pub starting_fen: Option<String>, // None starts from the standard position
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RespondTakebackRequest {
+    pub accept: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPgnRequest {
+    pub pgn: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubmitMoveRequest {
+    #[serde(default)]
     pub move_uci: String, // e.g., "e2e4", "e1g1" (castling), "e7e8q" (promotion)
+    #[serde(default)]
+    pub move_san: Option<String>, // e.g., "Nf3", "O-O", "e8=Q"; used instead of move_uci when set
+    #[serde(default)]
+    pub dry_run: bool, // validate and return the would-be result without saving
+    #[serde(default)]
+    pub comment: Option<String>, // note on the move, e.g. for teaching games
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClaimDrawRequest {
+    #[serde(default)]
+    pub move_uci: Option<String>, // move to play first, if the claim is about the position it reaches
+    #[serde(default)]
+    pub move_san: Option<String>, // the same in SAN; used instead of move_uci when set
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetConditionalMoveRequest {
+    pub position_fen: String, // position to answer, with you to move
+    pub move_uci: String,     // reply to play automatically if the game gets there
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTournamentRequest {
+    pub name: String,
+    pub participant_ids: Vec<Uuid>, // at least two
+    #[serde(default)]
+    pub format: TournamentFormat, // round_robin if not given
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
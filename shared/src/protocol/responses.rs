@@ -1,11 +1,25 @@
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::types::{Game, GameInfo, Move, User, UserProfile};
+use chrono::{DateTime, Utc};
+
+use crate::types::{
+    ChallengeInfo, Color, Game, GameInfo, Move, PastGameInfo, TournamentInfo, TournamentStanding,
+    User, UserProfile, UserStats,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthResponse {
     pub token: String,
+    /// Single-use token for `POST /api/auth/refresh`
+    pub refresh_token: String,
+    pub user: User,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailChangeResponse {
     pub user: User,
+    pub pending_email: Option<String>, // set while the new address awaits confirmation
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,22 +28,83 @@ pub struct GameResponse {
     pub white_player: UserProfile,
     pub black_player: UserProfile,
     pub moves: Vec<Move>,
+    #[serde(default)]
+    pub your_color: Option<Color>, // None for spectators
+    #[serde(default)]
+    pub white_captured: Vec<String>, // Black pieces White has taken, e.g. "queen", best first
+    #[serde(default)]
+    pub black_captured: Vec<String>, // White pieces Black has taken
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameListResponse {
     pub games: Vec<GameInfo>,
+    pub total_count: i64, // across all pages
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameHistoryResponse {
+    pub games: Vec<PastGameInfo>, // most recently finished first
+    pub total_count: i64,         // across all pages
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeListResponse {
+    pub incoming: Vec<ChallengeInfo>, // pending challenges sent to you
+    pub outgoing: Vec<ChallengeInfo>, // pending challenges you sent
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentGamesResponse {
+    pub tournament: TournamentInfo,
+    pub games: Vec<GameInfo>, // in pairing order
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentStandingsResponse {
+    pub tournament: TournamentInfo,
+    pub standings: Vec<TournamentStanding>, // leader first
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoveResponse {
     pub r#move: Move,
     pub game: Game,
+    /// The move put the opponent in check (or mated them)
+    pub gives_check: bool,
+    /// Legal replies the opponent has; 0 after mate or stalemate
+    pub opponent_legal_move_count: usize,
+    #[serde(default)]
+    pub your_color: Option<Color>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoveListResponse {
     pub moves: Vec<Move>,
+    pub total_count: i64, // moves in the whole game, including any skipped by `since`
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveEvaluation {
+    pub move_number: i32,
+    pub color: Color,
+    pub eval_centipawns: i32, // material after the move, positive when White is ahead
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisResponse {
+    pub moves: Vec<MoveEvaluation>, // one per move, in play order
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalMoveInfo {
+    pub position_fen: String, // without move counters
+    pub move_uci: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalMoveListResponse {
+    pub moves: Vec<ConditionalMoveInfo>, // your planned replies, oldest first
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,13 +112,95 @@ pub struct UserListResponse {
     pub users: Vec<UserProfile>,
 }
 
+/// Another player's public profile; email stays private
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserProfileResponse {
+    pub username: String,
+    pub member_since: DateTime<Utc>,
+    #[serde(default)]
+    pub online: bool, // seen in the last few minutes
+    pub stats: UserStats,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PgnResponse {
     pub pgn: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardResponse {
+    pub ascii: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplorerMove {
+    pub move_uci: String,
+    pub move_san: String,
+    pub games: u32,
+    pub white_win_pct: f64,
+    pub draw_pct: f64,
+    pub black_win_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplorerResponse {
+    pub fen: String,
+    pub total_games: u32,
+    pub moves: Vec<ExplorerMove>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionMismatch {
+    pub ply: usize,
+    pub move_uci: String,
+    pub stored_fen: String,
+    pub computed_fen: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameVerificationResponse {
+    pub game_id: Uuid,
+    pub plies_checked: usize,
+    pub first_divergence: Option<usize>,
+    pub mismatches: Vec<PositionMismatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameAuditResponse {
+    pub games_checked: usize,
+    /// Games whose stored positions don't match a replay of their moves
+    pub corrupted: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingsResponse {
+    pub winning: u32,
+    pub equal: u32,
+    pub losing: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalMovesResponse {
+    pub moves: Vec<String>, // UCI
+    #[serde(default)]
+    pub san: Vec<String>, // the same moves in SAN, in the same order
+    pub from_square: Option<String>, // the square the moves were filtered to, if any
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewResponse {
+    pub ply: usize,
+    pub fen: String,
+    pub side_to_move: Color,
+    pub legal_moves_san: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
     pub details: Option<String>,
+    /// Stable error code, e.g. "not_found" or "illegal_move"
+    #[serde(default)]
+    pub code: String,
 }
@@ -37,9 +37,21 @@ pub enum ServerMessage {
         time_remaining: Duration,
     },
     ChallengeReceived {
-        game_id: Uuid,
+        challenge_id: Uuid,
         opponent: String,
-        your_color: Color,
+        your_color: Option<Color>, // None if colors are picked at random on accepting
+    },
+    ChallengeExpired {
+        challenge_id: Uuid,
+    },
+    TakebackRequested {
+        game_id: Uuid,
+        requested_by: Color,
+    },
+    TakebackAnswered {
+        game_id: Uuid,
+        accepted: bool,
+        position_fen: String, // the position after the answer
     },
     Pong,
     Error {
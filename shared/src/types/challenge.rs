@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::types::{Color, TimeControl};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeStatus {
+    Pending,
+    Accepted,
+    Declined,
+}
+
+impl std::fmt::Display for ChallengeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChallengeStatus::Pending => write!(f, "pending"),
+            ChallengeStatus::Accepted => write!(f, "accepted"),
+            ChallengeStatus::Declined => write!(f, "declined"),
+        }
+    }
+}
+
+impl FromStr for ChallengeStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(ChallengeStatus::Pending),
+            "accepted" => Ok(ChallengeStatus::Accepted),
+            "declined" => Ok(ChallengeStatus::Declined),
+            _ => Err(format!("Invalid challenge status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeInfo {
+    pub id: Uuid,
+    pub challenger_username: String,
+    pub challenged_username: String,
+    pub color_preference: Option<Color>, // the challenger's color; None for random
+    pub time_control: Option<TimeControl>,
+    pub status: ChallengeStatus,
+    pub game_id: Option<Uuid>, // set once accepted
+    pub created_at: DateTime<Utc>,
+}
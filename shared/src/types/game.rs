@@ -38,6 +38,36 @@ pub enum GameStatus {
     Abandoned,
 }
 
+impl GameStatus {
+    /// The side that won, for decided games
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            GameStatus::WhiteWon => Some(Color::White),
+            GameStatus::BlackWon => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    /// How the game went for the player of `color`, once it's decided
+    pub fn outcome_for(&self, color: Color) -> Option<Outcome> {
+        match (self, self.winner()) {
+            (_, Some(winner)) if winner == color => Some(Outcome::Won),
+            (_, Some(_)) => Some(Outcome::Lost),
+            (GameStatus::Draw, None) => Some(Outcome::Draw),
+            _ => None,
+        }
+    }
+}
+
+/// A finished game's result from one player's side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Won,
+    Lost,
+    Draw,
+}
+
 impl std::fmt::Display for GameStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -65,6 +95,51 @@ impl FromStr for GameStatus {
     }
 }
 
+/// Correspondence pace presets a game can be created with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeControl {
+    /// One day per move
+    Daily1,
+    /// Three days per move
+    Daily3,
+    /// A week per move
+    Daily7,
+}
+
+impl TimeControl {
+    pub fn hours(&self) -> i64 {
+        match self {
+            TimeControl::Daily1 => 24,
+            TimeControl::Daily3 => 72,
+            TimeControl::Daily7 => 168,
+        }
+    }
+}
+
+impl std::fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeControl::Daily1 => write!(f, "daily1"),
+            TimeControl::Daily3 => write!(f, "daily3"),
+            TimeControl::Daily7 => write!(f, "daily7"),
+        }
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "daily1" => Ok(TimeControl::Daily1),
+            "daily3" => Ok(TimeControl::Daily3),
+            "daily7" => Ok(TimeControl::Daily7),
+            _ => Err(format!("Invalid time control: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     pub id: Uuid,
@@ -76,6 +151,16 @@ pub struct Game {
     pub move_deadline: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub takebacks_allowed: bool,
+    /// Hours each player has per move
+    pub move_deadline_hours: i64,
+    /// Anyone may watch the game, not just the players
+    #[serde(default)]
+    pub is_public: bool,
+    /// Why a drawn game was drawn: "stalemate", "threefold", "fifty_move",
+    /// "fivefold", "seventy_five_move", "insufficient_material" or "agreement"
+    #[serde(default)]
+    pub draw_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,3 +173,9 @@ pub struct GameInfo {
     pub move_deadline: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PastGameInfo {
+    pub game: GameInfo,
+    pub outcome_for_user: Outcome, // relative to whoever asked
+}
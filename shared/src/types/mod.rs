@@ -1,7 +1,11 @@
+pub mod challenge;
 pub mod game;
 pub mod r#move;
+pub mod tournament;
 pub mod user;
 
+pub use challenge::*;
 pub use game::*;
 pub use r#move::*;
+pub use tournament::*;
 pub use user::*;
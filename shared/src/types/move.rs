@@ -15,4 +15,13 @@ pub struct Move {
     pub position_before: String, // FEN
     pub position_after: String,  // FEN
     pub timestamp: DateTime<Utc>,
+    /// Seconds the player took over the move, if known
+    #[serde(default)]
+    pub time_taken_seconds: Option<i64>,
+    /// The player's comment on the move, if any
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// This move ended the game, by mate or a draw
+    #[serde(default)]
+    pub is_terminal: bool,
 }
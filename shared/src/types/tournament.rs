@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentFormat {
+    /// Everyone plays everyone once
+    #[default]
+    RoundRobin,
+}
+
+impl std::fmt::Display for TournamentFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TournamentFormat::RoundRobin => write!(f, "round_robin"),
+        }
+    }
+}
+
+impl FromStr for TournamentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round_robin" => Ok(TournamentFormat::RoundRobin),
+            _ => Err(format!("Invalid tournament format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub format: TournamentFormat,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One participant's line in the standings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TournamentStanding {
+    pub user_id: Uuid,
+    pub username: String,
+    pub points: f64, // 1 per win, 0.5 per draw
+    pub played: u32, // finished games only
+    pub won: u32,
+    pub drawn: u32,
+    pub lost: u32,
+}
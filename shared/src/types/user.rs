@@ -8,10 +8,22 @@ pub struct User {
     pub username: String,
     pub email: String,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub verified: bool, // false until the email address is confirmed
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct UserStats {
+    pub played: i64, // finished games, excluding imports and abandoned games
+    pub won: i64,
+    pub lost: i64,
+    pub drawn: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserProfile {
     pub id: Uuid,
     pub username: String,
+    #[serde(default)]
+    pub online: bool, // seen in the last few minutes
 }